@@ -0,0 +1,326 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::AppHandle;
+
+use super::{
+    clock,
+    convert::{to_pcm16, FormatConverter},
+    drift::{DriftCorrector, DriftStats},
+    filters::AudioFilterChain,
+    meter::LevelMeter,
+    mixer::Mixer,
+    AudioInput, AudioPacket, AudioSource, AudioSourceSettings, ENCODER_CHANNELS,
+    ENCODER_SAMPLE_RATE,
+};
+use crate::com::ComApartment;
+
+/// How far behind real time the mix runs, giving every source time to deliver
+const MIX_LATENCY: i64 = 1_000_000;
+const MIX_INTERVAL: Duration = Duration::from_millis(10);
+/// How often a lost input tries to reopen while no device is available
+const REOPEN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A chunk of audio in the encoder's format
+#[derive(Debug, Clone)]
+pub struct EncoderAudio {
+    /// Time of the first sample in 100ns units, on the same clock as video frame timestamps
+    pub timestamp: i64,
+    /// 16-bit little-endian stereo PCM at the encoder sample rate
+    pub data: Vec<u8>,
+}
+
+impl EncoderAudio {
+    /// The samples as interleaved 16-bit values
+    pub fn samples(&self) -> Vec<i16> {
+        self.data
+            .chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect()
+    }
+}
+
+/// Reads each audio input on its own thread and mixes them into encoder-ready chunks
+pub struct AudioCapture {
+    receiver: Receiver<EncoderAudio>,
+    /// Each input on its own, lined up with the mix, when separate tracks were asked for
+    track_receiver: Receiver<(usize, EncoderAudio)>,
+    stop_signal: Arc<AtomicBool>,
+    threads: Vec<thread::JoinHandle<()>>,
+    drift_stats: Arc<Mutex<Vec<DriftStats>>>,
+}
+
+/// Processing applied to one input in the mix thread, in order
+struct TrackChain {
+    converter: FormatConverter,
+    drift: DriftCorrector,
+    filters: AudioFilterChain,
+}
+
+impl AudioCapture {
+    /// Captures for a recording. Levels and silence warnings are emitted when `app` is given.
+    /// With `separate_tracks`, each input is also available on its own from `drain_tracks`.
+    pub fn start(inputs: Vec<AudioInput>, app: Option<AppHandle>, separate_tracks: bool) -> Self {
+        let meter = app.map(|app| LevelMeter::new(app, &inputs, true));
+        Self::spawn(inputs, meter, true, separate_tracks)
+    }
+
+    /// Captures only to emit levels, so sources can be checked before recording starts
+    pub fn monitor(inputs: Vec<AudioInput>, app: AppHandle) -> Self {
+        let meter = LevelMeter::new(app, &inputs, false);
+        Self::spawn(inputs, Some(meter), false, false)
+    }
+
+    fn spawn(
+        inputs: Vec<AudioInput>,
+        meter: Option<LevelMeter>,
+        encode: bool,
+        separate_tracks: bool,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (track_sender, track_receiver) = mpsc::channel();
+        let (packet_sender, packet_receiver) = mpsc::channel();
+        let stop_signal = Arc::new(AtomicBool::new(false));
+
+        let gains: Vec<f32> = inputs.iter().map(|input| input.gain.max(0.0)).collect();
+        let chains: Vec<TrackChain> = inputs
+            .iter()
+            .enumerate()
+            .map(|(track, input)| TrackChain {
+                converter: FormatConverter::new(),
+                drift: DriftCorrector::new(track, input.source.clone()),
+                filters: input.filters.build(ENCODER_SAMPLE_RATE, ENCODER_CHANNELS),
+            })
+            .collect();
+        let drift_stats = Arc::new(Mutex::new(Vec::new()));
+        let mut threads: Vec<_> = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(track, input)| {
+                let packet_sender = packet_sender.clone();
+                let stop_signal = Arc::clone(&stop_signal);
+                thread::spawn(move || read_input(track, input, packet_sender, &stop_signal))
+            })
+            .collect();
+        // The mixer finishes once every reader has dropped its sender
+        drop(packet_sender);
+
+        let output = MixOutput {
+            mix: encode.then_some(sender),
+            tracks: (encode && separate_tracks).then_some(track_sender),
+        };
+        threads.push(thread::spawn({
+            let stop_signal = Arc::clone(&stop_signal);
+            let drift_stats = Arc::clone(&drift_stats);
+            move || {
+                let chains = mix(gains, chains, packet_receiver, output, meter, &stop_signal);
+                *drift_stats.lock().unwrap() = chains
+                    .iter()
+                    .map(|chain| chain.drift.stats().clone())
+                    .collect();
+            }
+        }));
+
+        Self {
+            receiver,
+            track_receiver,
+            stop_signal,
+            threads,
+            drift_stats,
+        }
+    }
+
+    /// Stops every input and waits for the remaining audio to be mixed
+    pub fn stop(&mut self) {
+        self.stop_signal.store(true, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+
+    /// Audio mixed since the last call, oldest first
+    pub fn drain(&self) -> impl Iterator<Item = EncoderAudio> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Audio of each input since the last call, tagged with its index in the inputs. Every
+    /// track covers the same span as the mix, with silence wherever the input had none.
+    pub fn drain_tracks(&self) -> impl Iterator<Item = (usize, EncoderAudio)> + '_ {
+        self.track_receiver.try_iter()
+    }
+
+    /// Clock drift of each input, available once the capture has stopped
+    pub fn drift_stats(&self) -> Vec<DriftStats> {
+        self.drift_stats.lock().unwrap().clone()
+    }
+}
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Reads one input until stopped, tagging packets with its track index. If the device goes away
+/// the input switches to the default device, and keeps retrying while there is none; the mixer
+/// fills the gap with silence meanwhile.
+fn read_input(
+    track: usize,
+    input: AudioInput,
+    sender: Sender<(usize, AudioPacket)>,
+    stop_signal: &AtomicBool,
+) {
+    // Declared first so every COM object is released before COM is torn down
+    let _com = ComApartment::enter();
+
+    while !stop_signal.load(Ordering::Relaxed) {
+        let Some(mut source) = open_with_fallback(&input.source) else {
+            thread::sleep(REOPEN_INTERVAL);
+            continue;
+        };
+
+        loop {
+            if stop_signal.load(Ordering::Relaxed) {
+                return;
+            }
+            match source.read() {
+                Ok(Some(packet)) => {
+                    if sender.send((track, packet)).is_err() {
+                        return;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("Audio capture error, reopening source: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Opens the configured source, or the default device of the same kind if that fails
+fn open_with_fallback(settings: &AudioSourceSettings) -> Option<Box<dyn AudioSource>> {
+    let candidates = std::iter::once(settings.clone()).chain(settings.fallback());
+    for candidate in candidates {
+        match candidate.open() {
+            Ok(source) => {
+                let format = source.format();
+                println!(
+                    "Audio source {:?}: {} Hz, {} channels",
+                    candidate, format.sample_rate, format.channels
+                );
+                return Some(source);
+            }
+            Err(e) => eprintln!("Failed to open audio source {:?}: {}", candidate, e),
+        }
+    }
+    None
+}
+
+/// Where the mix thread sends its audio; unset outputs are skipped
+struct MixOutput {
+    mix: Option<Sender<EncoderAudio>>,
+    tracks: Option<Sender<(usize, EncoderAudio)>>,
+}
+
+/// Processes and mixes packets from every input, running `MIX_LATENCY` behind real time until
+/// the inputs stop. Without an output the mix is discarded and only the meter is fed.
+/// Returns the track chains so their drift figures can be reported.
+fn mix(
+    gains: Vec<f32>,
+    mut chains: Vec<TrackChain>,
+    receiver: Receiver<(usize, AudioPacket)>,
+    output: MixOutput,
+    mut meter: Option<LevelMeter>,
+    stop_signal: &AtomicBool,
+) -> Vec<TrackChain> {
+    let origin = clock::now();
+    let mut mixer = Mixer::new(origin, &gains);
+    // A single-track mixer per input lines each one up on the same frames as the mix
+    let mut track_mixers: Vec<Mixer> = match output.tracks {
+        Some(_) => gains
+            .iter()
+            .map(|&gain| Mixer::new(origin, &[gain]))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut push = |mixer: &mut Mixer,
+                    track_mixers: &mut [Mixer],
+                    meter: &mut Option<LevelMeter>,
+                    (track, packet): (usize, AudioPacket)| {
+        let chain = &mut chains[track];
+        let samples = chain.converter.convert(&packet);
+        let (timestamp, mut samples) = chain.drift.correct(packet.timestamp, &samples);
+        chain.filters.process(&mut samples);
+        // Metered as it arrives rather than when mixed, so the meter doesn't lag
+        if let Some(meter) = meter.as_mut() {
+            meter.measure(track, &samples, gains[track]);
+        }
+        mixer.push(track, timestamp, &samples);
+        if let Some(track_mixer) = track_mixers.get_mut(track) {
+            track_mixer.push(0, timestamp, &samples);
+        }
+    };
+
+    loop {
+        let disconnected = match receiver.recv_timeout(MIX_INTERVAL) {
+            Ok(packet) => {
+                push(&mut mixer, &mut track_mixers, &mut meter, packet);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        for packet in receiver.try_iter() {
+            push(&mut mixer, &mut track_mixers, &mut meter, packet);
+        }
+        if let Some(meter) = meter.as_mut() {
+            meter.tick();
+        }
+
+        // Once stopped, flush whatever the inputs delivered instead of waiting for real time
+        let until = if disconnected || stop_signal.load(Ordering::Relaxed) {
+            mixer.end()
+        } else {
+            clock::now() - MIX_LATENCY
+        };
+        if let Some((timestamp, samples)) = mixer.mix_until(until) {
+            if let Some(output) = &output.mix {
+                let chunk = EncoderAudio {
+                    timestamp,
+                    data: to_pcm16(&samples),
+                };
+                if output.send(chunk).is_err() {
+                    break;
+                }
+            }
+        }
+        if let Some(output) = &output.tracks {
+            for (track, track_mixer) in track_mixers.iter_mut().enumerate() {
+                if let Some((timestamp, samples)) = track_mixer.mix_until(until) {
+                    let chunk = EncoderAudio {
+                        timestamp,
+                        data: to_pcm16(&samples),
+                    };
+                    // Losing the separate tracks shouldn't stop the mix
+                    let _ = output.send((track, chunk));
+                }
+            }
+        }
+
+        if disconnected {
+            break;
+        }
+    }
+
+    chains
+}
@@ -0,0 +1,20 @@
+use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+
+/// Current time in 100ns units on the performance counter clock, which is the clock both
+/// capture frame timestamps and WASAPI packet positions are based on
+pub fn now() -> i64 {
+    let mut counter = 0i64;
+    let mut frequency = 0i64;
+    unsafe {
+        let _ = QueryPerformanceCounter(&mut counter);
+        let _ = QueryPerformanceFrequency(&mut frequency);
+    }
+    if frequency == 0 {
+        return 0;
+    }
+
+    // Split to avoid overflowing on machines that have been up for a long time
+    let seconds = counter / frequency;
+    let remainder = counter % frequency;
+    seconds * 10_000_000 + remainder * 10_000_000 / frequency
+}
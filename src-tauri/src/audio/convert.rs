@@ -0,0 +1,128 @@
+use super::{AudioFormat, AudioPacket, ENCODER_CHANNELS, ENCODER_SAMPLE_RATE};
+
+/// Linear-interpolating sample rate converter for interleaved audio that keeps its position
+/// between packets, so consecutive packets join without clicks
+pub struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    channels: usize,
+    /// Extra speed-up on top of the rate conversion, used for drift correction
+    ratio: f64,
+    /// Read position relative to `previous`, in input frames
+    position: f64,
+    /// Last input frame of the previous packet
+    previous: Option<Vec<f32>>,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32, channels: u16) -> Self {
+        Self {
+            from_rate,
+            to_rate,
+            channels: channels.max(1) as usize,
+            ratio: 1.0,
+            position: 0.0,
+            previous: None,
+        }
+    }
+
+    /// Produces `ratio` times as many output frames as the rates alone would, e.g. 1.0001 to
+    /// stretch the audio by 100 ppm. Takes effect from the next packet.
+    pub fn set_ratio(&mut self, ratio: f64) {
+        self.ratio = ratio;
+    }
+
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        // Once interpolating, keep going so the carried-over frame isn't lost
+        if self.from_rate == self.to_rate && self.ratio == 1.0 && self.previous.is_none() {
+            return input.to_vec();
+        }
+
+        let channels = self.channels;
+        let mut frames: Vec<f32> = self.previous.take().unwrap_or_default();
+        frames.extend_from_slice(input);
+        let frame_count = frames.len() / channels;
+        if frame_count < 2 {
+            self.previous = Some(frames);
+            return Vec::new();
+        }
+
+        let step = self.from_rate as f64 / self.to_rate as f64 / self.ratio;
+        let mut output = Vec::with_capacity(((frame_count as f64 / step) as usize + 1) * channels);
+        while self.position + 1.0 < frame_count as f64 {
+            let index = self.position as usize;
+            let fraction = (self.position - index as f64) as f32;
+            let (current, next) = (index * channels, (index + 1) * channels);
+            for channel in 0..channels {
+                let a = frames[current + channel];
+                let b = frames[next + channel];
+                output.push(a + (b - a) * fraction);
+            }
+            self.position += step;
+        }
+
+        // Carry the last frame over so the next packet interpolates from it
+        self.position -= (frame_count - 1) as f64;
+        self.previous = Some(frames[(frame_count - 1) * channels..].to_vec());
+        output
+    }
+}
+
+/// Maps interleaved audio with any channel count to stereo
+pub fn to_stereo(samples: &[f32], channels: u16) -> Vec<f32> {
+    match channels {
+        0 => Vec::new(),
+        1 => samples
+            .iter()
+            .flat_map(|&sample| [sample, sample])
+            .collect(),
+        2 => samples.to_vec(),
+        // Surround layouts start with front left and front right
+        _ => samples
+            .chunks_exact(channels as usize)
+            .flat_map(|frame| [frame[0], frame[1]])
+            .collect(),
+    }
+}
+
+/// Converts float samples to little-endian 16-bit PCM
+pub fn to_pcm16(samples: &[f32]) -> Vec<u8> {
+    samples
+        .iter()
+        .flat_map(|&sample| ((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+        .collect()
+}
+
+/// Converts packets of any format to the encoder's sample rate and channel layout
+#[derive(Default)]
+pub struct FormatConverter {
+    resampler: Option<(AudioFormat, Resampler)>,
+}
+
+impl FormatConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns interleaved stereo float samples at the encoder sample rate
+    pub fn convert(&mut self, packet: &AudioPacket) -> Vec<f32> {
+        if self
+            .resampler
+            .as_ref()
+            .is_none_or(|(format, _)| *format != packet.format)
+        {
+            self.resampler = Some((
+                packet.format,
+                Resampler::new(
+                    packet.format.sample_rate,
+                    ENCODER_SAMPLE_RATE,
+                    ENCODER_CHANNELS,
+                ),
+            ));
+        }
+
+        let stereo = to_stereo(&packet.samples, packet.format.channels);
+        let (_, resampler) = self.resampler.as_mut().unwrap();
+        resampler.process(&stereo)
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, ffi::c_void};
+
+use serde::Serialize;
+use windows::{
+    core::BSTR,
+    Win32::{
+        Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+        Media::Audio::{
+            eCapture, eConsole, eRender, EDataFlow, IAudioClient, IMMDevice, IMMDeviceEnumerator,
+            MMDeviceEnumerator, DEVICE_STATE_ACTIVE,
+        },
+        System::Com::{CoCreateInstance, CoTaskMemFree, CLSCTX_ALL, STGM_READ},
+    },
+};
+
+use super::AudioError;
+use crate::com::ComApartment;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioDeviceKind {
+    Output,
+    Input,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AudioDevice {
+    /// Endpoint ID, stable across enumerations and reboots
+    pub id: String,
+    pub name: String,
+    pub kind: AudioDeviceKind,
+    pub is_default: bool,
+    /// Shared-mode mix format, which is what gets captured; zero if it couldn't be read
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// Mix formats by endpoint ID. Reading one means activating an audio client on the device,
+/// so the device watcher keeps them between listings.
+pub(super) type FormatCache = HashMap<String, (u32, u16)>;
+
+/// Lists the active output and input devices
+pub fn list_devices() -> Result<Vec<AudioDevice>, AudioError> {
+    let _com = ComApartment::enter();
+    list_with_cache(&device_enumerator()?, &mut FormatCache::new())
+}
+
+pub(super) fn device_enumerator() -> Result<IMMDeviceEnumerator, AudioError> {
+    unsafe { CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL) }
+        .map_err(AudioError::ListDevices)
+}
+
+/// Lists the active devices, only reading the mix format of devices missing from `formats`
+pub(super) fn list_with_cache(
+    enumerator: &IMMDeviceEnumerator,
+    formats: &mut FormatCache,
+) -> Result<Vec<AudioDevice>, AudioError> {
+    let mut devices = Vec::new();
+    for (flow, kind) in [
+        (eRender, AudioDeviceKind::Output),
+        (eCapture, AudioDeviceKind::Input),
+    ] {
+        devices.extend(list_flow(enumerator, flow, kind, formats)?);
+    }
+    Ok(devices)
+}
+
+fn list_flow(
+    enumerator: &IMMDeviceEnumerator,
+    flow: EDataFlow,
+    kind: AudioDeviceKind,
+    formats: &mut FormatCache,
+) -> Result<Vec<AudioDevice>, AudioError> {
+    // There may be no default device at all, e.g. with every microphone unplugged
+    let default_id = unsafe { enumerator.GetDefaultAudioEndpoint(flow, eConsole) }
+        .ok()
+        .and_then(|device| device_id(&device));
+
+    let collection = unsafe { enumerator.EnumAudioEndpoints(flow, DEVICE_STATE_ACTIVE) }
+        .map_err(AudioError::ListDevices)?;
+    let count = unsafe { collection.GetCount() }.map_err(AudioError::ListDevices)?;
+
+    let mut devices = Vec::new();
+    for i in 0..count {
+        let Ok(device) = (unsafe { collection.Item(i) }) else {
+            continue;
+        };
+        let (Some(id), Some(name)) = (device_id(&device), friendly_name(&device)) else {
+            continue;
+        };
+
+        // Formats that couldn't be read aren't cached, so they are tried again next time
+        let format = match formats.get(&id) {
+            Some(&format) => Some(format),
+            None => mix_format(&device).inspect(|&format| {
+                formats.insert(id.clone(), format);
+            }),
+        };
+        let (sample_rate, channels) = format.unwrap_or((0, 0));
+        devices.push(AudioDevice {
+            is_default: default_id.as_deref() == Some(id.as_str()),
+            id,
+            name,
+            kind,
+            sample_rate,
+            channels,
+        });
+    }
+    Ok(devices)
+}
+
+fn device_id(device: &IMMDevice) -> Option<String> {
+    unsafe {
+        let id = device.GetId().ok()?;
+        let string = id.to_string().ok();
+        CoTaskMemFree(Some(id.0 as *const c_void));
+        string
+    }
+}
+
+fn friendly_name(device: &IMMDevice) -> Option<String> {
+    unsafe {
+        let store = device.OpenPropertyStore(STGM_READ).ok()?;
+        let value = store.GetValue(&PKEY_Device_FriendlyName).ok()?;
+        BSTR::try_from(&value).ok().map(|name| name.to_string())
+    }
+}
+
+/// Sample rate and channel count the audio engine mixes this device at
+fn mix_format(device: &IMMDevice) -> Option<(u32, u16)> {
+    unsafe {
+        let client: IAudioClient = device.Activate(CLSCTX_ALL, None).ok()?;
+        let format = client.GetMixFormat().ok()?;
+        let result = ((*format).nSamplesPerSec, (*format).nChannels);
+        CoTaskMemFree(Some(format as *const c_void));
+        Some(result)
+    }
+}
@@ -0,0 +1,166 @@
+use serde::Serialize;
+
+use super::{convert::Resampler, AudioSourceSettings, ENCODER_CHANNELS, ENCODER_SAMPLE_RATE};
+
+/// Weight of each new drift measurement; packet timestamps jitter by a few milliseconds
+const DRIFT_SMOOTHING: f64 = 0.02;
+/// Drift is worked off over about this many frames of audio
+const CORRECTION_FRAMES: f64 = ENCODER_SAMPLE_RATE as f64 * 2.0;
+/// Most the playback speed is changed by, 0.2%, which is far below audible pitch change
+const MAX_CORRECTION: f64 = 0.002;
+/// Drift beyond this, in 100ns units, is a glitch such as a device stall rather than clock skew.
+/// It is fixed at once by dropping or inserting samples.
+const RESYNC_THRESHOLD: i64 = 500_000;
+
+/// Drift figures for one audio input, reported in the recording stats
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DriftStats {
+    pub track: usize,
+    pub source: AudioSourceSettings,
+    /// Audio clock relative to the video clock, in ppm; positive means the device runs slow
+    pub skew_ppm: f64,
+    /// Largest smoothed drift seen before correction, in milliseconds
+    pub max_drift_ms: f64,
+    /// Smoothed drift at the end of the recording, in milliseconds
+    pub final_drift_ms: f64,
+    /// Frames inserted as silence or dropped when resynchronizing after a glitch
+    pub inserted_frames: u64,
+    pub dropped_frames: u64,
+}
+
+/// Keeps an input's sample count in step with its packet timestamps, which come from the same
+/// clock as video frames. Small skew is corrected by resampling; large jumps are snapped by
+/// dropping or inserting samples.
+pub struct DriftCorrector {
+    resampler: Resampler,
+    /// Timestamp of the first sample since the last resync, and the frames taken and given since
+    origin: Option<i64>,
+    output_frames: i64,
+    input_frames: i64,
+    drift: f64,
+    stats: DriftStats,
+}
+
+impl DriftCorrector {
+    pub fn new(track: usize, source: AudioSourceSettings) -> Self {
+        Self {
+            resampler: Resampler::new(ENCODER_SAMPLE_RATE, ENCODER_SAMPLE_RATE, ENCODER_CHANNELS),
+            origin: None,
+            output_frames: 0,
+            input_frames: 0,
+            drift: 0.0,
+            stats: DriftStats {
+                track,
+                source,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn timeline(&self, frames: i64) -> i64 {
+        self.origin.unwrap_or(0) + frames * 10_000_000 / ENCODER_SAMPLE_RATE as i64
+    }
+
+    /// Takes encoder-format samples stamped at `timestamp` and returns them corrected, with the
+    /// timestamp they should be mixed at
+    pub fn correct(&mut self, timestamp: i64, samples: &[f32]) -> (i64, Vec<f32>) {
+        let channels = ENCODER_CHANNELS as usize;
+        self.origin.get_or_insert(timestamp);
+
+        let expected = self.timeline(self.output_frames);
+        let measured = timestamp - expected;
+        if measured.abs() > RESYNC_THRESHOLD {
+            let frames =
+                (measured.unsigned_abs() as i64 * ENCODER_SAMPLE_RATE as i64 / 10_000_000) as u64;
+            if measured > 0 {
+                self.stats.inserted_frames += frames;
+            } else {
+                self.stats.dropped_frames += frames;
+            }
+            eprintln!(
+                "Audio track {} jumped {:.1}ms, resynchronizing",
+                self.stats.track,
+                measured as f64 / 10_000.0
+            );
+            // Restart the timeline at the packet; the mixer fills the gap or trims the overlap
+            self.origin = Some(timestamp);
+            self.output_frames = 0;
+            self.input_frames = 0;
+            self.drift = 0.0;
+        } else {
+            self.drift += (measured as f64 - self.drift) * DRIFT_SMOOTHING;
+        }
+
+        let drift_frames = self.drift * ENCODER_SAMPLE_RATE as f64 / 10_000_000.0;
+        let ratio = 1.0 + (drift_frames / CORRECTION_FRAMES).clamp(-MAX_CORRECTION, MAX_CORRECTION);
+        self.resampler.set_ratio(ratio);
+
+        // Frames the device delivered against the time that passed, since the last resync
+        let elapsed = (timestamp - self.origin.unwrap_or(timestamp)) as f64 / 10_000_000.0;
+        if elapsed > 1.0 {
+            let nominal = elapsed * ENCODER_SAMPLE_RATE as f64;
+            self.stats.skew_ppm = (nominal - self.input_frames as f64) / nominal * 1_000_000.0;
+        }
+        let drift_ms = self.drift / 10_000.0;
+        self.stats.max_drift_ms = self.stats.max_drift_ms.max(drift_ms.abs());
+        self.stats.final_drift_ms = drift_ms;
+
+        let output_timestamp = self.timeline(self.output_frames);
+        let output = self.resampler.process(samples);
+        self.output_frames += (output.len() / channels) as i64;
+        self.input_frames += (samples.len() / channels) as i64;
+
+        (output_timestamp, output)
+    }
+
+    pub fn stats(&self) -> &DriftStats {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One video frame at 60 fps, in 100ns units
+    const VIDEO_FRAME: i64 = 10_000_000 / 60;
+    const PACKET_FRAMES: usize = 480;
+
+    /// Feeds two minutes of packets from a device whose clock is off by `skew_ppm`, with a
+    /// millisecond of timestamp jitter. Returns the corrector's skew estimate and the furthest
+    /// any packet was placed from the time it was really captured.
+    fn run_skewed(skew_ppm: f64) -> (f64, i64) {
+        let mut corrector = DriftCorrector::new(0, AudioSourceSettings::default());
+        let samples = vec![0.0; PACKET_FRAMES * ENCODER_CHANNELS as usize];
+        let packet_secs = PACKET_FRAMES as f64 / ENCODER_SAMPLE_RATE as f64;
+        let start = 1_000_000_000;
+
+        let mut seed = 1u32;
+        let mut worst = 0;
+        for packet in 0..(120.0 / packet_secs) as usize {
+            // A device running slow takes longer than nominal to fill each packet
+            let captured_at =
+                start + (packet as f64 * packet_secs * (1.0 + skew_ppm / 1e6) * 1e7) as i64;
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let jitter = (seed >> 16) as i64 % 20_001 - 10_000;
+
+            let (timestamp, _) = corrector.correct(captured_at + jitter, &samples);
+            worst = worst.max((timestamp - captured_at).abs());
+        }
+        (corrector.stats().skew_ppm, worst)
+    }
+
+    #[test]
+    fn follows_a_slow_device_clock() {
+        let (skew_ppm, worst) = run_skewed(500.0);
+        assert!((skew_ppm - 500.0).abs() < 20.0, "estimated {skew_ppm} ppm");
+        assert!(worst < VIDEO_FRAME, "off by {}ms", worst / 10_000);
+    }
+
+    #[test]
+    fn follows_a_fast_device_clock() {
+        let (skew_ppm, worst) = run_skewed(-500.0);
+        assert!((skew_ppm + 500.0).abs() < 20.0, "estimated {skew_ppm} ppm");
+        assert!(worst < VIDEO_FRAME, "off by {}ms", worst / 10_000);
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use super::{db_to_linear, linear_to_db, time_constant, AudioFilter};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressorSettings {
+    /// Level in dBFS above which the signal is compressed
+    pub threshold_db: f32,
+    /// Input dB above the threshold per output dB, e.g. 4.0 for 4:1
+    pub ratio: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    /// Gain in dB applied after compression to bring the level back up
+    pub makeup_db: f32,
+    /// Peak ceiling in dBFS enforced after the makeup gain
+    pub limit_db: f32,
+}
+
+impl Default for CompressorSettings {
+    fn default() -> Self {
+        Self {
+            threshold_db: -20.0,
+            ratio: 4.0,
+            attack_ms: 5.0,
+            release_ms: 150.0,
+            makeup_db: 6.0,
+            limit_db: -1.0,
+        }
+    }
+}
+
+/// Feed-forward compressor followed by a peak limiter, with both channels sharing one gain
+pub struct Compressor {
+    settings: CompressorSettings,
+    channels: usize,
+    attack: f32,
+    release: f32,
+    /// Current gain reduction of the compressor in dB, always >= 0
+    reduction_db: f32,
+    ceiling: f32,
+    limiter_gain: f32,
+}
+
+impl Compressor {
+    pub fn new(settings: CompressorSettings, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            attack: time_constant(settings.attack_ms, sample_rate),
+            release: time_constant(settings.release_ms, sample_rate),
+            reduction_db: 0.0,
+            ceiling: db_to_linear(settings.limit_db.min(0.0)),
+            limiter_gain: 1.0,
+            settings,
+        }
+    }
+
+    /// Gain reduction in dB the static curve asks for at `level_db`
+    fn target_reduction(&self, level_db: f32) -> f32 {
+        let over = level_db - self.settings.threshold_db;
+        if over <= 0.0 {
+            return 0.0;
+        }
+        over * (1.0 - 1.0 / self.settings.ratio.max(1.0))
+    }
+}
+
+impl AudioFilter for Compressor {
+    fn process(&mut self, samples: &mut [f32]) {
+        let makeup = db_to_linear(self.settings.makeup_db);
+
+        for frame in samples.chunks_mut(self.channels) {
+            let peak = frame
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+
+            let target = self.target_reduction(linear_to_db(peak));
+            let coefficient = if target > self.reduction_db {
+                self.attack
+            } else {
+                self.release
+            };
+            self.reduction_db = target + (self.reduction_db - target) * coefficient;
+            let gain = db_to_linear(-self.reduction_db) * makeup;
+
+            // The limiter reacts instantly to overs and recovers at the release rate
+            let output_peak = peak * gain * self.limiter_gain;
+            if output_peak > self.ceiling {
+                self.limiter_gain = self.ceiling / (peak * gain);
+            } else {
+                self.limiter_gain = 1.0 + (self.limiter_gain - 1.0) * self.release;
+            }
+
+            for sample in frame.iter_mut() {
+                *sample *= gain * self.limiter_gain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{test_peak, test_tone};
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    #[test]
+    fn reduces_level_above_threshold_by_ratio() {
+        let settings = CompressorSettings {
+            threshold_db: -20.0,
+            ratio: 4.0,
+            makeup_db: 0.0,
+            limit_db: 0.0,
+            ..Default::default()
+        };
+        let mut compressor = Compressor::new(settings, RATE, 2);
+        let mut samples = test_tone(db_to_linear(-8.0), 2.0, RATE);
+        compressor.process(&mut samples);
+
+        // 12 dB over the threshold at 4:1 leaves 3 dB over it
+        let output_db = linear_to_db(test_peak(&samples[RATE as usize..]));
+        assert!((output_db - -17.0).abs() < 1.0, "{output_db} dBFS");
+    }
+
+    #[test]
+    fn leaves_level_below_threshold() {
+        let mut compressor = Compressor::new(
+            CompressorSettings {
+                makeup_db: 0.0,
+                ..Default::default()
+            },
+            RATE,
+            2,
+        );
+        let input = test_tone(db_to_linear(-30.0), 1.0, RATE);
+        let mut samples = input.clone();
+        compressor.process(&mut samples);
+        assert_eq!(samples, input);
+    }
+
+    #[test]
+    fn limiter_keeps_peaks_under_the_ceiling() {
+        let settings = CompressorSettings {
+            makeup_db: 20.0,
+            limit_db: -1.0,
+            ..Default::default()
+        };
+        let ceiling = db_to_linear(settings.limit_db);
+        let mut compressor = Compressor::new(settings, RATE, 2);
+        // A quiet stretch lets the limiter recover before the next loud one
+        for amplitude in [0.9, 0.01, 0.5, 0.05, 0.9] {
+            let mut samples = test_tone(amplitude, 0.5, RATE);
+            compressor.process(&mut samples);
+            assert!(test_peak(&samples) <= ceiling * 1.0001);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use super::{db_to_linear, time_constant, AudioFilter};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoiseGateSettings {
+    /// Level in dBFS above which the gate opens
+    pub threshold_db: f32,
+    /// Attenuation in dB applied while the gate is closed
+    pub range_db: f32,
+    pub attack_ms: f32,
+    /// How long the gate stays open after the level drops, so word endings aren't cut
+    pub hold_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for NoiseGateSettings {
+    fn default() -> Self {
+        Self {
+            threshold_db: -45.0,
+            range_db: -60.0,
+            attack_ms: 2.0,
+            hold_ms: 150.0,
+            release_ms: 120.0,
+        }
+    }
+}
+
+/// Mutes the source while its level stays below a threshold
+pub struct NoiseGate {
+    channels: usize,
+    threshold: f32,
+    /// Closes a little below the opening threshold so the gate doesn't chatter
+    close_threshold: f32,
+    floor: f32,
+    attack: f32,
+    release: f32,
+    hold_samples: usize,
+    hold_remaining: usize,
+    gain: f32,
+}
+
+impl NoiseGate {
+    pub fn new(settings: NoiseGateSettings, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            threshold: db_to_linear(settings.threshold_db),
+            close_threshold: db_to_linear(settings.threshold_db - 6.0),
+            floor: db_to_linear(settings.range_db.min(0.0)),
+            attack: time_constant(settings.attack_ms, sample_rate),
+            release: time_constant(settings.release_ms, sample_rate),
+            hold_samples: (settings.hold_ms.max(0.0) * 0.001 * sample_rate as f32) as usize,
+            hold_remaining: 0,
+            gain: db_to_linear(settings.range_db.min(0.0)),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.hold_remaining > 0
+    }
+}
+
+impl AudioFilter for NoiseGate {
+    fn process(&mut self, samples: &mut [f32]) {
+        for frame in samples.chunks_mut(self.channels) {
+            let level = frame
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+            let threshold = if self.is_open() {
+                self.close_threshold
+            } else {
+                self.threshold
+            };
+
+            if level >= threshold {
+                self.hold_remaining = self.hold_samples.max(1);
+            } else {
+                self.hold_remaining = self.hold_remaining.saturating_sub(1);
+            }
+
+            let (target, coefficient) = if self.is_open() {
+                (1.0, self.attack)
+            } else {
+                (self.floor, self.release)
+            };
+            self.gain = target + (self.gain - target) * coefficient;
+
+            for sample in frame.iter_mut() {
+                *sample *= self.gain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{linear_to_db, test_peak, test_tone};
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    #[test]
+    fn attenuates_quiet_input() {
+        let mut gate = NoiseGate::new(NoiseGateSettings::default(), RATE, 2);
+        let mut samples = test_tone(db_to_linear(-60.0), 1.0, RATE);
+        gate.process(&mut samples);
+        // -60 dBFS less the gate's 60 dB range
+        assert!(linear_to_db(test_peak(&samples)) < -119.0);
+    }
+
+    #[test]
+    fn passes_loud_input() {
+        let mut gate = NoiseGate::new(NoiseGateSettings::default(), RATE, 2);
+        let input = test_tone(0.5, 1.0, RATE);
+        let mut samples = input.clone();
+        gate.process(&mut samples);
+        // Past the attack the tone comes through unchanged
+        let settled = RATE as usize / 10 * 2;
+        assert!((test_peak(&samples[settled..]) - test_peak(&input[settled..])).abs() < 1e-3);
+    }
+
+    #[test]
+    fn holds_open_after_the_level_drops() {
+        let settings = NoiseGateSettings::default();
+        let mut gate = NoiseGate::new(settings.clone(), RATE, 2);
+        gate.process(&mut test_tone(0.5, 0.5, RATE));
+
+        let quiet = test_tone(db_to_linear(-70.0), 1.0, RATE);
+        let mut samples = quiet.clone();
+        gate.process(&mut samples);
+        let at = |ms: f32| (ms * 0.001 * RATE as f32) as usize * 2;
+
+        let held = at(0.0)..at(settings.hold_ms - 10.0);
+        assert!(test_peak(&samples[held.clone()]) / test_peak(&quiet[held]) > 0.99);
+        let released = at(settings.hold_ms + 5.0 * settings.release_ms)..;
+        assert!(test_peak(&samples[released.clone()]) / test_peak(&quiet[released]) < 0.01);
+    }
+}
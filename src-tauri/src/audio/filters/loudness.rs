@@ -0,0 +1,424 @@
+use serde::{Deserialize, Serialize};
+
+use super::{db_to_linear, time_constant, AudioFilter};
+
+/// Gating block length and step from ITU-R BS.1770, in milliseconds
+const BLOCK_MS: u32 = 400;
+const STEP_MS: u32 = 100;
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+/// Width of a loudness histogram bin in LU
+const BIN_LU: f64 = 0.1;
+/// Bins from the absolute gate up to +10 LUFS; anything louder goes in the top bin
+const BINS: usize = 800;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoudnessSettings {
+    /// Integrated loudness to aim for in LUFS; EBU R128 broadcast is -23, streaming is usually -16
+    pub target_lufs: f32,
+    /// Most gain in dB the normalizer will add or remove
+    pub max_gain_db: f32,
+    /// Peak ceiling in dBFS the normalized audio is kept under
+    pub peak_db: f32,
+}
+
+impl Default for LoudnessSettings {
+    fn default() -> Self {
+        Self {
+            target_lufs: -16.0,
+            max_gain_db: 20.0,
+            peak_db: -1.0,
+        }
+    }
+}
+
+/// Second-order IIR section in direct form I
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// The BS.1770 K-weighting filter: a high shelf modelling the head followed by a high pass,
+/// designed for any sample rate from the reference analog parameters
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = sample_rate as f64;
+
+    let shelf = {
+        let (f0, gain_db, q) = (
+            1_681.974_450_955_533,
+            3.999_843_853_973_347,
+            0.707_175_236_955_419_6,
+        );
+        let k = (std::f64::consts::PI * f0 / rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        let a0 = 1.0 + k / q + k * k;
+        Biquad::new(
+            [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    };
+
+    let high_pass = {
+        let (f0, q) = (38.135_470_876_024_44, 0.500_327_037_323_877_3);
+        let k = (std::f64::consts::PI * f0 / rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Biquad::new(
+            [1.0, -2.0, 1.0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    };
+
+    [shelf, high_pass]
+}
+
+fn lufs(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.max(1e-12).log10()
+}
+
+/// Histogram bin holding blocks of `loudness` LUFS, which must be above the absolute gate
+fn bin_index(loudness: f64) -> usize {
+    (((loudness - ABSOLUTE_GATE) / BIN_LU).max(0.0) as usize).min(BINS - 1)
+}
+
+/// Gating blocks that fell into one histogram bin
+#[derive(Debug, Clone, Copy, Default)]
+struct Bin {
+    count: u64,
+    /// Sum of the blocks' mean squares
+    energy: f64,
+}
+
+/// Measures integrated loudness following ITU-R BS.1770 / EBU R128, for the left and right channels.
+/// Blocks are kept as a loudness histogram rather than one by one, so memory and the cost of
+/// `integrated` stay the same however long the recording runs. The relative gate is then
+/// applied to whole bins, placing it within 0.1 LU of the exact value.
+pub struct LoudnessMeter {
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    step_frames: usize,
+    /// Sum of squared K-weighted samples over every channel in the current step
+    step_energy: f64,
+    step_count: usize,
+    /// Mean square of the most recent steps, four of which make a gating block
+    steps: Vec<f64>,
+    block_count: usize,
+    /// Blocks above the absolute gate, by loudness
+    histogram: Vec<Bin>,
+    /// Every block above the absolute gate, for the relative gate
+    above_absolute: Bin,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        Self {
+            channels,
+            filters: (0..channels).map(|_| k_weighting(sample_rate)).collect(),
+            step_frames: (sample_rate * STEP_MS / 1000) as usize,
+            step_energy: 0.0,
+            step_count: 0,
+            steps: Vec::new(),
+            block_count: 0,
+            histogram: vec![Bin::default(); BINS],
+            above_absolute: Bin::default(),
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        let steps_per_block = (BLOCK_MS / STEP_MS) as usize;
+
+        for frame in samples.chunks(self.channels) {
+            for (sample, filters) in frame.iter().zip(self.filters.iter_mut()) {
+                let weighted = filters
+                    .iter_mut()
+                    .fold(*sample as f64, |value, filter| filter.process(value));
+                self.step_energy += weighted * weighted;
+            }
+            self.step_count += 1;
+
+            if self.step_count == self.step_frames {
+                self.steps.push(self.step_energy / self.step_frames as f64);
+                self.step_energy = 0.0;
+                self.step_count = 0;
+
+                if self.steps.len() >= steps_per_block {
+                    let recent = &self.steps[self.steps.len() - steps_per_block..];
+                    self.add_block(recent.iter().sum::<f64>() / steps_per_block as f64);
+                    self.steps.remove(0);
+                }
+            }
+        }
+    }
+
+    fn add_block(&mut self, mean_square: f64) {
+        self.block_count += 1;
+        let loudness = lufs(mean_square);
+        if loudness <= ABSOLUTE_GATE {
+            return;
+        }
+        for bin in [
+            &mut self.histogram[bin_index(loudness)],
+            &mut self.above_absolute,
+        ] {
+            bin.count += 1;
+            bin.energy += mean_square;
+        }
+    }
+
+    /// Number of gating blocks measured so far
+    pub fn blocks(&self) -> usize {
+        self.block_count
+    }
+
+    /// Gated integrated loudness in LUFS, or `None` until a block louder than the
+    /// absolute gate has been measured
+    pub fn integrated(&self) -> Option<f32> {
+        if self.above_absolute.count == 0 {
+            return None;
+        }
+
+        let relative_gate =
+            lufs(self.above_absolute.energy / self.above_absolute.count as f64) + RELATIVE_GATE;
+        let gated =
+            self.histogram[bin_index(relative_gate)..]
+                .iter()
+                .fold(Bin::default(), |total, bin| Bin {
+                    count: total.count + bin.count,
+                    energy: total.energy + bin.energy,
+                });
+        if gated.count == 0 {
+            return None;
+        }
+        Some(lufs(gated.energy / gated.count as f64) as f32)
+    }
+}
+
+/// Gain in dB that moves `integrated` to the target, within the allowed range
+fn normalization_gain(settings: &LoudnessSettings, integrated: f32) -> f32 {
+    let max_gain = settings.max_gain_db.abs();
+    (settings.target_lufs - integrated).clamp(-max_gain, max_gain)
+}
+
+/// Measures a whole recording for post-pass normalization: its integrated loudness and peak
+pub struct LoudnessPass {
+    meter: LoudnessMeter,
+    peak: f32,
+}
+
+impl LoudnessPass {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            meter: LoudnessMeter::new(sample_rate, channels),
+            peak: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        self.meter.push(samples);
+        self.peak = samples
+            .iter()
+            .fold(self.peak, |peak, sample| peak.max(sample.abs()));
+    }
+
+    /// Gain in dB that brings everything pushed so far to the target, lowered if needed to
+    /// keep peaks under the ceiling, or `None` if the audio is too quiet to measure
+    pub fn gain_db(&self, settings: &LoudnessSettings) -> Option<f32> {
+        let integrated = self.meter.integrated()?;
+        let mut gain_db = normalization_gain(settings, integrated);
+        if self.peak > 0.0 {
+            let headroom_db = settings.peak_db.min(0.0) - 20.0 * self.peak.log10();
+            gain_db = gain_db.min(headroom_db);
+        }
+        Some(gain_db)
+    }
+}
+
+/// Post-pass normalization of a whole recording: measures its integrated loudness and scales it
+/// to the target, lowering the gain if needed to keep peaks under the ceiling.
+/// Returns the gain applied in dB, or `None` if the audio is too quiet to measure.
+pub fn normalize_loudness(
+    samples: &mut [f32],
+    sample_rate: u32,
+    channels: u16,
+    settings: &LoudnessSettings,
+) -> Option<f32> {
+    let mut pass = LoudnessPass::new(sample_rate, channels);
+    pass.push(samples);
+    let gain_db = pass.gain_db(settings)?;
+
+    let gain = db_to_linear(gain_db);
+    samples.iter_mut().for_each(|sample| *sample *= gain);
+    Some(gain_db)
+}
+
+/// Streaming normalization for audio that is encoded as it is captured. The gain follows the
+/// integrated loudness measured so far and changes slowly, so it settles once the source has
+/// been measured for a while rather than matching the target exactly from the first second.
+pub struct LoudnessNormalizer {
+    settings: LoudnessSettings,
+    channels: usize,
+    meter: LoudnessMeter,
+    ceiling: f32,
+    smoothing: f32,
+    /// Block count the target was last computed at, so it's only recomputed on new blocks
+    measured_blocks: usize,
+    target: f32,
+    gain: f32,
+}
+
+impl LoudnessNormalizer {
+    pub fn new(settings: LoudnessSettings, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            meter: LoudnessMeter::new(sample_rate, channels),
+            ceiling: db_to_linear(settings.peak_db.min(0.0)),
+            smoothing: time_constant(3000.0, sample_rate),
+            measured_blocks: 0,
+            target: 1.0,
+            gain: 1.0,
+            settings,
+        }
+    }
+}
+
+impl AudioFilter for LoudnessNormalizer {
+    fn process(&mut self, samples: &mut [f32]) {
+        self.meter.push(samples);
+        if self.meter.blocks() != self.measured_blocks {
+            self.measured_blocks = self.meter.blocks();
+            if let Some(integrated) = self.meter.integrated() {
+                self.target = db_to_linear(normalization_gain(&self.settings, integrated));
+            }
+        }
+
+        for frame in samples.chunks_mut(self.channels) {
+            self.gain = self.target + (self.gain - self.target) * self.smoothing;
+            let peak = frame
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+            // Without lookahead the ceiling can only be held by capping this frame's gain
+            let gain = if peak * self.gain > self.ceiling {
+                self.ceiling / peak
+            } else {
+                self.gain
+            };
+            for sample in frame.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{test_peak, test_tone};
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    #[test]
+    fn measures_a_sine() {
+        // A full-scale 1 kHz sine in both channels reads 0 LUFS
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        meter.push(&test_tone(0.1, 5.0, RATE));
+        let integrated = meter.integrated().unwrap();
+        assert!((integrated - -20.0).abs() < 0.2, "{integrated} LUFS");
+    }
+
+    #[test]
+    fn silence_has_no_loudness() {
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        meter.push(&vec![0.0; RATE as usize * 2 * 2]);
+        assert!(meter.blocks() > 0);
+        assert_eq!(meter.integrated(), None);
+    }
+
+    #[test]
+    fn relative_gate_leaves_out_quiet_passages() {
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        meter.push(&test_tone(0.1, 10.0, RATE));
+        meter.push(&test_tone(db_to_linear(-50.0), 10.0, RATE));
+        let integrated = meter.integrated().unwrap();
+        assert!((integrated - -20.0).abs() < 0.3, "{integrated} LUFS");
+    }
+
+    #[test]
+    fn measures_in_pieces_the_same_as_at_once() {
+        let samples = test_tone(0.3, 3.0, RATE);
+        let mut whole = LoudnessMeter::new(RATE, 2);
+        whole.push(&samples);
+        let mut pieces = LoudnessMeter::new(RATE, 2);
+        for chunk in samples.chunks(960) {
+            pieces.push(chunk);
+        }
+        assert_eq!(whole.blocks(), pieces.blocks());
+        assert_eq!(whole.integrated(), pieces.integrated());
+    }
+
+    #[test]
+    fn post_pass_reaches_the_target() {
+        let settings = LoudnessSettings::default();
+        let mut samples = test_tone(0.1, 5.0, RATE);
+        let gain_db = normalize_loudness(&mut samples, RATE, 2, &settings).unwrap();
+        assert!((gain_db - 4.0).abs() < 0.2, "{gain_db} dB");
+
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        meter.push(&samples);
+        let integrated = meter.integrated().unwrap();
+        assert!(
+            (integrated - settings.target_lufs).abs() < 0.2,
+            "{integrated} LUFS"
+        );
+    }
+
+    #[test]
+    fn post_pass_keeps_peaks_under_the_ceiling() {
+        let settings = LoudnessSettings {
+            target_lufs: 0.0,
+            ..LoudnessSettings::default()
+        };
+        let mut samples = test_tone(0.5, 5.0, RATE);
+        // The tone wants 6 dB but only has 5 before its peaks reach -1 dBFS
+        let gain_db = normalize_loudness(&mut samples, RATE, 2, &settings).unwrap();
+        assert!((gain_db - 5.0).abs() < 0.1, "{gain_db} dB");
+        assert!(test_peak(&samples) <= db_to_linear(settings.peak_db) + 1e-4);
+    }
+
+    #[test]
+    fn post_pass_leaves_silence_alone() {
+        let mut samples = vec![0.0; RATE as usize * 2 * 2];
+        assert_eq!(
+            normalize_loudness(&mut samples, RATE, 2, &LoudnessSettings::default()),
+            None
+        );
+    }
+}
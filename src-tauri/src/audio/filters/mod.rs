@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+mod compressor;
+mod gate;
+mod loudness;
+mod suppressor;
+
+pub use compressor::{Compressor, CompressorSettings};
+pub use gate::{NoiseGate, NoiseGateSettings};
+pub use loudness::{
+    normalize_loudness, LoudnessMeter, LoudnessNormalizer, LoudnessPass, LoudnessSettings,
+};
+pub use suppressor::{NoiseSuppressionSettings, NoiseSuppressor};
+
+/// A stage that modifies interleaved float samples in place. Filters only depend on the samples
+/// they are given, so they run the same on live capture and on recorded buffers.
+pub trait AudioFilter: Send {
+    fn process(&mut self, samples: &mut [f32]);
+}
+
+/// Filters applied to one source. Stages left as `None` are skipped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioFilterSettings {
+    pub noise_gate: Option<NoiseGateSettings>,
+    pub noise_suppression: Option<NoiseSuppressionSettings>,
+    pub compressor: Option<CompressorSettings>,
+    pub loudness: Option<LoudnessSettings>,
+}
+
+impl AudioFilterSettings {
+    /// Builds the chain in a fixed order: gate, suppressor, compressor, then loudness
+    pub fn build(&self, sample_rate: u32, channels: u16) -> AudioFilterChain {
+        let mut chain = AudioFilterChain::new();
+        if let Some(settings) = &self.noise_gate {
+            chain.push(NoiseGate::new(settings.clone(), sample_rate, channels));
+        }
+        if let Some(settings) = &self.noise_suppression {
+            chain.push(NoiseSuppressor::new(settings.clone(), channels));
+        }
+        if let Some(settings) = &self.compressor {
+            chain.push(Compressor::new(settings.clone(), sample_rate, channels));
+        }
+        if let Some(settings) = &self.loudness {
+            chain.push(LoudnessNormalizer::new(
+                settings.clone(),
+                sample_rate,
+                channels,
+            ));
+        }
+        chain
+    }
+}
+
+/// Ordered list of filters applied to a source's samples
+#[derive(Default)]
+pub struct AudioFilterChain {
+    filters: Vec<Box<dyn AudioFilter>>,
+}
+
+impl AudioFilterChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a filter to the end of the chain
+    pub fn push<F: AudioFilter + 'static>(&mut self, filter: F) {
+        self.filters.push(Box::new(filter));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Runs every filter over the samples in order
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for filter in self.filters.iter_mut() {
+            filter.process(samples);
+        }
+    }
+}
+
+pub(crate) fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+pub(crate) fn linear_to_db(value: f32) -> f32 {
+    20.0 * value.max(1e-9).log10()
+}
+
+/// One-pole smoothing coefficient that covers most of the distance in `ms`
+pub(crate) fn time_constant(ms: f32, sample_rate: u32) -> f32 {
+    let samples = ms.max(0.01) * 0.001 * sample_rate as f32;
+    (-1.0 / samples).exp()
+}
+
+/// Stereo sine at 1 kHz with a peak of `amplitude`, for running the filters over in tests
+#[cfg(test)]
+fn test_tone(amplitude: f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+    let frames = (seconds * sample_rate as f32) as usize;
+    (0..frames)
+        .flat_map(|i| {
+            let phase = 2.0 * std::f32::consts::PI * 1000.0 * i as f32 / sample_rate as f32;
+            let sample = amplitude * phase.sin();
+            [sample, sample]
+        })
+        .collect()
+}
+
+/// Largest absolute sample
+#[cfg(test)]
+fn test_peak(samples: &[f32]) -> f32 {
+    samples
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+}
@@ -0,0 +1,291 @@
+use std::{collections::VecDeque, f32::consts::PI};
+
+use serde::{Deserialize, Serialize};
+
+use super::{db_to_linear, AudioFilter};
+
+/// Samples per analysis frame, about 10ms at 48 kHz
+const FRAME: usize = 512;
+/// Frames overlap by half
+const HOP: usize = FRAME / 2;
+/// How far the tracked minimum may rise per frame, so it follows a noise floor that gets louder
+const NOISE_RISE: f32 = 1.005;
+/// Frames within this factor of the minimum are treated as noise when averaging the noise level
+const NOISE_RATIO: f32 = 4.0;
+const NOISE_SMOOTHING: f32 = 0.95;
+/// Smoothing of the per-bin power and gain between frames, which keeps "musical noise" down
+const POWER_SMOOTHING: f32 = 0.7;
+const GAIN_SMOOTHING: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoiseSuppressionSettings {
+    /// Most attenuation in dB applied to a frequency that is all noise
+    pub reduction_db: f32,
+    /// How much of the noise estimate is subtracted; above 1.0 removes more noise and more voice
+    pub strength: f32,
+}
+
+impl Default for NoiseSuppressionSettings {
+    fn default() -> Self {
+        Self {
+            reduction_db: 18.0,
+            strength: 1.5,
+        }
+    }
+}
+
+/// Spectral subtraction against a noise floor learned from the quietest moments of each frequency.
+/// Suited to steady noise such as fans and hum. Delays the audio by one frame.
+pub struct NoiseSuppressor {
+    channels: Vec<ChannelSuppressor>,
+}
+
+impl NoiseSuppressor {
+    pub fn new(settings: NoiseSuppressionSettings, channels: u16) -> Self {
+        // Square root of a periodic Hann window, applied before and after the transform so
+        // overlapping frames add back up to the input
+        let window: Vec<f32> = (0..FRAME)
+            .map(|i| (0.5 - 0.5 * (2.0 * PI * i as f32 / FRAME as f32).cos()).sqrt())
+            .collect();
+
+        Self {
+            channels: (0..channels.max(1))
+                .map(|_| ChannelSuppressor::new(&settings, window.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl AudioFilter for NoiseSuppressor {
+    fn process(&mut self, samples: &mut [f32]) {
+        let channels = self.channels.len();
+        for frame in samples.chunks_mut(channels) {
+            for (sample, channel) in frame.iter_mut().zip(self.channels.iter_mut()) {
+                *sample = channel.push(*sample);
+            }
+        }
+    }
+}
+
+struct ChannelSuppressor {
+    floor: f32,
+    strength: f32,
+    window: Vec<f32>,
+    /// The last `FRAME` input samples
+    history: Vec<f32>,
+    pending: Vec<f32>,
+    overlap: Vec<f32>,
+    output: VecDeque<f32>,
+    power: Vec<f32>,
+    minimum: Option<Vec<f32>>,
+    /// Average power of the frames judged to be noise, which the minimum alone underestimates
+    noise: Vec<f32>,
+    gains: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+impl ChannelSuppressor {
+    fn new(settings: &NoiseSuppressionSettings, window: Vec<f32>) -> Self {
+        let bins = FRAME / 2 + 1;
+        Self {
+            floor: db_to_linear(-settings.reduction_db.abs()),
+            strength: settings.strength.max(0.0),
+            window,
+            history: vec![0.0; FRAME],
+            pending: Vec::with_capacity(HOP),
+            overlap: vec![0.0; FRAME],
+            output: std::iter::repeat_n(0.0, HOP).collect(),
+            power: vec![0.0; bins],
+            minimum: None,
+            noise: vec![0.0; bins],
+            gains: vec![1.0; bins],
+            re: vec![0.0; FRAME],
+            im: vec![0.0; FRAME],
+        }
+    }
+
+    /// Takes one input sample and returns one output sample, `FRAME` samples later
+    fn push(&mut self, sample: f32) -> f32 {
+        self.pending.push(sample);
+        if self.pending.len() == HOP {
+            self.history.copy_within(HOP.., 0);
+            self.history[FRAME - HOP..].copy_from_slice(&self.pending);
+            self.pending.clear();
+            self.process_frame();
+        }
+        self.output.pop_front().unwrap_or(0.0)
+    }
+
+    fn process_frame(&mut self) {
+        for i in 0..FRAME {
+            self.re[i] = self.history[i] * self.window[i];
+            self.im[i] = 0.0;
+        }
+        fft(&mut self.re, &mut self.im, false);
+
+        let bins = self.power.len();
+        let first_frame = self.minimum.is_none();
+        for bin in 0..bins {
+            let power = self.re[bin] * self.re[bin] + self.im[bin] * self.im[bin];
+            self.power[bin] = if first_frame {
+                power
+            } else {
+                POWER_SMOOTHING * self.power[bin] + (1.0 - POWER_SMOOTHING) * power
+            };
+        }
+
+        // Track the minimum of each bin, creeping upwards so a louder noise floor is learned,
+        // and average the frames close to it. Both start from the first frame, which is usually
+        // taken before anyone speaks.
+        if first_frame {
+            self.noise.copy_from_slice(&self.power);
+        }
+        let minimum = self.minimum.get_or_insert_with(|| self.power.clone());
+        for ((minimum, noise), &power) in minimum
+            .iter_mut()
+            .zip(self.noise.iter_mut())
+            .zip(self.power.iter())
+        {
+            *minimum = (*minimum * NOISE_RISE).min(power);
+            if power <= *minimum * NOISE_RATIO {
+                *noise = NOISE_SMOOTHING * *noise + (1.0 - NOISE_SMOOTHING) * power;
+            }
+        }
+
+        for bin in 0..bins {
+            let power = self.power[bin].max(1e-12);
+            let gain = (1.0 - self.strength * self.noise[bin] / power)
+                .max(0.0)
+                .sqrt();
+            let gain = gain.max(self.floor);
+            self.gains[bin] = GAIN_SMOOTHING * self.gains[bin] + (1.0 - GAIN_SMOOTHING) * gain;
+        }
+
+        // Apply the same gain to the mirrored half so the result stays real
+        for i in 0..FRAME {
+            let bin = if i < bins { i } else { FRAME - i };
+            self.re[i] *= self.gains[bin];
+            self.im[i] *= self.gains[bin];
+        }
+        fft(&mut self.re, &mut self.im, true);
+
+        for i in 0..FRAME {
+            self.overlap[i] += self.re[i] * self.window[i];
+        }
+        self.output.extend(self.overlap[..HOP].iter().copied());
+        self.overlap.copy_within(HOP.., 0);
+        self.overlap[FRAME - HOP..].fill(0.0);
+    }
+}
+
+/// In-place radix-2 FFT. The inverse is scaled by 1/n.
+fn fft(re: &mut [f32], im: &mut [f32], inverse: bool) {
+    let n = re.len();
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f32;
+        re.iter_mut().for_each(|value| *value *= scale);
+        im.iter_mut().for_each(|value| *value *= scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_tone;
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// Stereo white noise with a peak of `amplitude`, the same on every run
+    fn noise(amplitude: f32, seconds: f32) -> Vec<f32> {
+        let mut state = 0x2545_f491_u32;
+        (0..(seconds * RATE as f32) as usize * 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                amplitude * (state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// The last second of the input, where the suppressor has long since settled
+    fn last_second(samples: &[f32]) -> &[f32] {
+        &samples[samples.len() - RATE as usize * 2..]
+    }
+
+    #[test]
+    fn attenuates_steady_noise() {
+        let mut suppressor = NoiseSuppressor::new(NoiseSuppressionSettings::default(), 2);
+        let input = noise(0.05, 3.0);
+        let mut samples = input.clone();
+        suppressor.process(&mut samples);
+        // At least 6 dB quieter
+        assert!(rms(last_second(&samples)) < rms(last_second(&input)) * 0.5);
+    }
+
+    #[test]
+    fn keeps_a_tone_over_the_noise() {
+        let mut suppressor = NoiseSuppressor::new(NoiseSuppressionSettings::default(), 2);
+        // Learns the noise floor first
+        suppressor.process(&mut noise(0.05, 2.0));
+
+        let tone = test_tone(0.3, 2.0, RATE);
+        let mut samples: Vec<f32> = noise(0.05, 2.0)
+            .iter()
+            .zip(&tone)
+            .map(|(noise, tone)| noise + tone)
+            .collect();
+        suppressor.process(&mut samples);
+        let kept = rms(last_second(&samples)) / rms(last_second(&tone));
+        assert!(kept > 0.9 && kept < 1.1, "kept {}", kept);
+    }
+
+    #[test]
+    fn passes_silence_through() {
+        let mut suppressor = NoiseSuppressor::new(NoiseSuppressionSettings::default(), 2);
+        let mut samples = vec![0.0; RATE as usize * 2];
+        suppressor.process(&mut samples);
+        assert!(samples.iter().all(|sample| *sample == 0.0));
+    }
+}
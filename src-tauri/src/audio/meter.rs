@@ -0,0 +1,142 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use super::{AudioInput, AudioSourceSettings};
+
+/// How often `audio-levels` is emitted
+const METER_INTERVAL: Duration = Duration::from_millis(50);
+/// Peak level below which a source counts as silent, about -60 dBFS
+const SILENCE_LEVEL: f32 = 0.001;
+
+/// Level of one source over the last metering interval
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceLevel {
+    pub track: usize,
+    pub source: AudioSourceSettings,
+    /// Linear peak amplitude, 0.0–1.0
+    pub peak: f32,
+    /// Linear RMS amplitude, 0.0–1.0
+    pub rms: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SilenceWarning {
+    pub track: usize,
+    pub source: AudioSourceSettings,
+    pub silent_secs: u64,
+}
+
+struct TrackMeter {
+    source: AudioSourceSettings,
+    silence_warning: Option<Duration>,
+    peak: f32,
+    sum_squares: f64,
+    count: usize,
+    last_sound: Instant,
+    warned: bool,
+}
+
+/// Measures each source as its audio arrives and emits `audio-levels` at about 20 Hz.
+/// While recording it also emits `audio-silence-warning` once a source has been silent for
+/// its configured time.
+pub struct LevelMeter {
+    app: AppHandle,
+    tracks: Vec<TrackMeter>,
+    last_emit: Instant,
+    warn_on_silence: bool,
+}
+
+impl LevelMeter {
+    pub fn new(app: AppHandle, inputs: &[AudioInput], warn_on_silence: bool) -> Self {
+        let now = Instant::now();
+        Self {
+            app,
+            tracks: inputs
+                .iter()
+                .map(|input| TrackMeter {
+                    source: input.source.clone(),
+                    silence_warning: (input.silence_warning_secs > 0)
+                        .then(|| Duration::from_secs(input.silence_warning_secs)),
+                    peak: 0.0,
+                    sum_squares: 0.0,
+                    count: 0,
+                    last_sound: now,
+                    warned: false,
+                })
+                .collect(),
+            last_emit: now,
+            warn_on_silence,
+        }
+    }
+
+    /// Adds samples from a track to the current interval, as heard after `gain`
+    pub fn measure(&mut self, track: usize, samples: &[f32], gain: f32) {
+        let Some(meter) = self.tracks.get_mut(track) else {
+            return;
+        };
+        for sample in samples.iter().map(|sample| sample * gain) {
+            meter.peak = meter.peak.max(sample.abs());
+            meter.sum_squares += (sample * sample) as f64;
+        }
+        meter.count += samples.len();
+    }
+
+    /// Emits the levels once an interval has passed and checks for silent sources
+    pub fn tick(&mut self) {
+        if self.last_emit.elapsed() < METER_INTERVAL {
+            return;
+        }
+        self.last_emit = Instant::now();
+
+        let mut levels = Vec::with_capacity(self.tracks.len());
+        for (track, meter) in self.tracks.iter_mut().enumerate() {
+            let rms = if meter.count > 0 {
+                (meter.sum_squares / meter.count as f64).sqrt() as f32
+            } else {
+                0.0
+            };
+            levels.push(SourceLevel {
+                track,
+                source: meter.source.clone(),
+                peak: meter.peak.min(1.0),
+                rms: rms.min(1.0),
+            });
+
+            // Loopback sends nothing at all while nothing plays, which counts as silence too
+            if meter.peak >= SILENCE_LEVEL {
+                meter.last_sound = Instant::now();
+                meter.warned = false;
+            }
+            meter.peak = 0.0;
+            meter.sum_squares = 0.0;
+            meter.count = 0;
+
+            let Some(limit) = meter.silence_warning else {
+                continue;
+            };
+            let silent_for = meter.last_sound.elapsed();
+            if self.warn_on_silence && !meter.warned && silent_for >= limit {
+                meter.warned = true;
+                eprintln!(
+                    "Audio source {:?} has been silent for {}s",
+                    meter.source,
+                    silent_for.as_secs()
+                );
+                let warning = SilenceWarning {
+                    track,
+                    source: meter.source.clone(),
+                    silent_secs: silent_for.as_secs(),
+                };
+                if let Err(e) = self.app.emit("audio-silence-warning", &warning) {
+                    eprintln!("Failed to emit audio-silence-warning event: {}", e);
+                }
+            }
+        }
+
+        if let Err(e) = self.app.emit("audio-levels", &levels) {
+            eprintln!("Failed to emit audio-levels event: {}", e);
+        }
+    }
+}
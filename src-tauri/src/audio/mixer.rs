@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+
+use super::{ENCODER_CHANNELS, ENCODER_SAMPLE_RATE};
+
+/// Timing differences up to this many frames between consecutive packets are treated as jitter
+/// rather than gaps or overlaps
+const JITTER_FRAMES: i64 = ENCODER_SAMPLE_RATE as i64 / 200;
+
+struct Track {
+    gain: f32,
+    /// Frame index of the first buffered frame
+    start: i64,
+    buffer: VecDeque<f32>,
+}
+
+/// Lines up several streams in the encoder format by timestamp and sums them with per-track gain.
+/// Gaps in a stream are mixed as silence.
+pub struct Mixer {
+    /// Timestamp of frame index zero, in 100ns units
+    origin: i64,
+    tracks: Vec<Track>,
+    /// Frame index up to which output has been produced
+    mixed_until: i64,
+}
+
+impl Mixer {
+    pub fn new(origin: i64, gains: &[f32]) -> Self {
+        Self {
+            origin,
+            tracks: gains
+                .iter()
+                .map(|&gain| Track {
+                    gain,
+                    start: 0,
+                    buffer: VecDeque::new(),
+                })
+                .collect(),
+            mixed_until: 0,
+        }
+    }
+
+    fn frame_index(&self, timestamp: i64) -> i64 {
+        (timestamp - self.origin) * ENCODER_SAMPLE_RATE as i64 / 10_000_000
+    }
+
+    fn timestamp(&self, frame_index: i64) -> i64 {
+        self.origin + frame_index * 10_000_000 / ENCODER_SAMPLE_RATE as i64
+    }
+
+    /// Queues interleaved encoder-format samples for a track
+    pub fn push(&mut self, track: usize, timestamp: i64, samples: &[f32]) {
+        let channels = ENCODER_CHANNELS as usize;
+        let mut index = self.frame_index(timestamp);
+        let mut samples = samples;
+
+        // Anything whose time has already been mixed arrived too late to be used
+        if index < self.mixed_until {
+            let skip = ((self.mixed_until - index) as usize).min(samples.len() / channels);
+            samples = &samples[skip * channels..];
+            index += skip as i64;
+        }
+        if samples.is_empty() {
+            return;
+        }
+
+        let Some(track) = self.tracks.get_mut(track) else {
+            return;
+        };
+        if track.buffer.is_empty() {
+            track.start = index;
+            track.buffer.extend(samples);
+            return;
+        }
+
+        let end = track.start + (track.buffer.len() / channels) as i64;
+        if index > end + JITTER_FRAMES {
+            let gap = (index - end) as usize * channels;
+            track.buffer.extend(std::iter::repeat_n(0.0, gap));
+        } else if index < end - JITTER_FRAMES {
+            let overlap = ((end - index) as usize).min(samples.len() / channels);
+            samples = &samples[overlap * channels..];
+        }
+        track.buffer.extend(samples);
+    }
+
+    /// Timestamp just past the newest queued sample on any track
+    pub fn end(&self) -> i64 {
+        let channels = ENCODER_CHANNELS as usize;
+        let end = self
+            .tracks
+            .iter()
+            .filter(|track| !track.buffer.is_empty())
+            .map(|track| track.start + (track.buffer.len() / channels) as i64)
+            .max()
+            .unwrap_or(self.mixed_until);
+        self.timestamp(end.max(self.mixed_until))
+    }
+
+    /// Mixes everything up to `until`, returning the start timestamp and the mixed samples.
+    /// Tracks without audio for part of that span contribute silence there.
+    pub fn mix_until(&mut self, until: i64) -> Option<(i64, Vec<f32>)> {
+        let channels = ENCODER_CHANNELS as usize;
+        let target = self.frame_index(until);
+        if target <= self.mixed_until {
+            return None;
+        }
+
+        let frames = (target - self.mixed_until) as usize;
+        let mut output = vec![0.0f32; frames * channels];
+        for track in self.tracks.iter_mut() {
+            if track.buffer.is_empty() {
+                continue;
+            }
+
+            let offset = (track.start - self.mixed_until).max(0) as usize;
+            if offset >= frames {
+                continue;
+            }
+            let take = (track.buffer.len() / channels).min(frames - offset);
+            for (out, sample) in output[offset * channels..]
+                .iter_mut()
+                .zip(track.buffer.drain(..take * channels))
+            {
+                *out += sample * track.gain;
+            }
+            track.start += take as i64;
+        }
+
+        let timestamp = self.timestamp(self.mixed_until);
+        self.mixed_until = target;
+        Some((timestamp, output))
+    }
+}
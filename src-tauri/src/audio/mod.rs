@@ -0,0 +1,174 @@
+mod capture;
+mod clock;
+mod convert;
+mod devices;
+mod drift;
+mod filters;
+mod meter;
+mod mixer;
+mod sine;
+mod wasapi;
+mod watcher;
+mod writer;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub use capture::{AudioCapture, EncoderAudio};
+pub use clock::now;
+pub use convert::{FormatConverter, Resampler};
+pub use devices::{list_devices, AudioDevice, AudioDeviceKind};
+pub use drift::{DriftCorrector, DriftStats};
+pub use filters::{
+    AudioFilter, AudioFilterChain, AudioFilterSettings, Compressor, CompressorSettings,
+    LoudnessMeter, LoudnessNormalizer, LoudnessPass, LoudnessSettings, NoiseGate,
+    NoiseGateSettings, NoiseSuppressionSettings, NoiseSuppressor,
+};
+pub use meter::{LevelMeter, SilenceWarning, SourceLevel};
+pub use mixer::Mixer;
+pub use sine::SineSource;
+pub use wasapi::{Endpoint, WasapiSource};
+pub use watcher::watch_devices;
+pub use writer::{
+    is_audio_file, open_normalized_writer, open_writer, AudioWriter, FlacWriter, NormalizingWriter,
+    OpusWriter, WavWriter,
+};
+
+/// Sample rate the encoder is configured with
+pub const ENCODER_SAMPLE_RATE: u32 = 48_000;
+/// Channel count the encoder is configured with
+pub const ENCODER_CHANNELS: u16 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl AudioFormat {
+    /// Length of `frames` sample frames in 100ns units
+    pub fn duration_of(&self, frames: usize) -> i64 {
+        frames as i64 * 10_000_000 / self.sample_rate as i64
+    }
+}
+
+/// Interleaved float samples, with the capture time of the first one
+#[derive(Debug, Clone)]
+pub struct AudioPacket {
+    /// Time of the first sample in 100ns units, on the same clock as video frame timestamps
+    pub timestamp: i64,
+    pub format: AudioFormat,
+    pub samples: Vec<f32>,
+}
+
+/// A producer of timestamped audio, opened and read on the capture thread
+pub trait AudioSource {
+    fn format(&self) -> AudioFormat;
+
+    /// Waits briefly for the next packet. Returns `Ok(None)` if nothing arrived in time.
+    fn read(&mut self) -> Result<Option<AudioPacket>, AudioError>;
+}
+
+/// Where an audio input comes from. Devices are the defaults when `device_id` is `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AudioSourceSettings {
+    /// Everything played on an output device
+    Loopback { device_id: Option<String> },
+    /// A microphone or other input device
+    Microphone { device_id: Option<String> },
+    /// A generated tone, for testing A/V sync without sound hardware
+    Sine { frequency: f32 },
+}
+
+impl Default for AudioSourceSettings {
+    fn default() -> Self {
+        AudioSourceSettings::Loopback { device_id: None }
+    }
+}
+
+impl AudioSourceSettings {
+    pub fn open(&self) -> Result<Box<dyn AudioSource>, AudioError> {
+        match self {
+            AudioSourceSettings::Loopback { device_id } => Ok(Box::new(WasapiSource::open(
+                Endpoint::Output,
+                device_id.as_deref(),
+            )?)),
+            AudioSourceSettings::Microphone { device_id } => Ok(Box::new(WasapiSource::open(
+                Endpoint::Input,
+                device_id.as_deref(),
+            )?)),
+            AudioSourceSettings::Sine { frequency } => Ok(Box::new(SineSource::new(*frequency))),
+        }
+    }
+
+    /// The same kind of source on the system default device, used when a chosen device is lost
+    pub fn fallback(&self) -> Option<AudioSourceSettings> {
+        match self {
+            AudioSourceSettings::Loopback { device_id: Some(_) } => {
+                Some(AudioSourceSettings::Loopback { device_id: None })
+            }
+            AudioSourceSettings::Microphone { device_id: Some(_) } => {
+                Some(AudioSourceSettings::Microphone { device_id: None })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// One source in the recording's audio mix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioInput {
+    pub source: AudioSourceSettings,
+    /// Linear gain applied before mixing; 1.0 leaves the level unchanged
+    pub gain: f32,
+    /// Processing applied to this input before it is mixed
+    pub filters: AudioFilterSettings,
+    /// Warn once the source has been silent this long while recording; 0 disables the warning
+    pub silence_warning_secs: u64,
+}
+
+impl Default for AudioInput {
+    fn default() -> Self {
+        Self {
+            source: AudioSourceSettings::default(),
+            gain: 1.0,
+            filters: AudioFilterSettings::default(),
+            silence_warning_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("failed to list audio devices: {0}")]
+    ListDevices(#[source] windows::core::Error),
+
+    #[error("failed to open audio device: {0}")]
+    OpenDevice(#[source] windows::core::Error),
+
+    #[error("audio capture failed: {0}")]
+    Capture(#[source] windows::core::Error),
+
+    #[error("unsupported audio format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("unsupported audio file type: {0}")]
+    UnsupportedFile(String),
+
+    #[error("failed to write audio file: {0}")]
+    Write(#[from] std::io::Error),
+
+    #[error("audio encoding failed: {0}")]
+    Encode(String),
+}
+
+impl Serialize for AudioError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
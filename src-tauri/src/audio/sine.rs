@@ -0,0 +1,67 @@
+use std::{f32::consts::TAU, thread, time::Duration};
+
+use super::{clock, AudioError, AudioFormat, AudioPacket, AudioSource};
+
+const FORMAT: AudioFormat = AudioFormat {
+    sample_rate: 48_000,
+    channels: 2,
+};
+const PACKET_INTERVAL: Duration = Duration::from_millis(10);
+const AMPLITUDE: f32 = 0.25;
+/// Length of the beep at the start of every second, in sample frames
+const BEEP_FRAMES: u64 = FORMAT.sample_rate as u64 / 10;
+
+/// Generates a short beep at the start of every second in real time, so the offset between
+/// audio and a clock in the video can be measured
+pub struct SineSource {
+    frequency: f32,
+    started_at: i64,
+    frames_written: u64,
+}
+
+impl SineSource {
+    pub fn new(frequency: f32) -> Self {
+        Self {
+            frequency: frequency.max(1.0),
+            started_at: clock::now(),
+            frames_written: 0,
+        }
+    }
+}
+
+impl AudioSource for SineSource {
+    fn format(&self) -> AudioFormat {
+        FORMAT
+    }
+
+    fn read(&mut self) -> Result<Option<AudioPacket>, AudioError> {
+        thread::sleep(PACKET_INTERVAL);
+
+        // Produce exactly as many frames as real time has advanced
+        let elapsed = (clock::now() - self.started_at).max(0) as u64;
+        let due = elapsed * FORMAT.sample_rate as u64 / 10_000_000;
+        if due <= self.frames_written {
+            return Ok(None);
+        }
+
+        let first = self.frames_written;
+        let mut samples = Vec::with_capacity((due - first) as usize * FORMAT.channels as usize);
+        for frame in first..due {
+            let position = frame % FORMAT.sample_rate as u64;
+            let sample = if position < BEEP_FRAMES {
+                let t = frame as f32 / FORMAT.sample_rate as f32;
+                (t * self.frequency * TAU).sin() * AMPLITUDE
+            } else {
+                0.0
+            };
+            samples.extend(std::iter::repeat_n(sample, FORMAT.channels as usize));
+        }
+        self.frames_written = due;
+
+        Ok(Some(AudioPacket {
+            timestamp: self.started_at + FORMAT.duration_of(first as usize),
+            format: FORMAT,
+            samples,
+        }))
+    }
+}
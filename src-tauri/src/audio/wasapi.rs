@@ -0,0 +1,199 @@
+use std::{ffi::c_void, ptr, thread, time::Duration};
+
+use windows::{
+    core::HSTRING,
+    Win32::{
+        Media::Audio::{
+            eCapture, eConsole, eRender, IAudioCaptureClient, IAudioClient, IMMDeviceEnumerator,
+            MMDeviceEnumerator, AUDCLNT_BUFFERFLAGS_SILENT, AUDCLNT_SHAREMODE_SHARED,
+            AUDCLNT_STREAMFLAGS_LOOPBACK, WAVEFORMATEX,
+        },
+        System::Com::{CoCreateInstance, CoTaskMemFree, CLSCTX_ALL},
+    },
+};
+
+use super::{AudioError, AudioFormat, AudioPacket, AudioSource};
+
+/// Size of the shared-mode buffer WASAPI keeps for us, in 100ns units
+const BUFFER_DURATION: i64 = 2_000_000;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Which side of the audio engine a source listens to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Everything played on an output device, through loopback
+    Output,
+    /// A microphone or other input device
+    Input,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SampleFormat {
+    F32,
+    I16,
+}
+
+/// Captures from an audio endpoint through WASAPI in shared mode
+pub struct WasapiSource {
+    client: IAudioClient,
+    capture: IAudioCaptureClient,
+    format: AudioFormat,
+    sample_format: SampleFormat,
+}
+
+impl WasapiSource {
+    /// Opens the endpoint with the given ID, or the default device of that kind.
+    /// COM must already be initialized on the calling thread.
+    pub fn open(endpoint: Endpoint, device_id: Option<&str>) -> Result<Self, AudioError> {
+        let flow = match endpoint {
+            Endpoint::Output => eRender,
+            Endpoint::Input => eCapture,
+        };
+        let stream_flags = match endpoint {
+            Endpoint::Output => AUDCLNT_STREAMFLAGS_LOOPBACK,
+            Endpoint::Input => 0,
+        };
+
+        unsafe {
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                    .map_err(AudioError::OpenDevice)?;
+            let device = match device_id {
+                Some(id) => enumerator.GetDevice(&HSTRING::from(id)),
+                None => enumerator.GetDefaultAudioEndpoint(flow, eConsole),
+            }
+            .map_err(AudioError::OpenDevice)?;
+
+            let client: IAudioClient = device
+                .Activate(CLSCTX_ALL, None)
+                .map_err(AudioError::OpenDevice)?;
+
+            // Shared mode has to use the engine's mix format, which is usually 32-bit float
+            let mix_format = client.GetMixFormat().map_err(AudioError::OpenDevice)?;
+            let parsed = parse_format(&*mix_format);
+            let initialized = client.Initialize(
+                AUDCLNT_SHAREMODE_SHARED,
+                stream_flags,
+                BUFFER_DURATION,
+                0,
+                mix_format,
+                None,
+            );
+            CoTaskMemFree(Some(mix_format as *const c_void));
+
+            let (format, sample_format) = parsed?;
+            initialized.map_err(AudioError::OpenDevice)?;
+
+            let capture: IAudioCaptureClient =
+                client.GetService().map_err(AudioError::OpenDevice)?;
+            client.Start().map_err(AudioError::OpenDevice)?;
+
+            Ok(Self {
+                client,
+                capture,
+                format,
+                sample_format,
+            })
+        }
+    }
+}
+
+impl AudioSource for WasapiSource {
+    fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    fn read(&mut self) -> Result<Option<AudioPacket>, AudioError> {
+        // Loopback delivers nothing while nothing plays; the mixer fills those gaps with silence
+        let pending = unsafe { self.capture.GetNextPacketSize() }.map_err(AudioError::Capture)?;
+        if pending == 0 {
+            thread::sleep(POLL_INTERVAL);
+            return Ok(None);
+        }
+
+        let mut data = ptr::null_mut();
+        let mut frames = 0u32;
+        let mut flags = 0u32;
+        let mut qpc_position = 0u64;
+        unsafe {
+            self.capture
+                .GetBuffer(
+                    &mut data,
+                    &mut frames,
+                    &mut flags,
+                    None,
+                    Some(&mut qpc_position as *mut _),
+                )
+                .map_err(AudioError::Capture)?;
+        }
+
+        let sample_count = frames as usize * self.format.channels as usize;
+        let samples = if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 || data.is_null() {
+            vec![0.0; sample_count]
+        } else {
+            match self.sample_format {
+                SampleFormat::F32 => unsafe {
+                    std::slice::from_raw_parts(data as *const f32, sample_count).to_vec()
+                },
+                SampleFormat::I16 => unsafe {
+                    std::slice::from_raw_parts(data as *const i16, sample_count)
+                        .iter()
+                        .map(|&sample| sample as f32 / i16::MAX as f32)
+                        .collect()
+                },
+            }
+        };
+        unsafe { self.capture.ReleaseBuffer(frames) }.map_err(AudioError::Capture)?;
+
+        Ok(Some(AudioPacket {
+            timestamp: qpc_position as i64,
+            format: self.format,
+            samples,
+        }))
+    }
+}
+
+impl Drop for WasapiSource {
+    fn drop(&mut self) {
+        let _ = unsafe { self.client.Stop() };
+    }
+}
+
+fn parse_format(format: &WAVEFORMATEX) -> Result<(AudioFormat, SampleFormat), AudioError> {
+    let tag = if format.wFormatTag == WAVE_FORMAT_EXTENSIBLE {
+        // The real tag is the first field of the sub-format GUID, which follows the
+        // WAVEFORMATEX header, the valid-bits union and the channel mask
+        unsafe {
+            ptr::read_unaligned(
+                (format as *const WAVEFORMATEX as *const u8)
+                    .add(std::mem::size_of::<WAVEFORMATEX>() + 6) as *const u32,
+            ) as u16
+        }
+    } else {
+        format.wFormatTag
+    };
+
+    let bits = format.wBitsPerSample;
+    let sample_format = match (tag, bits) {
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => SampleFormat::F32,
+        (WAVE_FORMAT_PCM, 16) => SampleFormat::I16,
+        _ => {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "format tag {}, {} bits per sample",
+                tag, bits
+            )))
+        }
+    };
+
+    Ok((
+        AudioFormat {
+            sample_rate: format.nSamplesPerSec,
+            channels: format.nChannels,
+        },
+        sample_format,
+    ))
+}
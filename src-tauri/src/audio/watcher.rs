@@ -0,0 +1,123 @@
+use std::{
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Emitter};
+use windows::{
+    core::{implement, Result as WinResult, PCWSTR},
+    Win32::{
+        Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+        Foundation::PROPERTYKEY,
+        Media::Audio::{
+            EDataFlow, ERole, IMMNotificationClient, IMMNotificationClient_Impl,
+            PKEY_AudioEngine_DeviceFormat, DEVICE_STATE,
+        },
+    },
+};
+
+use super::devices::{device_enumerator, list_with_cache, FormatCache};
+use crate::com::ComApartment;
+
+/// Plugging a device in sends a burst of notifications; they are handled together
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+/// Lists the devices again whenever Windows reports an endpoint change, for the lifetime of
+/// the app, and emits `audio-devices-changed` with the new list if it differs
+pub fn watch_devices(app: AppHandle) {
+    thread::spawn(move || {
+        // Declared first so every COM object is released before COM is torn down
+        let _com = ComApartment::enter();
+        let enumerator = match device_enumerator() {
+            Ok(enumerator) => enumerator,
+            Err(e) => {
+                eprintln!("Failed to watch audio devices: {}", e);
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let client: IMMNotificationClient = DeviceNotifier { sender }.into();
+        if let Err(e) = unsafe { enumerator.RegisterEndpointNotificationCallback(&client) } {
+            eprintln!("Failed to watch audio devices: {}", e);
+            return;
+        }
+
+        let mut formats = FormatCache::new();
+        let mut previous = list_with_cache(&enumerator, &mut formats).ok();
+        // The notifier holds the sender for as long as it stays registered, which is forever
+        while let Ok(change) = receiver.recv() {
+            thread::sleep(SETTLE_TIME);
+            for id in std::iter::once(change).chain(receiver.try_iter()).flatten() {
+                formats.remove(&id);
+            }
+
+            let devices = match list_with_cache(&enumerator, &mut formats) {
+                Ok(devices) => devices,
+                Err(e) => {
+                    eprintln!("Failed to list audio devices: {}", e);
+                    continue;
+                }
+            };
+            if previous.as_ref() == Some(&devices) {
+                continue;
+            }
+
+            println!("Audio devices changed");
+            if let Err(e) = app.emit("audio-devices-changed", &devices) {
+                eprintln!("Failed to emit audio-devices-changed event: {}", e);
+            }
+            previous = Some(devices);
+        }
+    });
+}
+
+/// Passes endpoint notifications to the watcher thread. Windows calls it on its own threads
+/// and expects it to return quickly, so it only sends the ID of the device whose cached
+/// format may be stale, or `None` when only a default changed.
+#[implement(IMMNotificationClient)]
+struct DeviceNotifier {
+    sender: Sender<Option<String>>,
+}
+
+impl DeviceNotifier {
+    fn send(&self, id: &PCWSTR) {
+        let _ = self.sender.send(unsafe { id.to_string() }.ok());
+    }
+}
+
+impl IMMNotificationClient_Impl for DeviceNotifier_Impl {
+    fn OnDeviceStateChanged(&self, id: &PCWSTR, _state: DEVICE_STATE) -> WinResult<()> {
+        self.send(id);
+        Ok(())
+    }
+
+    fn OnDeviceAdded(&self, id: &PCWSTR) -> WinResult<()> {
+        self.send(id);
+        Ok(())
+    }
+
+    fn OnDeviceRemoved(&self, id: &PCWSTR) -> WinResult<()> {
+        self.send(id);
+        Ok(())
+    }
+
+    fn OnDefaultDeviceChanged(
+        &self,
+        _flow: EDataFlow,
+        _role: ERole,
+        _id: &PCWSTR,
+    ) -> WinResult<()> {
+        let _ = self.sender.send(None);
+        Ok(())
+    }
+
+    fn OnPropertyValueChanged(&self, id: &PCWSTR, key: &PROPERTYKEY) -> WinResult<()> {
+        // Drivers change all sorts of properties; only these show up in the list
+        if *key == PKEY_AudioEngine_DeviceFormat || *key == PKEY_Device_FriendlyName {
+            self.send(id);
+        }
+        Ok(())
+    }
+}
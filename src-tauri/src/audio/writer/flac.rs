@@ -0,0 +1,589 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+};
+
+use super::AudioWriter;
+use crate::audio::{AudioError, ENCODER_CHANNELS, ENCODER_SAMPLE_RATE};
+
+/// Samples per channel in each FLAC frame
+const BLOCK_SIZE: usize = 4096;
+const BITS_PER_SAMPLE: u32 = 16;
+/// Highest Rice partition order tried; partitions must divide the block evenly
+const MAX_PARTITION_ORDER: u32 = 6;
+/// Largest 4-bit Rice parameter, since 15 is the escape code
+const MAX_RICE_PARAMETER: u32 = 14;
+
+/// Lossless FLAC using the fixed linear predictors and Rice-coded residuals.
+/// The STREAMINFO sample count is written once the length is known; the MD5 is left unset.
+pub struct FlacWriter {
+    file: BufWriter<File>,
+    channels: usize,
+    pending: Vec<i16>,
+    frame_number: u64,
+    total_samples: u64,
+}
+
+impl FlacWriter {
+    pub fn create(path: &str) -> Result<Self, AudioError> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(b"fLaC")?;
+        file.write_all(&stream_info(0))?;
+        Ok(Self {
+            file,
+            channels: ENCODER_CHANNELS as usize,
+            pending: Vec::new(),
+            frame_number: 0,
+            total_samples: 0,
+        })
+    }
+
+    fn write_frame(&mut self, samples: &[i16]) -> Result<(), AudioError> {
+        let frame = encode_frame(samples, self.channels, self.frame_number);
+        self.file.write_all(&frame)?;
+        self.frame_number += 1;
+        self.total_samples += (samples.len() / self.channels) as u64;
+        Ok(())
+    }
+}
+
+impl AudioWriter for FlacWriter {
+    fn write(&mut self, samples: &[i16]) -> Result<(), AudioError> {
+        self.pending.extend_from_slice(samples);
+        let frame_len = BLOCK_SIZE * self.channels;
+        while self.pending.len() >= frame_len {
+            let frame: Vec<i16> = self.pending.drain(..frame_len).collect();
+            self.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), AudioError> {
+        let remaining = std::mem::take(&mut self.pending);
+        let usable = remaining.len() - remaining.len() % self.channels;
+        if usable > 0 {
+            self.write_frame(&remaining[..usable])?;
+        }
+
+        // STREAMINFO follows the "fLaC" marker
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file.write_all(&stream_info(self.total_samples))?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// The STREAMINFO metadata block, with its header, as the only and last metadata block
+fn stream_info(total_samples: u64) -> Vec<u8> {
+    let mut bits = BitWriter::new();
+    // Last metadata block, type 0, 34 bytes long
+    bits.write(1, 1);
+    bits.write(0, 7);
+    bits.write(34, 24);
+    bits.write(BLOCK_SIZE as u64, 16);
+    bits.write(BLOCK_SIZE as u64, 16);
+    // Frame sizes unknown
+    bits.write(0, 24);
+    bits.write(0, 24);
+    bits.write(ENCODER_SAMPLE_RATE as u64, 20);
+    bits.write(ENCODER_CHANNELS as u64 - 1, 3);
+    bits.write(BITS_PER_SAMPLE as u64 - 1, 5);
+    bits.write(total_samples, 36);
+    // MD5 of the audio, all zero meaning not computed
+    for _ in 0..16 {
+        bits.write(0, 8);
+    }
+    bits.into_bytes()
+}
+
+fn encode_frame(samples: &[i16], channels: usize, frame_number: u64) -> Vec<u8> {
+    let block_size = samples.len() / channels;
+    let mut bits = BitWriter::new();
+
+    // Sync code and fixed block size strategy
+    bits.write(0b11_1111_1111_1110, 14);
+    bits.write(0, 1);
+    bits.write(0, 1);
+    // Block size stored as a 16-bit value at the end of the header
+    bits.write(0b0111, 4);
+    // 48 kHz
+    bits.write(0b1010, 4);
+    // Independent channels
+    bits.write(channels as u64 - 1, 4);
+    // 16 bits per sample
+    bits.write(0b100, 3);
+    bits.write(0, 1);
+    for byte in utf8_number(frame_number) {
+        bits.write(byte as u64, 8);
+    }
+    bits.write(block_size as u64 - 1, 16);
+    let crc = crc8(bits.bytes());
+    bits.write(crc as u64, 8);
+
+    for channel in 0..channels {
+        let channel_samples: Vec<i32> = samples
+            .iter()
+            .skip(channel)
+            .step_by(channels)
+            .map(|&sample| sample as i32)
+            .collect();
+        write_subframe(&mut bits, &channel_samples);
+    }
+
+    bits.align();
+    let crc = crc16(bits.bytes());
+    bits.write(crc as u64, 16);
+    bits.into_bytes()
+}
+
+fn write_subframe(bits: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|&sample| sample == samples[0]) {
+        bits.write(0, 8);
+        bits.write_signed(samples[0], BITS_PER_SAMPLE);
+        return;
+    }
+
+    // Pick the fixed predictor whose residuals are smallest
+    let best = (0..=4usize)
+        .filter(|&order| order < samples.len())
+        .map(|order| {
+            let residuals = fixed_residuals(samples, order);
+            let magnitude: u64 = residuals.iter().map(|r| r.unsigned_abs() as u64).sum();
+            (order, residuals, magnitude)
+        })
+        .min_by_key(|(_, _, magnitude)| *magnitude);
+
+    let encoded = best.and_then(|(order, residuals, _)| {
+        let (partition_order, parameters, size) = best_partitioning(&residuals, order);
+        let warm_up = order as u64 * BITS_PER_SAMPLE as u64;
+        let verbatim = samples.len() as u64 * BITS_PER_SAMPLE as u64;
+        // 6 bits of residual coding method and partition order
+        (warm_up + 6 + size < verbatim).then_some((order, residuals, partition_order, parameters))
+    });
+
+    match encoded {
+        Some((order, residuals, partition_order, parameters)) => {
+            bits.write(0, 1);
+            bits.write(0b001000 | order as u64, 6);
+            bits.write(0, 1);
+            for &sample in &samples[..order] {
+                bits.write_signed(sample, BITS_PER_SAMPLE);
+            }
+
+            // Rice coding with 4-bit parameters
+            bits.write(0, 2);
+            bits.write(partition_order as u64, 4);
+            let partitions = 1usize << partition_order;
+            let partition_len = samples.len() >> partition_order;
+            let mut start = 0;
+            for (partition, &parameter) in parameters.iter().enumerate().take(partitions) {
+                // The first partition is shorter by the warm-up samples
+                let len = if partition == 0 {
+                    partition_len - order
+                } else {
+                    partition_len
+                };
+                bits.write(parameter as u64, 4);
+                for &residual in &residuals[start..start + len] {
+                    bits.write_rice(residual, parameter);
+                }
+                start += len;
+            }
+        }
+        None => {
+            bits.write(0, 1);
+            bits.write(0b000001, 6);
+            bits.write(0, 1);
+            for &sample in samples {
+                bits.write_signed(sample, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+/// Residuals after the fixed polynomial predictor of the given order, skipping the warm-up
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i32> {
+    samples
+        .windows(order + 1)
+        .map(|window| {
+            let s = |back: usize| window[order - back];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// Rice parameter and bit cost for one partition
+fn best_parameter(residuals: &[i32]) -> (u32, u64) {
+    (0..=MAX_RICE_PARAMETER)
+        .map(|parameter| {
+            let size: u64 = residuals
+                .iter()
+                .map(|&residual| (zigzag(residual) >> parameter) as u64 + 1 + parameter as u64)
+                .sum();
+            (parameter, size)
+        })
+        .min_by_key(|(_, size)| *size)
+        .unwrap_or((0, 0))
+}
+
+/// Partition order, per-partition parameters and total residual size in bits.
+/// The first partition is shorter by the `order` warm-up samples.
+fn best_partitioning(residuals: &[i32], order: usize) -> (u32, Vec<u32>, u64) {
+    let block_size = residuals.len() + order;
+
+    let mut best: Option<(u32, Vec<u32>, u64)> = None;
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1usize << partition_order;
+        if block_size % partitions != 0 || block_size / partitions <= order {
+            break;
+        }
+        let partition_len = block_size / partitions;
+
+        let mut parameters = Vec::with_capacity(partitions);
+        let mut size = 0u64;
+        let mut start = 0;
+        for partition in 0..partitions {
+            let len = if partition == 0 {
+                partition_len - order
+            } else {
+                partition_len
+            };
+            let (parameter, bits) = best_parameter(&residuals[start..start + len]);
+            parameters.push(parameter);
+            size += 4 + bits;
+            start += len;
+        }
+
+        if best
+            .as_ref()
+            .is_none_or(|(_, _, best_size)| size < *best_size)
+        {
+            best = Some((partition_order, parameters, size));
+        }
+    }
+    best.unwrap_or((0, vec![0], u64::MAX / 2))
+}
+
+/// Frame numbers are stored with the UTF-8 variable length scheme
+fn utf8_number(value: u64) -> Vec<u8> {
+    if value < 0x80 {
+        return vec![value as u8];
+    }
+    let mut continuation = Vec::new();
+    let mut value = value;
+    // Each continuation byte holds 6 bits; the lead byte holds what is left
+    loop {
+        continuation.push(0x80 | (value & 0x3F) as u8);
+        value >>= 6;
+        let count = continuation.len() + 1;
+        if value < (1 << (7 - count)) {
+            let lead = !(0xFFu8 >> count) | value as u8;
+            let mut bytes = vec![lead];
+            bytes.extend(continuation.iter().rev());
+            return bytes;
+        }
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// Big-endian bit packer
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    used: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            accumulator: 0,
+            used: 0,
+        }
+    }
+
+    /// Writes the low `count` bits of `value`, up to 32 at a time
+    fn write(&mut self, value: u64, count: u32) {
+        if count > 32 {
+            self.write(value >> 32, count - 32);
+            self.write(value & 0xFFFF_FFFF, 32);
+            return;
+        }
+        self.accumulator = (self.accumulator << count) | (value & ((1u64 << count) - 1));
+        self.used += count;
+        while self.used >= 8 {
+            self.used -= 8;
+            self.bytes.push((self.accumulator >> self.used) as u8);
+        }
+        self.accumulator &= (1u64 << self.used) - 1;
+    }
+
+    fn write_signed(&mut self, value: i32, count: u32) {
+        self.write(value as u32 as u64, count);
+    }
+
+    fn write_rice(&mut self, value: i32, parameter: u32) {
+        let value = zigzag(value);
+        let mut quotient = value >> parameter;
+        while quotient >= 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient + 1);
+        if parameter > 0 {
+            self.write((value & ((1 << parameter) - 1)) as u64, parameter);
+        }
+    }
+
+    /// Pads with zero bits up to the next byte boundary
+    fn align(&mut self) {
+        if self.used > 0 {
+            self.write(0, 8 - self.used);
+        }
+    }
+
+    /// Bytes completed so far
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Big-endian bit reader, the counterpart of `BitWriter`
+    struct BitReader<'a> {
+        bytes: &'a [u8],
+        /// Position in bits
+        position: usize,
+    }
+
+    impl<'a> BitReader<'a> {
+        fn new(bytes: &'a [u8]) -> Self {
+            Self { bytes, position: 0 }
+        }
+
+        fn read(&mut self, count: u32) -> u64 {
+            (0..count).fold(0, |value, _| {
+                let bit = self.bytes[self.position / 8] >> (7 - self.position % 8) & 1;
+                self.position += 1;
+                value << 1 | bit as u64
+            })
+        }
+
+        fn read_sample(&mut self) -> i32 {
+            self.read(BITS_PER_SAMPLE) as u16 as i16 as i32
+        }
+
+        fn read_rice(&mut self, parameter: u32) -> i32 {
+            let mut quotient = 0u32;
+            while self.read(1) == 0 {
+                quotient += 1;
+            }
+            let value = quotient << parameter | self.read(parameter) as u32;
+            (value >> 1) as i32 ^ -((value & 1) as i32)
+        }
+
+        fn align(&mut self) {
+            self.position = self.position.div_ceil(8) * 8;
+        }
+
+        fn byte_position(&self) -> usize {
+            self.position / 8
+        }
+    }
+
+    /// Decodes one frame of the subset the writer produces, checking both CRCs. Returns the
+    /// frame number, the interleaved samples and the frame's length in bytes.
+    fn decode_frame(bytes: &[u8], channels: usize) -> (u64, Vec<i16>, usize) {
+        let mut reader = BitReader::new(bytes);
+        assert_eq!(reader.read(14), 0b11_1111_1111_1110, "sync code");
+        assert_eq!(reader.read(2), 0);
+        assert_eq!(reader.read(4), 0b0111);
+        assert_eq!(reader.read(4), 0b1010);
+        assert_eq!(reader.read(4) as usize, channels - 1);
+        assert_eq!(reader.read(3), 0b100);
+        assert_eq!(reader.read(1), 0);
+        let frame_number = read_utf8_number(&mut reader);
+        let block_size = reader.read(16) as usize + 1;
+        let header_len = reader.byte_position();
+        assert_eq!(
+            reader.read(8) as u8,
+            crc8(&bytes[..header_len]),
+            "header CRC"
+        );
+
+        let subframes: Vec<Vec<i32>> = (0..channels)
+            .map(|_| decode_subframe(&mut reader, block_size))
+            .collect();
+        reader.align();
+        let len = reader.byte_position();
+        assert_eq!(reader.read(16) as u16, crc16(&bytes[..len]), "frame CRC");
+
+        let samples = (0..block_size)
+            .flat_map(|i| subframes.iter().map(move |subframe| subframe[i] as i16))
+            .collect();
+        (frame_number, samples, len + 2)
+    }
+
+    fn read_utf8_number(reader: &mut BitReader) -> u64 {
+        let lead = reader.read(8);
+        let count = (lead as u8).leading_ones();
+        if count == 0 {
+            return lead;
+        }
+        (1..count).fold(lead & ((1 << (7 - count)) - 1), |value, _| {
+            let byte = reader.read(8);
+            assert_eq!(byte & 0xC0, 0x80, "continuation byte");
+            value << 6 | byte & 0x3F
+        })
+    }
+
+    fn decode_subframe(reader: &mut BitReader, block_size: usize) -> Vec<i32> {
+        assert_eq!(reader.read(1), 0);
+        let kind = reader.read(6);
+        assert_eq!(reader.read(1), 0, "wasted bits");
+        match kind {
+            0 => vec![reader.read_sample(); block_size],
+            1 => (0..block_size).map(|_| reader.read_sample()).collect(),
+            8..=12 => {
+                let order = kind as usize - 8;
+                let mut samples: Vec<i32> = (0..order).map(|_| reader.read_sample()).collect();
+                assert_eq!(reader.read(2), 0, "Rice coding with 4-bit parameters");
+                let partition_order = reader.read(4) as u32;
+                for partition in 0..1usize << partition_order {
+                    let parameter = reader.read(4) as u32;
+                    let mut len = block_size >> partition_order;
+                    if partition == 0 {
+                        len -= order;
+                    }
+                    for _ in 0..len {
+                        let residual = reader.read_rice(parameter);
+                        let s = |back: usize| samples[samples.len() - back];
+                        let prediction = match order {
+                            0 => 0,
+                            1 => s(1),
+                            2 => 2 * s(1) - s(2),
+                            3 => 3 * s(1) - 3 * s(2) + s(3),
+                            _ => 4 * s(1) - 6 * s(2) + 4 * s(3) - s(4),
+                        };
+                        samples.push(prediction + residual);
+                    }
+                }
+                samples
+            }
+            _ => panic!("unexpected subframe type {kind}"),
+        }
+    }
+
+    /// Silence, tones with a little noise, full-scale noise and clipped extremes, so every
+    /// subframe type gets used. The length isn't a whole number of blocks.
+    fn test_signal() -> Vec<i16> {
+        let mut seed = 1u32;
+        let mut noise = move || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 16) as i16
+        };
+        let rate = ENCODER_SAMPLE_RATE as f32;
+        let mut samples = vec![0; ENCODER_SAMPLE_RATE as usize / 2 * 2];
+        for i in 0..ENCODER_SAMPLE_RATE as usize {
+            let t = i as f32 / rate;
+            let left = 12_000.0 * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+            let right = 9_000.0 * (2.0 * std::f32::consts::PI * 1_250.0 * t).sin();
+            samples.push(left as i16 + noise() / 512);
+            samples.push(right as i16 + noise() / 512);
+        }
+        samples.extend((0..ENCODER_SAMPLE_RATE as usize / 2 * 2).map(|_| noise()));
+        samples.extend((0..1_234 * 2).map(|i| if i % 4 < 2 { i16::MAX } else { i16::MIN }));
+        samples
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let samples = test_signal();
+        let path = std::env::temp_dir().join(format!("framelet-test-{}.flac", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut writer = Box::new(FlacWriter::create(path).unwrap());
+        // Written in uneven pieces, as the recorder hands them over
+        for chunk in samples.chunks(1_001 * 2) {
+            writer.write(chunk).unwrap();
+        }
+        writer.finish().unwrap();
+        let bytes = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let channels = ENCODER_CHANNELS as usize;
+        assert_eq!(&bytes[..4], b"fLaC");
+        let frames = (samples.len() / channels) as u64;
+        assert_eq!(bytes[4..42], stream_info(frames));
+
+        let mut decoded = Vec::new();
+        let mut position = 42;
+        let mut expected_frame = 0;
+        while position < bytes.len() {
+            let (frame_number, frame, len) = decode_frame(&bytes[position..], channels);
+            assert_eq!(frame_number, expected_frame);
+            decoded.extend(frame);
+            position += len;
+            expected_frame += 1;
+        }
+        assert_eq!(decoded.len(), samples.len());
+        assert!(decoded == samples, "decoded audio differs");
+    }
+
+    #[test]
+    fn stores_large_frame_numbers() {
+        let samples = test_signal()[..BLOCK_SIZE * 2].to_vec();
+        for frame_number in [0x7F, 0x80, 70_000, 0x7FFF_FFFF] {
+            let frame = encode_frame(&samples, 2, frame_number);
+            let (decoded_number, decoded, len) = decode_frame(&frame, 2);
+            assert_eq!(decoded_number, frame_number);
+            assert_eq!(decoded, samples);
+            assert_eq!(len, frame.len());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use super::{AudioError, LoudnessSettings};
+
+mod flac;
+mod normalize;
+mod opus;
+mod wav;
+
+pub use flac::FlacWriter;
+pub use normalize::NormalizingWriter;
+pub use opus::OpusWriter;
+pub use wav::WavWriter;
+
+/// Writes 16-bit interleaved stereo audio at the encoder sample rate to a file
+pub trait AudioWriter: Send {
+    fn write(&mut self, samples: &[i16]) -> Result<(), AudioError>;
+
+    /// Flushes buffered audio and fills in the headers that depend on the length
+    fn finish(self: Box<Self>) -> Result<(), AudioError>;
+}
+
+/// Opens a writer for the format matching the file extension: `.wav`, `.flac`, or `.opus`/`.ogg`
+pub fn open_writer(path: &str) -> Result<Box<dyn AudioWriter>, AudioError> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "wav" => Ok(Box::new(WavWriter::create(path)?)),
+        "flac" => Ok(Box::new(FlacWriter::create(path)?)),
+        "opus" | "ogg" => Ok(Box::new(OpusWriter::create(path)?)),
+        _ => Err(AudioError::UnsupportedFile(path.to_string())),
+    }
+}
+
+/// Opens a writer like `open_writer`, normalizing the finished file to `loudness` if set
+pub fn open_normalized_writer(
+    path: &str,
+    loudness: Option<LoudnessSettings>,
+) -> Result<Box<dyn AudioWriter>, AudioError> {
+    let writer = open_writer(path)?;
+    match loudness {
+        Some(settings) => Ok(Box::new(NormalizingWriter::create(writer, path, settings)?)),
+        None => Ok(writer),
+    }
+}
+
+/// True if `path` has an extension an audio-only recording can be written to
+pub fn is_audio_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            ["wav", "flac", "opus", "ogg"].contains(&extension.to_ascii_lowercase().as_str())
+        })
+}
@@ -0,0 +1,197 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+};
+
+use super::AudioWriter;
+use crate::audio::{
+    filters::db_to_linear, AudioError, LoudnessPass, LoudnessSettings, ENCODER_CHANNELS,
+    ENCODER_SAMPLE_RATE,
+};
+
+/// Samples read back from the spool file at a time, a second of stereo audio
+const CHUNK_SAMPLES: usize = ENCODER_SAMPLE_RATE as usize * ENCODER_CHANNELS as usize;
+
+/// Normalizes a finished recording to a loudness target. The audio is kept in a raw file
+/// beside the output and measured as it arrives; `finish` applies the gain the whole
+/// recording needs and only then encodes it into the wrapped writer.
+pub struct NormalizingWriter {
+    inner: Option<Box<dyn AudioWriter>>,
+    settings: LoudnessSettings,
+    pass: LoudnessPass,
+    spool: Option<BufWriter<File>>,
+    spool_path: PathBuf,
+}
+
+impl NormalizingWriter {
+    pub fn create(
+        inner: Box<dyn AudioWriter>,
+        path: &str,
+        settings: LoudnessSettings,
+    ) -> Result<Self, AudioError> {
+        let spool_path = PathBuf::from(format!("{}.loudness", path));
+        let spool = BufWriter::new(File::create(&spool_path)?);
+        Ok(Self {
+            inner: Some(inner),
+            settings,
+            pass: LoudnessPass::new(ENCODER_SAMPLE_RATE, ENCODER_CHANNELS),
+            spool: Some(spool),
+            spool_path,
+        })
+    }
+}
+
+impl AudioWriter for NormalizingWriter {
+    fn write(&mut self, samples: &[i16]) -> Result<(), AudioError> {
+        let floats: Vec<f32> = samples
+            .iter()
+            .map(|&sample| sample as f32 / i16::MAX as f32)
+            .collect();
+        self.pass.push(&floats);
+
+        let bytes: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        if let Some(spool) = self.spool.as_mut() {
+            spool.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), AudioError> {
+        if let Some(mut spool) = self.spool.take() {
+            spool.flush()?;
+        }
+        let gain_db = self.pass.gain_db(&self.settings);
+        match gain_db {
+            Some(gain_db) => println!("Normalizing audio by {:.1} dB", gain_db),
+            None => println!("Audio too quiet to normalize"),
+        }
+        let gain = gain_db.map_or(1.0, db_to_linear);
+
+        let mut inner = self.inner.take().expect("finished twice");
+        let mut spool = BufReader::new(File::open(&self.spool_path)?);
+        let mut bytes = vec![0u8; CHUNK_SAMPLES * 2];
+        loop {
+            let read = read_full(&mut spool, &mut bytes)?;
+            if read == 0 {
+                break;
+            }
+            let samples: Vec<i16> = bytes[..read - read % 2]
+                .chunks_exact(2)
+                .map(|pair| {
+                    let sample = i16::from_le_bytes([pair[0], pair[1]]) as f32 * gain;
+                    sample.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+                })
+                .collect();
+            inner.write(&samples)?;
+        }
+        inner.finish()
+    }
+}
+
+impl Drop for NormalizingWriter {
+    fn drop(&mut self) {
+        // Also covers recordings that fail before `finish`
+        self.spool.take();
+        let _ = fs::remove_file(&self.spool_path);
+    }
+}
+
+/// Fills `buffer` unless the end of the file comes first, returning the bytes read
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Keeps what it is given so the test can look at it
+    struct Collect(Arc<Mutex<Vec<i16>>>);
+
+    impl AudioWriter for Collect {
+        fn write(&mut self, samples: &[i16]) -> Result<(), AudioError> {
+            self.0.lock().unwrap().extend_from_slice(samples);
+            Ok(())
+        }
+
+        fn finish(self: Box<Self>) -> Result<(), AudioError> {
+            Ok(())
+        }
+    }
+
+    /// Stereo 1 kHz sine with a peak of `amplitude`, in 20ms chunks like the recorder writes
+    fn tone(amplitude: f32, seconds: usize) -> Vec<Vec<i16>> {
+        let samples: Vec<i16> = (0..ENCODER_SAMPLE_RATE as usize * seconds)
+            .flat_map(|i| {
+                let phase =
+                    2.0 * std::f32::consts::PI * 1000.0 * i as f32 / ENCODER_SAMPLE_RATE as f32;
+                let sample = (amplitude * phase.sin() * i16::MAX as f32) as i16;
+                [sample, sample]
+            })
+            .collect();
+        samples.chunks(1920).map(<[i16]>::to_vec).collect()
+    }
+
+    fn peak(samples: &[i16]) -> f32 {
+        samples
+            .iter()
+            .map(|sample| (*sample as f32 / i16::MAX as f32).abs())
+            .fold(0.0, f32::max)
+    }
+
+    fn normalize(chunks: &[Vec<i16>], name: &str) -> (Vec<i16>, PathBuf) {
+        let path = std::env::temp_dir().join(format!("framelet-normalize-{}.wav", name));
+        let path = path.to_string_lossy().into_owned();
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let mut writer = Box::new(
+            NormalizingWriter::create(
+                Box::new(Collect(Arc::clone(&output))),
+                &path,
+                LoudnessSettings::default(),
+            )
+            .unwrap(),
+        );
+        let spool_path = writer.spool_path.clone();
+        for chunk in chunks {
+            writer.write(chunk).unwrap();
+        }
+        writer.finish().unwrap();
+        let output = output.lock().unwrap().clone();
+        (output, spool_path)
+    }
+
+    #[test]
+    fn scales_the_whole_recording_to_the_target() {
+        // A 0.1 peak sine reads -20 LUFS, 4 dB under the default target
+        let chunks = tone(0.1, 5);
+        let (output, spool_path) = normalize(&chunks, "gain");
+        assert_eq!(output.len(), chunks.concat().len());
+        // The same gain from the first sample to the last
+        let expected = 0.1 * db_to_linear(4.0);
+        let head = peak(&output[..4800]);
+        let tail = peak(&output[output.len() - 4800..]);
+        assert!((head - expected).abs() < 0.005, "{head}");
+        assert!((tail - expected).abs() < 0.005, "{tail}");
+        assert!(!spool_path.exists());
+    }
+
+    #[test]
+    fn passes_silence_through_unchanged() {
+        let chunks = vec![vec![0i16; 1920]; 100];
+        let (output, _) = normalize(&chunks, "silence");
+        assert_eq!(output, chunks.concat());
+    }
+}
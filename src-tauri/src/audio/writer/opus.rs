@@ -0,0 +1,140 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use opus::{Application, Channels, Encoder};
+
+use super::AudioWriter;
+use crate::audio::{AudioError, ENCODER_CHANNELS, ENCODER_SAMPLE_RATE};
+
+/// Samples per channel in each Opus packet, 20ms
+const FRAME_SIZE: usize = 960;
+const MAX_PACKET_SIZE: usize = 4000;
+const BITRATE: i32 = 128_000;
+
+/// Opus in an Ogg container
+pub struct OpusWriter {
+    writer: PacketWriter<'static, BufWriter<File>>,
+    encoder: Encoder,
+    serial: u32,
+    channels: usize,
+    /// The encoder's delay in samples per channel, which players skip at the start
+    pre_skip: u16,
+    pending: Vec<i16>,
+    /// The newest packet and its granule position, held back so the last one can end the stream
+    held: Option<(Vec<u8>, u64)>,
+    /// Samples per channel passed in, which the final granule position is set from
+    total_samples: u64,
+    encoded_samples: u64,
+}
+
+impl OpusWriter {
+    pub fn create(path: &str) -> Result<Self, AudioError> {
+        let channels = ENCODER_CHANNELS as usize;
+        let mut encoder = Encoder::new(ENCODER_SAMPLE_RATE, Channels::Stereo, Application::Audio)
+            .map_err(|e| AudioError::Encode(e.to_string()))?;
+        encoder
+            .set_bitrate(opus::Bitrate::Bits(BITRATE))
+            .map_err(|e| AudioError::Encode(e.to_string()))?;
+        // Read after configuring, since the delay depends on the application and settings
+        let pre_skip = encoder
+            .get_lookahead()
+            .map_err(|e| AudioError::Encode(e.to_string()))? as u16;
+
+        let serial = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos())
+            ^ std::process::id();
+        let mut writer = PacketWriter::new(BufWriter::new(File::create(path)?));
+
+        // The identification and comment headers each sit on their own page
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1);
+        head.push(channels as u8);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&ENCODER_SAMPLE_RATE.to_le_bytes());
+        // Output gain and channel mapping family 0
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+        writer.write_packet(head, serial, PacketWriteEndInfo::EndPage, 0)?;
+
+        let vendor = concat!("framelet ", env!("CARGO_PKG_VERSION"));
+        let mut tags = Vec::new();
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor.as_bytes());
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        writer.write_packet(tags, serial, PacketWriteEndInfo::EndPage, 0)?;
+
+        Ok(Self {
+            writer,
+            encoder,
+            serial,
+            channels,
+            pre_skip,
+            pending: Vec::new(),
+            held: None,
+            total_samples: 0,
+            encoded_samples: 0,
+        })
+    }
+
+    fn encode_frame(&mut self, frame: &[i16]) -> Result<(), AudioError> {
+        let mut packet = vec![0u8; MAX_PACKET_SIZE];
+        let len = self
+            .encoder
+            .encode(frame, &mut packet)
+            .map_err(|e| AudioError::Encode(e.to_string()))?;
+        packet.truncate(len);
+
+        self.encoded_samples += FRAME_SIZE as u64;
+        if let Some((previous, granule)) = self.held.take() {
+            self.writer.write_packet(
+                previous,
+                self.serial,
+                PacketWriteEndInfo::NormalPacket,
+                granule,
+            )?;
+        }
+        self.held = Some((packet, self.pre_skip as u64 + self.encoded_samples));
+        Ok(())
+    }
+}
+
+impl AudioWriter for OpusWriter {
+    fn write(&mut self, samples: &[i16]) -> Result<(), AudioError> {
+        self.pending.extend_from_slice(samples);
+        self.total_samples += (samples.len() / self.channels) as u64;
+
+        let frame_len = FRAME_SIZE * self.channels;
+        while self.pending.len() >= frame_len {
+            let frame: Vec<i16> = self.pending.drain(..frame_len).collect();
+            self.encode_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), AudioError> {
+        // Pad the last frame with silence, plus one more so the encoder delay is flushed out
+        let frame_len = FRAME_SIZE * self.channels;
+        if !self.pending.is_empty() {
+            let mut frame = std::mem::take(&mut self.pending);
+            frame.resize(frame_len, 0);
+            self.encode_frame(&frame)?;
+        }
+        self.encode_frame(&vec![0; frame_len])?;
+
+        // The final granule position trims the padding
+        if let Some((packet, _)) = self.held.take() {
+            let end = self.pre_skip as u64 + self.total_samples;
+            self.writer
+                .write_packet(packet, self.serial, PacketWriteEndInfo::EndStream, end)?;
+        }
+        self.writer.into_inner().flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+};
+
+use super::AudioWriter;
+use crate::audio::{AudioError, ENCODER_CHANNELS, ENCODER_SAMPLE_RATE};
+
+const HEADER_SIZE: u32 = 44;
+
+/// 16-bit PCM WAV. The sizes in the header are written once the length is known.
+pub struct WavWriter {
+    file: BufWriter<File>,
+    data_size: u64,
+}
+
+impl WavWriter {
+    pub fn create(path: &str) -> Result<Self, AudioError> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(&header(0))?;
+        Ok(Self { file, data_size: 0 })
+    }
+}
+
+fn header(data_size: u32) -> Vec<u8> {
+    let channels = ENCODER_CHANNELS;
+    let block_align = channels * 2;
+    let byte_rate = ENCODER_SAMPLE_RATE * block_align as u32;
+
+    let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(HEADER_SIZE - 8 + data_size).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    // PCM
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&ENCODER_SAMPLE_RATE.to_le_bytes());
+    header.extend_from_slice(&byte_rate.to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_size.to_le_bytes());
+    header
+}
+
+impl AudioWriter for WavWriter {
+    fn write(&mut self, samples: &[i16]) -> Result<(), AudioError> {
+        let bytes: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        self.file.write_all(&bytes)?;
+        self.data_size += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), AudioError> {
+        // WAV sizes are 32-bit, which caps a file at about six hours of stereo audio
+        let data_size = self.data_size.min((u32::MAX - HEADER_SIZE) as u64) as u32;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header(data_size))?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+use crate::pipeline::RgbaFrame;
+
+/// Produces output frames from several captures that each deliver frames at their own pace
+pub trait Compositor: Send {
+    /// Size the output is encoded at; rendered frames of another size are scaled to fit
+    fn size(&self) -> (u32, u32);
+
+    /// Screen-space position of the top-left output pixel, used by overlays such as the cursor.
+    /// `None` when the output isn't laid out in screen space, in which case those overlays
+    /// are left out.
+    fn origin(&self) -> Option<(i32, i32)>;
+
+    /// Renders the current state of the sources into a canvas the compositor keeps between
+    /// calls, which the caller may draw over. Returns `None` until there is something to show.
+    fn render(&mut self) -> Option<&mut RgbaFrame>;
+}
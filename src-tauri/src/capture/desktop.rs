@@ -0,0 +1,144 @@
+use windows_capture::settings::CursorCaptureSettings;
+
+use super::{
+    capture_item, latest::LatestFrameCapture, CaptureSourceManager, Compositor, MonitorInfo,
+};
+use crate::{
+    pipeline::RgbaFrame,
+    types::{CaptureSourceType, Region},
+};
+
+/// Largest frame hardware H.264 encoders reliably accept; bigger desktops are scaled down
+const MAX_OUTPUT_WIDTH: u32 = 4096;
+const MAX_OUTPUT_HEIGHT: u32 = 2304;
+
+struct DesktopMonitor {
+    left: i32,
+    top: i32,
+    capture: LatestFrameCapture,
+    latest: Option<RgbaFrame>,
+}
+
+/// Records several monitors as one picture, laid out the way Windows arranges them
+pub struct DesktopCompositor {
+    monitors: Vec<DesktopMonitor>,
+    /// Recorded area in screen coordinates
+    bounds: Region,
+    output_size: (u32, u32),
+    canvas: RgbaFrame,
+}
+
+impl DesktopCompositor {
+    /// Starts capturing the monitors with the given handles, or every monitor if none are
+    /// given. `region` is relative to the top-left corner of the combined monitors and may
+    /// span more than one of them.
+    pub fn start(
+        handles: &[isize],
+        region: Option<Region>,
+        cursor: CursorCaptureSettings,
+    ) -> Result<Self, String> {
+        let monitors: Vec<MonitorInfo> = CaptureSourceManager::get_monitors()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|monitor| handles.is_empty() || handles.contains(&monitor.hmonitor))
+            .collect();
+        let desktop = desktop_bounds(&monitors).ok_or("No monitors to record")?;
+
+        let bounds = match region {
+            Some(region) => Region {
+                x: desktop.x + region.x,
+                y: desktop.y + region.y,
+                width: region.width,
+                height: region.height,
+            },
+            None => desktop,
+        };
+        if bounds.width == 0 || bounds.height == 0 {
+            return Err("The region is empty".into());
+        }
+
+        let mut captures = Vec::new();
+        for monitor in &monitors {
+            let item = capture_item(monitor.hmonitor, CaptureSourceType::Monitor)?;
+            captures.push(DesktopMonitor {
+                left: monitor.left,
+                top: monitor.top,
+                capture: LatestFrameCapture::start(item, cursor)?,
+                latest: None,
+            });
+        }
+
+        Ok(Self {
+            monitors: captures,
+            output_size: fit_output(bounds.width, bounds.height),
+            canvas: RgbaFrame::blank(bounds.width, bounds.height),
+            bounds,
+        })
+    }
+}
+
+impl Compositor for DesktopCompositor {
+    fn size(&self) -> (u32, u32) {
+        self.output_size
+    }
+
+    fn origin(&self) -> Option<(i32, i32)> {
+        Some((self.bounds.x, self.bounds.y))
+    }
+
+    fn render(&mut self) -> Option<&mut RgbaFrame> {
+        for monitor in &mut self.monitors {
+            if let Some(frame) = monitor.capture.slot().take() {
+                monitor.latest = Some(frame);
+            }
+        }
+        if self.monitors.iter().all(|monitor| monitor.latest.is_none()) {
+            return None;
+        }
+
+        // Monitors that haven't delivered a frame yet, and gaps between monitors, stay black.
+        // The caller drew over the last frame, so it is cleared rather than painted over.
+        self.canvas.data.fill(0);
+        for monitor in &self.monitors {
+            if let Some(frame) = &monitor.latest {
+                self.canvas.blit(
+                    frame,
+                    monitor.left - self.bounds.x,
+                    monitor.top - self.bounds.y,
+                );
+            }
+        }
+        Some(&mut self.canvas)
+    }
+}
+
+/// The smallest rectangle covering all the monitors, in screen coordinates
+pub fn desktop_bounds(monitors: &[MonitorInfo]) -> Option<Region> {
+    let left = monitors.iter().map(|monitor| monitor.left).min()?;
+    let top = monitors.iter().map(|monitor| monitor.top).min()?;
+    let right = monitors
+        .iter()
+        .map(|monitor| monitor.left + monitor.width as i32)
+        .max()?;
+    let bottom = monitors
+        .iter()
+        .map(|monitor| monitor.top + monitor.height as i32)
+        .max()?;
+
+    Some(Region {
+        x: left,
+        y: top,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+    })
+}
+
+/// Scales a size down to what the encoder accepts, keeping the aspect ratio. Dimensions are
+/// kept even since H.264 needs that.
+fn fit_output(width: u32, height: u32) -> (u32, u32) {
+    let scale = (MAX_OUTPUT_WIDTH as f32 / width as f32)
+        .min(MAX_OUTPUT_HEIGHT as f32 / height as f32)
+        .min(1.0);
+    let even = |size: u32| ((size as f32 * scale) as u32 & !1).max(2);
+    (even(width), even(height))
+}
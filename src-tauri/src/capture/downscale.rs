@@ -0,0 +1,205 @@
+use windows::Win32::Graphics::{
+    Direct3D11::{
+        ID3D11Device, ID3D11ShaderResourceView, ID3D11Texture2D, D3D11_BIND_RENDER_TARGET,
+        D3D11_BIND_SHADER_RESOURCE, D3D11_BOX, D3D11_CPU_ACCESS_READ, D3D11_MAPPED_SUBRESOURCE,
+        D3D11_MAP_READ, D3D11_RESOURCE_MISC_GENERATE_MIPS, D3D11_TEXTURE2D_DESC,
+        D3D11_USAGE_DEFAULT, D3D11_USAGE_STAGING,
+    },
+    Dxgi::Common::{DXGI_FORMAT, DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_SAMPLE_DESC},
+};
+use windows_capture::frame::Frame;
+
+use crate::{pipeline::RgbaFrame, types::Region};
+
+/// Never halve more often than this, whatever size is asked for
+const MAX_LEVEL: u32 = 12;
+
+/// Reads captured frames back at about the size they will be shown at. The GPU halves the
+/// frame with mipmaps while the result still covers the requested size, so only that level
+/// is copied to the CPU and the preview thread scales the rest of the way.
+#[derive(Default)]
+pub struct GpuDownscaler {
+    textures: Option<Textures>,
+}
+
+/// Textures kept between frames while the size and format stay the same
+struct Textures {
+    width: u32,
+    height: u32,
+    levels: u32,
+    format: DXGI_FORMAT,
+    mips: ID3D11Texture2D,
+    view: ID3D11ShaderResourceView,
+    staging: ID3D11Texture2D,
+}
+
+impl GpuDownscaler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads `region` of the frame, or all of it, scaled down by a power of two towards
+    /// `max_width` x `max_height` but never below it
+    pub fn read(
+        &mut self,
+        frame: &Frame,
+        region: Option<&Region>,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<RgbaFrame, Box<dyn std::error::Error + Send + Sync>> {
+        let source = frame.as_raw_texture();
+        let mut desc = D3D11_TEXTURE2D_DESC::default();
+        unsafe { source.GetDesc(&mut desc) };
+
+        // The region coordinates are relative to the captured monitor, which matches the frame
+        let (left, top, right, bottom) = match region {
+            Some(region) => {
+                let left = (region.x.max(0) as u32).min(desc.Width);
+                let top = (region.y.max(0) as u32).min(desc.Height);
+                (
+                    left,
+                    top,
+                    left.saturating_add(region.width).min(desc.Width),
+                    top.saturating_add(region.height).min(desc.Height),
+                )
+            }
+            None => (0, 0, desc.Width, desc.Height),
+        };
+        let (width, height) = (right - left, bottom - top);
+        if width == 0 || height == 0 {
+            return Err("the region is outside the frame".into());
+        }
+
+        let scale =
+            (max_width.max(1) as f64 / width as f64).min(max_height.max(1) as f64 / height as f64);
+        let mut level = 0;
+        while level < MAX_LEVEL && scale * f64::from(2u32 << level) <= 1.0 {
+            level += 1;
+        }
+
+        let device = frame.device();
+        let reusable = self.textures.as_ref().is_some_and(|textures| {
+            (
+                textures.width,
+                textures.height,
+                textures.levels,
+                textures.format,
+            ) == (width, height, level + 1, desc.Format)
+        });
+        if !reusable {
+            self.textures = Some(Textures::create(
+                device,
+                width,
+                height,
+                level + 1,
+                desc.Format,
+            )?);
+        }
+        let textures = self.textures.as_ref().unwrap();
+
+        let context = unsafe { device.GetImmediateContext() }?;
+        let source_box = D3D11_BOX {
+            left,
+            top,
+            front: 0,
+            right,
+            bottom,
+            back: 1,
+        };
+        let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+        unsafe {
+            context.CopySubresourceRegion(&textures.mips, 0, 0, 0, 0, source, 0, Some(&source_box));
+            if level > 0 {
+                context.GenerateMips(&textures.view);
+            }
+            context.CopySubresourceRegion(
+                &textures.staging,
+                0,
+                0,
+                0,
+                0,
+                &textures.mips,
+                level,
+                None,
+            );
+            context.Map(&textures.staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))?;
+        }
+
+        let (out_width, out_height) = ((width >> level).max(1), (height >> level).max(1));
+        let row_len = out_width as usize * 4;
+        let mut data = Vec::with_capacity(row_len * out_height as usize);
+        for row in 0..out_height as usize {
+            let row = unsafe {
+                std::slice::from_raw_parts(
+                    (mapped.pData as *const u8).add(row * mapped.RowPitch as usize),
+                    row_len,
+                )
+            };
+            data.extend_from_slice(row);
+        }
+        unsafe { context.Unmap(&textures.staging, 0) };
+
+        if desc.Format == DXGI_FORMAT_B8G8R8A8_UNORM {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        Ok(RgbaFrame::new(out_width, out_height, data))
+    }
+}
+
+impl Textures {
+    fn create(
+        device: &ID3D11Device,
+        width: u32,
+        height: u32,
+        levels: u32,
+        format: DXGI_FORMAT,
+    ) -> windows::core::Result<Self> {
+        let mips_desc = D3D11_TEXTURE2D_DESC {
+            Width: width,
+            Height: height,
+            MipLevels: levels,
+            ArraySize: 1,
+            Format: format,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D11_USAGE_DEFAULT,
+            // Generating mips needs the texture to be both
+            BindFlags: (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_RENDER_TARGET.0) as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: D3D11_RESOURCE_MISC_GENERATE_MIPS.0 as u32,
+        };
+        let staging_desc = D3D11_TEXTURE2D_DESC {
+            Width: (width >> (levels - 1)).max(1),
+            Height: (height >> (levels - 1)).max(1),
+            MipLevels: 1,
+            Usage: D3D11_USAGE_STAGING,
+            BindFlags: 0,
+            CPUAccessFlags: D3D11_CPU_ACCESS_READ.0 as u32,
+            MiscFlags: 0,
+            ..mips_desc
+        };
+
+        let mut mips = None;
+        let mut staging = None;
+        let mut view = None;
+        unsafe {
+            device.CreateTexture2D(&mips_desc, None, Some(&mut mips))?;
+            device.CreateTexture2D(&staging_desc, None, Some(&mut staging))?;
+            let mips = mips.as_ref().unwrap();
+            device.CreateShaderResourceView(mips, None, Some(&mut view))?;
+        }
+        Ok(Self {
+            width,
+            height,
+            levels,
+            format,
+            mips: mips.unwrap(),
+            view: view.unwrap(),
+            staging: staging.unwrap(),
+        })
+    }
+}
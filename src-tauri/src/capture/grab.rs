@@ -0,0 +1,92 @@
+use std::{
+    sync::mpsc::{self, Sender},
+    time::Duration,
+};
+
+use windows_capture::{
+    capture::{Context, GraphicsCaptureApiHandler},
+    frame::Frame,
+    graphics_capture_api::InternalCaptureControl,
+    monitor::Monitor,
+    settings::{ColorFormat, CursorCaptureSettings, DrawBorderSettings, Settings},
+    window::Window,
+    WindowsCaptureGraphicsCaptureItem,
+};
+
+use crate::{pipeline::RgbaFrame, types::CaptureSourceType};
+
+/// Creates the capture item for a monitor or window handle
+pub fn capture_item(
+    handle: isize,
+    source_type: CaptureSourceType,
+) -> Result<WindowsCaptureGraphicsCaptureItem, String> {
+    match source_type {
+        CaptureSourceType::Monitor => WindowsCaptureGraphicsCaptureItem::try_from(
+            Monitor::from_raw_hmonitor(handle as *mut _),
+        ),
+        CaptureSourceType::Window => {
+            WindowsCaptureGraphicsCaptureItem::try_from(Window::from_raw_hwnd(handle as *mut _))
+        }
+        CaptureSourceType::Desktop | CaptureSourceType::Scene => {
+            return Err("Composited sources are captured one source at a time".into())
+        }
+        CaptureSourceType::Audio => return Err("Audio sources can't be captured as video".into()),
+    }
+    .map_err(|e| format!("Failed to create capture item: {}", e))
+}
+
+/// Capture handler that hands over the first frame it sees and stops
+struct FrameGrabber {
+    sender: Sender<RgbaFrame>,
+}
+
+impl GraphicsCaptureApiHandler for FrameGrabber {
+    type Flags = Sender<RgbaFrame>;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn new(ctx: Context<Self::Flags>) -> Result<Self, Self::Error> {
+        Ok(Self { sender: ctx.flags })
+    }
+
+    fn on_frame_arrived(
+        &mut self,
+        frame: &mut Frame,
+        capture_control: InternalCaptureControl,
+    ) -> Result<(), Self::Error> {
+        let mut buffer = frame.buffer()?;
+        let (width, height) = (buffer.width(), buffer.height());
+        let rgba = RgbaFrame::new(width, height, buffer.as_nopadding_buffer()?.to_vec());
+        let _ = self.sender.send(rgba);
+        capture_control.stop();
+        Ok(())
+    }
+}
+
+/// Captures a single frame of the item. Returns `None` if no frame arrives within `timeout`,
+/// which happens for minimized or hung windows.
+pub fn grab_frame(item: WindowsCaptureGraphicsCaptureItem, timeout: Duration) -> Option<RgbaFrame> {
+    let (sender, receiver) = mpsc::channel();
+    let settings = Settings::new(
+        item,
+        CursorCaptureSettings::WithoutCursor,
+        DrawBorderSettings::WithoutBorder,
+        ColorFormat::Rgba8,
+        sender,
+    );
+
+    let control = match FrameGrabber::start_free_threaded(settings) {
+        Ok(control) => control,
+        Err(e) => {
+            eprintln!("Failed to start frame capture: {}", e);
+            return None;
+        }
+    };
+
+    let frame = receiver.recv_timeout(timeout).ok();
+    // A source that sends no frames still runs the capture thread's message loop, so stopping
+    // ends the thread promptly and no capture outlives the call
+    if let Err(e) = control.stop() {
+        eprintln!("Failed to stop frame capture: {}", e);
+    }
+    frame
+}
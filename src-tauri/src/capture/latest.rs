@@ -0,0 +1,106 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use windows_capture::{
+    capture::{CaptureControl, Context, GraphicsCaptureApiHandler},
+    frame::Frame,
+    graphics_capture_api::InternalCaptureControl,
+    settings::{ColorFormat, CursorCaptureSettings, DrawBorderSettings, Settings},
+    WindowsCaptureGraphicsCaptureItem,
+};
+
+use crate::{pipeline::RgbaFrame, recording::screen_recorder::copy_frame};
+
+/// Hands the newest frame of a capture to a consumer running at its own pace. A frame is
+/// only copied off the GPU once the consumer has taken the previous one.
+pub struct FrameSlot {
+    frame: Mutex<Option<RgbaFrame>>,
+    wanted: AtomicBool,
+}
+
+impl FrameSlot {
+    /// Takes the frame that arrived since the last call, if any, and asks for the next one
+    pub fn take(&self) -> Option<RgbaFrame> {
+        let frame = self.frame.lock().unwrap().take();
+        self.wanted.store(true, Ordering::Relaxed);
+        frame
+    }
+}
+
+type LatestControl = CaptureControl<LatestFrameHandler, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A capture that keeps only its latest frame, for sources composited at a fixed rate
+pub struct LatestFrameCapture {
+    slot: Arc<FrameSlot>,
+    control: Option<LatestControl>,
+}
+
+impl LatestFrameCapture {
+    pub fn start(
+        item: WindowsCaptureGraphicsCaptureItem,
+        cursor: CursorCaptureSettings,
+    ) -> Result<Self, String> {
+        let slot = Arc::new(FrameSlot {
+            frame: Mutex::new(None),
+            wanted: AtomicBool::new(true),
+        });
+        let settings = Settings::new(
+            item,
+            cursor,
+            DrawBorderSettings::WithoutBorder,
+            ColorFormat::Rgba8,
+            Arc::clone(&slot),
+        );
+        let control = LatestFrameHandler::start_free_threaded(settings)
+            .map_err(|e| format!("Failed to start capture: {}", e))?;
+
+        Ok(Self {
+            slot,
+            control: Some(control),
+        })
+    }
+
+    pub fn slot(&self) -> &FrameSlot {
+        &self.slot
+    }
+}
+
+impl Drop for LatestFrameCapture {
+    fn drop(&mut self) {
+        if let Some(control) = self.control.take() {
+            // Stopping waits for the capture thread, which may be stuck on a hung source
+            thread::spawn(move || {
+                let _ = control.stop();
+            });
+        }
+    }
+}
+
+pub struct LatestFrameHandler {
+    slot: Arc<FrameSlot>,
+}
+
+impl GraphicsCaptureApiHandler for LatestFrameHandler {
+    type Flags = Arc<FrameSlot>;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn new(ctx: Context<Self::Flags>) -> Result<Self, Self::Error> {
+        Ok(Self { slot: ctx.flags })
+    }
+
+    fn on_frame_arrived(
+        &mut self,
+        frame: &mut Frame,
+        _capture_control: InternalCaptureControl,
+    ) -> Result<(), Self::Error> {
+        if self.slot.wanted.swap(false, Ordering::Relaxed) {
+            *self.slot.frame.lock().unwrap() = Some(copy_frame(frame, None)?);
+        }
+        Ok(())
+    }
+}
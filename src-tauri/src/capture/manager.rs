@@ -1,7 +1,23 @@
-use super::sources::{CaptureSourceError, MonitorInfo, WindowInfo};
+use std::path::Path;
+
+use super::{
+    desktop_bounds, process_image_path,
+    sources::{CaptureSourceError, MonitorInfo, WindowInfo},
+    window_details::{dpi_scale, is_cloaked, window_bounds, window_monitor, VirtualDesktops},
+    window_process_id,
+};
 use crate::recording::screen_recorder;
 use crate::types::{CaptureSource, CaptureSourceType};
-use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO};
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Graphics::Gdi::{
+            EnumDisplayDevicesW, GetMonitorInfoW, DISPLAY_DEVICEW, HMONITOR, MONITORINFO,
+            MONITORINFOEXW,
+        },
+        UI::WindowsAndMessaging::EDD_GET_DEVICE_INTERFACE_NAME,
+    },
+};
 use windows_capture::{monitor::Monitor, WindowsCaptureGraphicsCaptureItem};
 
 pub struct CaptureSourceManager;
@@ -45,10 +61,13 @@ impl CaptureSourceManager {
                 _ => continue,
             };
 
+            let stable_id = stable_monitor_id(hmonitor).unwrap_or_else(|| name.clone());
+
             monitor_info.push(MonitorInfo {
                 id,
                 hmonitor: hmonitor.0 as isize,
                 name,
+                stable_id,
                 width,
                 height,
                 left,
@@ -59,10 +78,11 @@ impl CaptureSourceManager {
         Ok(monitor_info)
     }
 
-    /// Gets all available windows
+    /// Gets all available windows, topmost first
     pub fn get_windows() -> Result<Vec<WindowInfo>, CaptureSourceError> {
         let windows =
             screen_recorder::get_available_windows().map_err(CaptureSourceError::ListWindows)?;
+        let desktops = VirtualDesktops::open();
 
         let mut window_info = Vec::new();
         for (id, window) in windows.iter().enumerate() {
@@ -86,12 +106,31 @@ impl CaptureSourceManager {
             let width = size.Width as u32;
             let height = size.Height as u32;
 
+            let pid = window_process_id(hwnd);
+            let exe_path = process_image_path(pid);
+            let process_name = exe_path.as_deref().and_then(|path| {
+                Path::new(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            });
+            let (left, top) = window_bounds(hwnd).map_or((0, 0), |rect| (rect.left, rect.top));
+
             window_info.push(WindowInfo {
                 id,
                 hwnd,
                 title,
                 width,
                 height,
+                pid,
+                process_name,
+                exe_path,
+                left,
+                top,
+                monitor: window_monitor(hwnd),
+                dpi_scale: dpi_scale(hwnd),
+                z_order: window_info.len(),
+                cloaked: is_cloaked(hwnd),
+                on_other_desktop: !desktops.is_on_current(hwnd),
             });
         }
 
@@ -100,11 +139,40 @@ impl CaptureSourceManager {
 
     /// Gets all available capture sources (monitors and windows)
     pub fn get_all_capture_sources() -> Result<Vec<CaptureSource>, CaptureSourceError> {
-        let mut sources = Vec::new();
+        let sources = Self::list_capture_sources()?;
+        let monitors = sources
+            .iter()
+            .filter(|source| source.source_type == CaptureSourceType::Monitor)
+            .count();
+        let windows = sources
+            .iter()
+            .filter(|source| source.source_type == CaptureSourceType::Window)
+            .count();
+        println!("Found {} monitors", monitors);
+        println!("Found {} windows", windows);
+        println!("Total sources found: {}", sources.len());
+        Ok(sources)
+    }
 
-        // Add monitors
+    /// Same as `get_all_capture_sources` without logging, for callers that poll
+    pub fn list_capture_sources() -> Result<Vec<CaptureSource>, CaptureSourceError> {
+        let mut sources = Vec::new();
         let monitors = Self::get_monitors()?;
-        println!("Found {} monitors", monitors.len());
+
+        // With several monitors they can also be recorded together
+        if monitors.len() > 1 {
+            if let Some(desktop) = desktop_bounds(&monitors) {
+                sources.push(CaptureSource {
+                    name: "Entire Desktop".to_string(),
+                    width: desktop.width,
+                    height: desktop.height,
+                    source_type: CaptureSourceType::Desktop,
+                    handle: 0,
+                    left: desktop.x,
+                    top: desktop.y,
+                });
+            }
+        }
 
         sources.extend(monitors.into_iter().map(|monitor| CaptureSource {
             name: monitor.name,
@@ -116,20 +184,59 @@ impl CaptureSourceManager {
             top: monitor.top,
         }));
 
-        let windows = Self::get_windows()?;
-        println!("Found {} windows", windows.len());
-
-        sources.extend(windows.into_iter().map(|window| CaptureSource {
-            name: window.title,
-            width: window.width,
-            height: window.height,
-            source_type: CaptureSourceType::Window,
-            handle: window.hwnd,
-            left: 0,
-            top: 0,
-        }));
+        sources.extend(
+            Self::get_windows()?
+                .into_iter()
+                .map(|window| CaptureSource {
+                    name: window.title,
+                    width: window.width,
+                    height: window.height,
+                    source_type: CaptureSourceType::Window,
+                    handle: window.hwnd,
+                    left: window.left,
+                    top: window.top,
+                }),
+        );
 
-        println!("Total sources found: {}", sources.len());
         Ok(sources)
     }
 }
+
+/// Device interface path of the monitor, e.g. `\\?\DISPLAY#GSM5B08#5&1d2c3b&0&UID4353#{...}`
+fn stable_monitor_id(hmonitor: HMONITOR) -> Option<String> {
+    let mut info = MONITORINFOEXW {
+        monitorInfo: MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFOEXW>() as u32,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    if !unsafe { GetMonitorInfoW(hmonitor, &mut info.monitorInfo) }.as_bool() {
+        return None;
+    }
+
+    // The monitor attached to the adapter output named in `szDevice`
+    let mut device = DISPLAY_DEVICEW {
+        cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+        ..Default::default()
+    };
+    let found = unsafe {
+        EnumDisplayDevicesW(
+            PCWSTR(info.szDevice.as_ptr()),
+            0,
+            &mut device,
+            EDD_GET_DEVICE_INTERFACE_NAME,
+        )
+    };
+    if !found.as_bool() {
+        return None;
+    }
+
+    let len = device
+        .DeviceID
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(device.DeviceID.len());
+    let id = String::from_utf16_lossy(&device.DeviceID[..len]);
+    (!id.is_empty()).then_some(id)
+}
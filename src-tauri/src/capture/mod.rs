@@ -1,8 +1,35 @@
+mod compositor;
+mod desktop;
+mod downscale;
+mod grab;
+mod latest;
 mod manager;
+mod preview;
+mod process;
+mod scene;
+mod selector;
 mod sources;
+mod thumbnail;
+mod watcher;
+mod window_details;
 
+pub use compositor::Compositor;
+pub use desktop::{desktop_bounds, DesktopCompositor};
+pub use downscale::GpuDownscaler;
+pub use grab::{capture_item, grab_frame};
+pub use latest::{FrameSlot, LatestFrameCapture};
 pub use manager::CaptureSourceManager;
+pub use preview::{
+    offer_captured_frame, offer_preview_frame, start_preview, stop_preview, stop_preview_capture,
+    PreviewFrame, PreviewSettings,
+};
+pub use process::{process_image_path, process_name, window_process_id};
+pub use scene::{ScalingMode, Scene, SceneCompositor, SceneLayer, SceneSource};
+pub use selector::{SelectorError, WindowMatcher, WindowSelector};
 pub use sources::{MonitorInfo, WindowInfo};
+pub use thumbnail::{encode_jpeg, get_source_thumbnail, SourceThumbnail};
+pub use watcher::{watch_capture_sources, CaptureSourcesChanged, RenamedSource, ResizedSource};
+pub use window_details::{dpi_scale, is_cloaked, window_bounds, window_monitor, VirtualDesktops};
 
 // Re-export error types
 pub use sources::CaptureSourceError;
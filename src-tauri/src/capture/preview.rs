@@ -0,0 +1,253 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Channel;
+use windows_capture::{
+    capture::{CaptureControl, Context, GraphicsCaptureApiHandler},
+    frame::Frame,
+    graphics_capture_api::InternalCaptureControl,
+    settings::{ColorFormat, CursorCaptureSettings, DrawBorderSettings, Settings},
+    WindowsCaptureGraphicsCaptureItem,
+};
+
+use super::{encode_jpeg, GpuDownscaler};
+use crate::{pipeline::RgbaFrame, types::Region};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreviewSettings {
+    pub fps: u32,
+    /// Frames are scaled down to fit within this size
+    pub max_width: u32,
+    pub max_height: u32,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self {
+            fps: 12,
+            max_width: 854,
+            max_height: 480,
+        }
+    }
+}
+
+/// One preview frame as sent to the webview
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewFrame {
+    pub width: u32,
+    pub height: u32,
+    /// `data:` URL with the base64-encoded JPEG
+    pub data_url: String,
+}
+
+/// Receives frames from whichever capture is running and encodes them on its own thread.
+/// Frames are only taken when one is due, and a frame still waiting is replaced by a newer one,
+/// so a slow webview never backs up into the capture.
+pub struct PreviewSink {
+    settings: PreviewSettings,
+    interval: Duration,
+    next_due: Mutex<Instant>,
+    pending: Mutex<Option<RgbaFrame>>,
+    ready: Condvar,
+    stopped: AtomicBool,
+}
+
+impl PreviewSink {
+    pub fn start(settings: PreviewSettings, channel: Channel<PreviewFrame>) -> Arc<Self> {
+        let sink = Arc::new(Self {
+            interval: Duration::from_secs(1) / settings.fps.clamp(1, 30),
+            settings,
+            next_due: Mutex::new(Instant::now()),
+            pending: Mutex::new(None),
+            ready: Condvar::new(),
+            stopped: AtomicBool::new(false),
+        });
+
+        thread::spawn({
+            let sink = Arc::clone(&sink);
+            move || sink.run(channel)
+        });
+        sink
+    }
+
+    /// True when the next frame should be submitted; claims the slot if so
+    fn take_due(&self) -> bool {
+        let now = Instant::now();
+        let mut next_due = self.next_due.lock().unwrap();
+        if now < *next_due {
+            return false;
+        }
+        *next_due = now + self.interval;
+        true
+    }
+
+    fn submit(&self, frame: RgbaFrame) {
+        *self.pending.lock().unwrap() = Some(frame);
+        self.ready.notify_one();
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.ready.notify_one();
+    }
+
+    fn run(&self, channel: Channel<PreviewFrame>) {
+        loop {
+            let frame = {
+                let mut pending = self.pending.lock().unwrap();
+                while pending.is_none() && !self.stopped.load(Ordering::Relaxed) {
+                    pending = self.ready.wait(pending).unwrap();
+                }
+                if self.stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                pending.take().unwrap()
+            };
+
+            let scaled = frame.downscale_to_fit(self.settings.max_width, self.settings.max_height);
+            let data_url = match encode_jpeg(&scaled) {
+                Ok(data_url) => data_url,
+                Err(e) => {
+                    eprintln!("Failed to encode preview frame: {}", e);
+                    continue;
+                }
+            };
+            let preview = PreviewFrame {
+                width: scaled.width,
+                height: scaled.height,
+                data_url,
+            };
+            if let Err(e) = channel.send(preview) {
+                eprintln!("Preview channel closed: {}", e);
+                self.stopped.store(true, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+}
+
+type PreviewControl = CaptureControl<PreviewCapture, Box<dyn std::error::Error + Send + Sync>>;
+
+lazy_static! {
+    static ref PREVIEW_SINK: Mutex<Option<Arc<PreviewSink>>> = Mutex::new(None);
+    /// Capture feeding the preview while nothing is being recorded
+    static ref PREVIEW_CAPTURE: Mutex<Option<PreviewControl>> = Mutex::new(None);
+}
+
+/// Hands a frame to the preview if one is showing and a frame is due. `frame` is only
+/// called when the frame will be used, so captures pay nothing in between.
+pub fn offer_preview_frame(frame: impl FnOnce() -> Option<RgbaFrame>) {
+    if let Some(sink) = due_sink() {
+        if let Some(frame) = frame() {
+            sink.submit(frame);
+        }
+    }
+}
+
+/// Like `offer_preview_frame` for a frame still on the GPU. It is scaled down there first,
+/// so only about the preview size is copied back.
+pub fn offer_captured_frame(
+    frame: &Frame,
+    region: Option<&Region>,
+    downscaler: &mut GpuDownscaler,
+) {
+    let Some(sink) = due_sink() else {
+        return;
+    };
+    let (max_width, max_height) = (sink.settings.max_width, sink.settings.max_height);
+    match downscaler.read(frame, region, max_width, max_height) {
+        Ok(frame) => sink.submit(frame),
+        Err(e) => eprintln!("Failed to read preview frame: {}", e),
+    }
+}
+
+/// The preview sink, if one is showing and wants a frame now
+fn due_sink() -> Option<Arc<PreviewSink>> {
+    let sink = match PREVIEW_SINK.lock().unwrap().as_ref() {
+        Some(sink) if !sink.stopped.load(Ordering::Relaxed) => Arc::clone(sink),
+        _ => return None,
+    };
+    sink.take_due().then_some(sink)
+}
+
+/// Starts previewing `item`, cropped to `region`, replacing any preview already running.
+/// Without an item no capture is started and the preview only shows what a running
+/// recording offers.
+pub fn start_preview(
+    item: Option<WindowsCaptureGraphicsCaptureItem>,
+    region: Option<Region>,
+    settings: PreviewSettings,
+    channel: Channel<PreviewFrame>,
+) -> Result<(), String> {
+    stop_preview();
+    *PREVIEW_SINK.lock().unwrap() = Some(PreviewSink::start(settings, channel));
+    let Some(item) = item else {
+        return Ok(());
+    };
+
+    let capture_settings = Settings::new(
+        item,
+        CursorCaptureSettings::Default,
+        DrawBorderSettings::WithoutBorder,
+        ColorFormat::Rgba8,
+        region,
+    );
+    let control = PreviewCapture::start_free_threaded(capture_settings)
+        .map_err(|e| format!("Failed to start preview capture: {}", e))?;
+    *PREVIEW_CAPTURE.lock().unwrap() = Some(control);
+    Ok(())
+}
+
+/// Stops the preview's own capture but keeps the preview open, so a recording can feed it
+pub fn stop_preview_capture() {
+    if let Some(control) = PREVIEW_CAPTURE.lock().unwrap().take() {
+        // Waiting for the capture thread could stall the caller if the source is stuck
+        thread::spawn(move || {
+            let _ = control.stop();
+        });
+    }
+}
+
+/// Stops the preview entirely
+pub fn stop_preview() {
+    stop_preview_capture();
+    if let Some(sink) = PREVIEW_SINK.lock().unwrap().take() {
+        sink.stop();
+    }
+}
+
+/// Capture handler used for previewing before a recording starts
+pub struct PreviewCapture {
+    region: Option<Region>,
+    downscaler: GpuDownscaler,
+}
+
+impl GraphicsCaptureApiHandler for PreviewCapture {
+    type Flags = Option<Region>;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn new(ctx: Context<Self::Flags>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            region: ctx.flags,
+            downscaler: GpuDownscaler::new(),
+        })
+    }
+
+    fn on_frame_arrived(
+        &mut self,
+        frame: &mut Frame,
+        _capture_control: InternalCaptureControl,
+    ) -> Result<(), Self::Error> {
+        offer_captured_frame(frame, self.region.as_ref(), &mut self.downscaler);
+        Ok(())
+    }
+}
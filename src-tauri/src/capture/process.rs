@@ -0,0 +1,48 @@
+use std::{os::raw::c_void, path::Path};
+
+use windows::{
+    core::PWSTR,
+    Win32::{
+        Foundation::{CloseHandle, HWND},
+        System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        },
+        UI::WindowsAndMessaging::GetWindowThreadProcessId,
+    },
+};
+
+/// Returns the ID of the process that owns the window
+pub fn window_process_id(hwnd: isize) -> u32 {
+    let mut pid = 0;
+    unsafe { GetWindowThreadProcessId(HWND(hwnd as *mut c_void), Some(&mut pid)) };
+    pid
+}
+
+/// Returns the full path of the process executable, if the process can be opened
+pub fn process_image_path(pid: u32) -> Option<String> {
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    let result = unsafe {
+        QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        )
+    };
+    let _ = unsafe { CloseHandle(handle) };
+    result.ok()?;
+
+    Some(String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+/// Returns the executable file name (e.g. `slack.exe`) of the process
+pub fn process_name(pid: u32) -> Option<String> {
+    let path = process_image_path(pid)?;
+    Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
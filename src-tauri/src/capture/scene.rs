@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use windows_capture::settings::CursorCaptureSettings;
+
+use super::{capture_item, latest::LatestFrameCapture, Compositor, SelectorError, WindowSelector};
+use crate::{
+    pipeline::RgbaFrame,
+    types::{CaptureSourceType, RecordingOptions, Region},
+};
+
+/// Several capture sources arranged on one output, e.g. two windows side by side or a grid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scene {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// Shown wherever no layer covers the output
+    pub background: [u8; 4],
+    pub layers: Vec<SceneLayer>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            fps: 30,
+            background: [0, 0, 0, 255],
+            layers: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneLayer {
+    pub source: SceneSource,
+    /// Where the source is drawn on the output
+    pub rect: Region,
+    /// Part of the source to show; all of it when unset
+    #[serde(default)]
+    pub crop: Option<Region>,
+    /// Layers with a higher index are drawn on top; equal ones in list order
+    #[serde(default)]
+    pub z_index: i32,
+    #[serde(default)]
+    pub scaling: ScalingMode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SceneSource {
+    Monitor {
+        handle: isize,
+    },
+    Window {
+        handle: isize,
+    },
+    /// A window found by title or executable, which keeps saved scenes usable across restarts
+    WindowMatch {
+        selector: WindowSelector,
+    },
+}
+
+/// How a source is fitted into its layer's rectangle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingMode {
+    /// Shows all of the source, keeping its aspect ratio; the background fills the rest
+    #[default]
+    Fit,
+    /// Covers the whole rectangle, keeping the aspect ratio and cutting off the overflow
+    Fill,
+    /// Stretches the source to the rectangle
+    Stretch,
+}
+
+impl Scene {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err("The scene has no size".into());
+        }
+        if self.width % 2 != 0 || self.height % 2 != 0 {
+            return Err("The scene's width and height must be even".into());
+        }
+        if self.layers.is_empty() {
+            return Err("The scene has no layers".into());
+        }
+        for (index, layer) in self.layers.iter().enumerate() {
+            if layer.rect.width == 0 || layer.rect.height == 0 {
+                return Err(format!("Layer {} has an empty rectangle", index + 1));
+            }
+            if let Some(crop) = &layer.crop {
+                if crop.width == 0 || crop.height == 0 {
+                    return Err(format!("Layer {} has an empty crop", index + 1));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Scenes move and scale their sources, so options that draw at screen positions can't
+    /// be placed. Masking framelet's own windows is skipped too; the OS still excludes them
+    /// where it can.
+    pub fn check_options(options: &RecordingOptions) -> Result<(), String> {
+        if !options.redactions.is_empty() {
+            return Err("Redactions can't be used when recording a scene".into());
+        }
+        if options.cursor.needs_compositing() {
+            return Err(
+                "Cursor effects can't be used when recording a scene; the plain cursor can".into(),
+            );
+        }
+        if options.auto_zoom.is_some() {
+            return Err("Auto-zoom can't be used when recording a scene".into());
+        }
+        Ok(())
+    }
+
+    /// Monitors and windows the layers capture, once windows are resolved
+    pub fn sources(&self) -> Vec<(CaptureSourceType, isize)> {
+        self.layers
+            .iter()
+            .filter_map(|layer| match &layer.source {
+                SceneSource::Monitor { handle } => Some((CaptureSourceType::Monitor, *handle)),
+                SceneSource::Window { handle } => Some((CaptureSourceType::Window, *handle)),
+                SceneSource::WindowMatch { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Looks up the windows that layers select by title or executable, which may wait for
+    /// them to appear
+    pub fn resolve_windows(mut self) -> Result<Self, SelectorError> {
+        for layer in &mut self.layers {
+            if let SceneSource::WindowMatch { selector } = &layer.source {
+                let window = selector.resolve()?;
+                layer.source = SceneSource::Window {
+                    handle: window.hwnd,
+                };
+            }
+        }
+        Ok(self)
+    }
+}
+
+struct ActiveLayer {
+    layer: SceneLayer,
+    capture: LatestFrameCapture,
+    /// The latest frame already cropped and scaled, redone only when a new frame arrives
+    scaled: Option<(RgbaFrame, i32, i32)>,
+}
+
+/// Renders a scene from the latest frame of each of its sources
+pub struct SceneCompositor {
+    width: u32,
+    height: u32,
+    background: [u8; 4],
+    layers: Vec<ActiveLayer>,
+    canvas: RgbaFrame,
+}
+
+impl SceneCompositor {
+    /// Starts capturing every layer's source. Window matches have to be resolved first.
+    pub fn start(scene: Scene, cursor: CursorCaptureSettings) -> Result<Self, String> {
+        scene.validate()?;
+
+        let mut layers = scene.layers;
+        // Stable, so equal indices keep their list order
+        layers.sort_by_key(|layer| layer.z_index);
+
+        let mut active = Vec::new();
+        for layer in layers {
+            let item = match &layer.source {
+                SceneSource::Monitor { handle } => {
+                    capture_item(*handle, CaptureSourceType::Monitor)
+                }
+                SceneSource::Window { handle } => capture_item(*handle, CaptureSourceType::Window),
+                SceneSource::WindowMatch { selector } => Err(format!(
+                    "Window {:?} hasn't been resolved",
+                    selector.matcher
+                )),
+            }?;
+            active.push(ActiveLayer {
+                layer,
+                capture: LatestFrameCapture::start(item, cursor)?,
+                scaled: None,
+            });
+        }
+
+        Ok(Self {
+            width: scene.width,
+            height: scene.height,
+            background: scene.background,
+            layers: active,
+            canvas: RgbaFrame::blank(scene.width, scene.height),
+        })
+    }
+}
+
+impl Compositor for SceneCompositor {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Scenes aren't laid out in screen space; see `Scene::check_options`
+    fn origin(&self) -> Option<(i32, i32)> {
+        None
+    }
+
+    fn render(&mut self) -> Option<&mut RgbaFrame> {
+        for active in &mut self.layers {
+            if let Some(frame) = active.capture.slot().take() {
+                active.scaled = Some(place_layer(&active.layer, &frame));
+            }
+        }
+        if self.layers.iter().all(|active| active.scaled.is_none()) {
+            return None;
+        }
+
+        self.canvas
+            .data
+            .chunks_exact_mut(4)
+            .for_each(|px| px.copy_from_slice(&self.background));
+        for active in &self.layers {
+            if let Some((image, x, y)) = &active.scaled {
+                self.canvas.blit(image, *x, *y);
+            }
+        }
+        Some(&mut self.canvas)
+    }
+}
+
+/// Crops and scales a source frame for its layer. Returns the image and where it goes.
+fn place_layer(layer: &SceneLayer, frame: &RgbaFrame) -> (RgbaFrame, i32, i32) {
+    let source = match &layer.crop {
+        Some(crop) => frame.crop(crop.x, crop.y, crop.width, crop.height),
+        None => frame.clone(),
+    };
+    let rect = &layer.rect;
+    let (src_width, src_height) = (source.width as f32, source.height as f32);
+    let (rect_width, rect_height) = (rect.width as f32, rect.height as f32);
+
+    match layer.scaling {
+        ScalingMode::Stretch => {
+            let image = source.resample(0.0, 0.0, src_width, src_height, rect.width, rect.height);
+            (image, rect.x, rect.y)
+        }
+        ScalingMode::Fit => {
+            let scale = (rect_width / src_width).min(rect_height / src_height);
+            let width = ((src_width * scale).round() as u32).clamp(1, rect.width);
+            let height = ((src_height * scale).round() as u32).clamp(1, rect.height);
+            let image = if scale < 1.0 {
+                source.downscale_to_fit(width, height)
+            } else {
+                source.resample(0.0, 0.0, src_width, src_height, width, height)
+            };
+            let x = rect.x + (rect.width - image.width) as i32 / 2;
+            let y = rect.y + (rect.height - image.height) as i32 / 2;
+            (image, x, y)
+        }
+        ScalingMode::Fill => {
+            // The largest part of the source with the rectangle's aspect ratio, centered
+            let scale = (rect_width / src_width).max(rect_height / src_height);
+            let (visible_width, visible_height) = (rect_width / scale, rect_height / scale);
+            let image = source.resample(
+                (src_width - visible_width) / 2.0,
+                (src_height - visible_height) / 2.0,
+                visible_width,
+                visible_height,
+                rect.width,
+                rect.height,
+            );
+            (image, rect.x, rect.y)
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use windows::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{GetForegroundWindow, GetWindow, GW_HWNDNEXT},
+};
+
+use super::{CaptureSourceError, CaptureSourceManager, WindowInfo};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How a window is picked out without knowing its HWND
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WindowMatcher {
+    /// The title is exactly this
+    Title { title: String },
+    /// The title matches this regular expression
+    TitleRegex { pattern: String },
+    /// The owning executable, e.g. `code.exe`, compared case-insensitively
+    Executable { name: String },
+    /// Whichever window is in front when the recording starts, skipping framelet's own
+    Foreground,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowSelector {
+    #[serde(flatten)]
+    pub matcher: WindowMatcher,
+    /// Keeps looking for this long if no window matches yet, e.g. while an app launches
+    #[serde(default)]
+    pub wait_secs: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum SelectorError {
+    #[error("invalid title pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+
+    #[error("no window matches {0}")]
+    NoMatch(String),
+
+    #[error("{} windows match {selector}: {}", .candidates.len(), .candidates.join(", "))]
+    Ambiguous {
+        selector: String,
+        candidates: Vec<String>,
+    },
+
+    #[error(transparent)]
+    ListWindows(#[from] CaptureSourceError),
+}
+
+impl Serialize for SelectorError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl WindowSelector {
+    /// Finds the single window the selector describes, waiting up to `wait_secs` for one
+    /// to appear. More than one match is an error rather than a guess.
+    pub fn resolve(&self) -> Result<WindowInfo, SelectorError> {
+        let pattern = match &self.matcher {
+            WindowMatcher::TitleRegex { pattern } => Some(Regex::new(pattern)?),
+            _ => None,
+        };
+        let deadline = Instant::now() + Duration::from_secs(self.wait_secs);
+
+        loop {
+            match self.find(pattern.as_ref()) {
+                Err(SelectorError::NoMatch(_)) if Instant::now() < deadline => {
+                    thread::sleep(POLL_INTERVAL)
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn find(&self, pattern: Option<&Regex>) -> Result<WindowInfo, SelectorError> {
+        let windows = CaptureSourceManager::get_windows()?;
+
+        let mut matches: Vec<WindowInfo> = match &self.matcher {
+            WindowMatcher::Title { title } => windows
+                .into_iter()
+                .filter(|window| &window.title == title)
+                .collect(),
+            WindowMatcher::TitleRegex { .. } => {
+                let pattern = pattern.expect("pattern is compiled before searching");
+                windows
+                    .into_iter()
+                    .filter(|window| pattern.is_match(&window.title))
+                    .collect()
+            }
+            WindowMatcher::Executable { name } => windows
+                .into_iter()
+                .filter(|window| executable_matches(window, name))
+                .collect(),
+            WindowMatcher::Foreground => foreground_window(&windows).into_iter().collect(),
+        };
+
+        match matches.len() {
+            0 => Err(SelectorError::NoMatch(self.describe())),
+            1 => Ok(matches.remove(0)),
+            _ => Err(SelectorError::Ambiguous {
+                selector: self.describe(),
+                candidates: matches.iter().map(describe_window).collect(),
+            }),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match &self.matcher {
+            WindowMatcher::Title { title } => format!("title \"{}\"", title),
+            WindowMatcher::TitleRegex { pattern } => format!("title pattern /{}/", pattern),
+            WindowMatcher::Executable { name } => format!("executable {}", name),
+            WindowMatcher::Foreground => "the foreground window".to_string(),
+        }
+    }
+}
+
+/// Compares the window's executable with `name`, which may leave out the `.exe`
+fn executable_matches(window: &WindowInfo, name: &str) -> bool {
+    let Some(executable) = &window.process_name else {
+        return false;
+    };
+    let executable = executable.to_lowercase();
+    let name = name.to_lowercase();
+    executable == name || executable.strip_suffix(".exe") == Some(name.as_str())
+}
+
+/// The frontmost capturable window. framelet's own windows aren't capture sources, so when
+/// one of them is in front the next window down is used.
+fn foreground_window(windows: &[WindowInfo]) -> Option<WindowInfo> {
+    let mut hwnd = unsafe { GetForegroundWindow() };
+    while !hwnd.is_invalid() {
+        if let Some(window) = windows.iter().find(|window| window.hwnd == hwnd.0 as isize) {
+            return Some(window.clone());
+        }
+        hwnd = unsafe { GetWindow(hwnd, GW_HWNDNEXT) }.unwrap_or(HWND::default());
+    }
+    None
+}
+
+fn describe_window(window: &WindowInfo) -> String {
+    format!(
+        "\"{}\" ({}, hwnd {:#x})",
+        window.title,
+        window.process_name.as_deref().unwrap_or_default(),
+        window.hwnd
+    )
+}
@@ -6,19 +6,37 @@ pub struct MonitorInfo {
     pub id: usize,
     pub hmonitor: isize,
     pub name: String,
+    /// Device interface path of the monitor, which unlike the HMONITOR survives restarts
+    /// and layout changes
+    pub stable_id: String,
     pub width: u32,
     pub height: u32,
     pub left: i32,
     pub top: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct WindowInfo {
     pub id: usize,
     pub hwnd: isize,
     pub title: String,
     pub width: u32,
     pub height: u32,
+    pub pid: u32,
+    /// Executable file name, e.g. `code.exe`; `None` if the process can't be opened
+    pub process_name: Option<String>,
+    pub exe_path: Option<String>,
+    /// Screen position of the visible frame
+    pub left: i32,
+    pub top: i32,
+    /// HMONITOR of the monitor showing most of the window
+    pub monitor: isize,
+    /// DPI relative to 96, e.g. 1.5 at 150% scaling
+    pub dpi_scale: f32,
+    /// Stacking position, 0 being the topmost window
+    pub z_order: usize,
+    pub cloaked: bool,
+    pub on_other_desktop: bool,
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,99 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use windows_capture::{encoder::ImageEncoder, frame::ImageFormat, settings::ColorFormat};
+
+use super::grab::{capture_item, grab_frame};
+use crate::{pipeline::RgbaFrame, types::CaptureSourceType};
+
+/// How long a source gets to produce a frame
+const CAPTURE_TIMEOUT: Duration = Duration::from_millis(1500);
+/// How long a thumbnail, or a failure to get one, is reused
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// A downscaled picture of a capture source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceThumbnail {
+    pub width: u32,
+    pub height: u32,
+    /// `data:` URL with the base64-encoded JPEG, usable directly as an image source
+    pub data_url: String,
+}
+
+struct CachedThumbnail {
+    started_at: Instant,
+    /// Set once the capture finishes. Requests arriving meanwhile wait for it rather than
+    /// starting a capture of their own.
+    result: OnceLock<Result<SourceThumbnail, String>>,
+}
+
+impl CachedThumbnail {
+    fn is_fresh(&self) -> bool {
+        self.result.get().is_none() || self.started_at.elapsed() < CACHE_TTL
+    }
+}
+
+lazy_static! {
+    static ref THUMBNAILS: Mutex<HashMap<(CaptureSourceType, isize, u32), Arc<CachedThumbnail>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Captures one frame of a monitor or window and scales it to fit within `max_size` pixels.
+/// Results are cached briefly so repeated enumeration doesn't start a capture each time.
+pub fn get_source_thumbnail(
+    handle: isize,
+    source_type: CaptureSourceType,
+    max_size: u32,
+) -> Result<SourceThumbnail, String> {
+    let cached = {
+        let mut thumbnails = THUMBNAILS.lock().unwrap();
+        thumbnails.retain(|_, cached| cached.is_fresh());
+        Arc::clone(
+            thumbnails
+                .entry((source_type, handle, max_size))
+                .or_insert_with(|| {
+                    Arc::new(CachedThumbnail {
+                        started_at: Instant::now(),
+                        result: OnceLock::new(),
+                    })
+                }),
+        )
+    };
+    // Captured outside the cache lock, so other sources aren't held up
+    cached
+        .result
+        .get_or_init(|| capture_thumbnail(handle, source_type, max_size.max(1)))
+        .clone()
+}
+
+fn capture_thumbnail(
+    handle: isize,
+    source_type: CaptureSourceType,
+    max_size: u32,
+) -> Result<SourceThumbnail, String> {
+    let item = capture_item(handle, source_type)?;
+    let frame = grab_frame(item, CAPTURE_TIMEOUT)
+        .ok_or_else(|| "Timed out waiting for a frame".to_string())?;
+    let thumbnail = frame.downscale_to_fit(max_size, max_size);
+    let data_url = encode_jpeg(&thumbnail)?;
+
+    Ok(SourceThumbnail {
+        width: thumbnail.width,
+        height: thumbnail.height,
+        data_url,
+    })
+}
+
+/// Encodes the frame as a JPEG `data:` URL
+pub fn encode_jpeg(frame: &RgbaFrame) -> Result<String, String> {
+    let bytes = ImageEncoder::new(ImageFormat::Jpeg, ColorFormat::Rgba8)
+        .encode(&frame.data, frame.width, frame.height)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(format!("data:image/jpeg;base64,{}", BASE64.encode(bytes)))
+}
@@ -0,0 +1,282 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use windows::Win32::{
+    Foundation::{HWND, RECT},
+    Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO},
+    UI::{
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+        WindowsAndMessaging::{
+            GetAncestor, GetMessageW, IsWindow, CHILDID_SELF, EVENT_OBJECT_CLOAKED,
+            EVENT_OBJECT_CREATE, EVENT_OBJECT_DESTROY, EVENT_OBJECT_HIDE,
+            EVENT_OBJECT_LOCATIONCHANGE, EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_SHOW,
+            EVENT_OBJECT_UNCLOAKED, GA_ROOT, MSG, OBJID_WINDOW, WINEVENT_OUTOFCONTEXT,
+        },
+    },
+};
+use windows_capture::monitor::Monitor;
+
+use super::CaptureSourceManager;
+use crate::{
+    recording::RecordingSession,
+    types::{CaptureSource, CaptureSourceType},
+};
+
+/// Windows are reported through events; monitors are checked this often
+const MONITOR_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Opening or dragging a window sends a burst of events; they are handled together
+const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+/// A WinEvent and the window it is about
+type WindowEvent = (u32, isize);
+
+lazy_static! {
+    /// The WinEvent callback has no user data pointer, so events go through a global
+    static ref WINDOW_EVENTS: Mutex<Option<Sender<WindowEvent>>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamedSource {
+    pub source: CaptureSource,
+    pub old_name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResizedSource {
+    pub source: CaptureSource,
+    pub old_width: u32,
+    pub old_height: u32,
+}
+
+/// Payload of `capture-sources-changed`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CaptureSourcesChanged {
+    pub added: Vec<CaptureSource>,
+    pub removed: Vec<CaptureSource>,
+    pub renamed: Vec<RenamedSource>,
+    pub resized: Vec<ResizedSource>,
+    /// The full list after the change
+    pub sources: Vec<CaptureSource>,
+}
+
+impl CaptureSourcesChanged {
+    /// Compares two snapshots, matching sources by type and handle
+    pub fn between(previous: &[CaptureSource], current: &[CaptureSource]) -> Self {
+        let key = |source: &CaptureSource| (source.source_type, source.handle);
+        let before: HashMap<_, _> = previous
+            .iter()
+            .map(|source| (key(source), source))
+            .collect();
+        let after: HashMap<_, _> = current.iter().map(|source| (key(source), source)).collect();
+
+        let mut changes = Self {
+            sources: current.to_vec(),
+            ..Default::default()
+        };
+        for source in current {
+            let Some(old) = before.get(&key(source)) else {
+                changes.added.push(source.clone());
+                continue;
+            };
+            if old.name != source.name {
+                changes.renamed.push(RenamedSource {
+                    source: source.clone(),
+                    old_name: old.name.clone(),
+                });
+            }
+            if (old.width, old.height) != (source.width, source.height) {
+                changes.resized.push(ResizedSource {
+                    source: source.clone(),
+                    old_width: old.width,
+                    old_height: old.height,
+                });
+            }
+        }
+        changes.removed = previous
+            .iter()
+            .filter(|source| !after.contains_key(&key(source)))
+            .cloned()
+            .collect();
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.resized.is_empty()
+    }
+
+    fn was_removed(&self, source_type: CaptureSourceType, handle: isize) -> bool {
+        self.removed
+            .iter()
+            .any(|source| source.source_type == source_type && source.handle == handle)
+    }
+}
+
+/// Minimized windows drop out of the source list but are still there to record
+fn still_exists(source_type: CaptureSourceType, handle: isize) -> bool {
+    source_type == CaptureSourceType::Window
+        && unsafe { IsWindow(Some(HWND(handle as *mut _))) }.as_bool()
+}
+
+/// Watches monitors and windows for the lifetime of the app and emits
+/// `capture-sources-changed` whenever one appears, disappears, is renamed or resized. A
+/// recording whose source disappears is told about it.
+///
+/// Listing every source is slow, so it is only done when a top-level window event arrives or
+/// the monitor layout, which is cheap to read, has changed.
+pub fn watch_capture_sources(app: AppHandle) {
+    let (sender, receiver) = mpsc::channel();
+    *WINDOW_EVENTS.lock().unwrap() = Some(sender);
+    thread::spawn(hook_window_events);
+
+    thread::spawn(move || {
+        let mut monitors = monitor_layout();
+        let mut previous = CaptureSourceManager::list_capture_sources().unwrap_or_default();
+        loop {
+            match receiver.recv_timeout(MONITOR_POLL_INTERVAL) {
+                Ok(event) => {
+                    thread::sleep(SETTLE_TIME);
+                    let events: Vec<_> =
+                        std::iter::once(event).chain(receiver.try_iter()).collect();
+                    if !events.iter().any(|event| affects(event, &previous)) {
+                        continue;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if monitor_layout() == monitors {
+                        continue;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            monitors = monitor_layout();
+            let sources = match CaptureSourceManager::list_capture_sources() {
+                Ok(sources) => sources,
+                Err(e) => {
+                    eprintln!("Failed to list capture sources: {}", e);
+                    continue;
+                }
+            };
+            let changes = CaptureSourcesChanged::between(&previous, &sources);
+            previous = sources;
+            if changes.is_empty() {
+                continue;
+            }
+
+            notify_recording(&app, &changes);
+            if let Err(e) = app.emit("capture-sources-changed", &changes) {
+                eprintln!("Failed to emit capture-sources-changed event: {}", e);
+            }
+        }
+    });
+}
+
+/// Destroyed windows can't be told apart from child windows any more, so they only count
+/// if they were listed
+fn affects(&(event, hwnd): &WindowEvent, sources: &[CaptureSource]) -> bool {
+    event != EVENT_OBJECT_DESTROY
+        || sources
+            .iter()
+            .any(|source| source.source_type == CaptureSourceType::Window && source.handle == hwnd)
+}
+
+/// Handles and bounds of the connected monitors
+fn monitor_layout() -> Vec<(isize, RECT)> {
+    let monitors = Monitor::enumerate().unwrap_or_default();
+    monitors
+        .iter()
+        .map(|monitor| {
+            let hmonitor = HMONITOR(monitor.as_raw_hmonitor());
+            let mut info = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            let _ = unsafe { GetMonitorInfoW(hmonitor, &mut info) };
+            (hmonitor.0 as isize, info.rcMonitor)
+        })
+        .collect()
+}
+
+/// Installs the window event hook for the lifetime of the app. Out-of-context hooks are
+/// called on the installing thread while it pumps messages.
+fn hook_window_events() {
+    unsafe {
+        let hook = SetWinEventHook(
+            EVENT_OBJECT_CREATE,
+            EVENT_OBJECT_UNCLOAKED,
+            None,
+            Some(window_event),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT,
+        );
+        if hook.is_invalid() {
+            eprintln!("Failed to watch window events");
+            return;
+        }
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {}
+        let _ = UnhookWinEvent(hook);
+    }
+}
+
+/// Passes on events that can change the source list: top-level windows being created,
+/// destroyed, shown, hidden, cloaked, moved or renamed
+unsafe extern "system" fn window_event(
+    _hook: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    id_child: i32,
+    _thread: u32,
+    _time: u32,
+) {
+    let relevant = matches!(
+        event,
+        EVENT_OBJECT_CREATE
+            | EVENT_OBJECT_DESTROY
+            | EVENT_OBJECT_SHOW
+            | EVENT_OBJECT_HIDE
+            | EVENT_OBJECT_CLOAKED
+            | EVENT_OBJECT_UNCLOAKED
+            | EVENT_OBJECT_LOCATIONCHANGE
+            | EVENT_OBJECT_NAMECHANGE
+    );
+    if !relevant || id_object != OBJID_WINDOW.0 || id_child != CHILDID_SELF as i32 {
+        return;
+    }
+    // Destroyed windows have no ancestors left to check; the watcher sorts them out
+    if event != EVENT_OBJECT_DESTROY && GetAncestor(hwnd, GA_ROOT) != hwnd {
+        return;
+    }
+    if let Some(sender) = WINDOW_EVENTS.lock().unwrap().as_ref() {
+        let _ = sender.send((event, hwnd.0 as isize));
+    }
+}
+
+fn notify_recording(app: &AppHandle, changes: &CaptureSourcesChanged) {
+    let state = app.state::<Mutex<Option<RecordingSession>>>();
+    let session = state.lock().unwrap();
+    if let Some(session) = session.as_ref() {
+        for &(source_type, handle) in session.sources() {
+            if changes.was_removed(source_type, handle) && !still_exists(source_type, handle) {
+                session.source_lost(app, (source_type, handle));
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::ffi::c_void;
+
+use windows::Win32::{
+    Foundation::{HWND, RECT},
+    Graphics::{
+        Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS},
+        Gdi::{MonitorFromWindow, MONITOR_DEFAULTTONEAREST},
+    },
+    System::Com::{CoCreateInstance, CLSCTX_ALL},
+    UI::{
+        HiDpi::GetDpiForWindow,
+        Shell::{IVirtualDesktopManager, VirtualDesktopManager},
+        WindowsAndMessaging::GetWindowRect,
+    },
+};
+
+use crate::com::ComApartment;
+
+/// Screen rectangle of the visible window frame. `GetWindowRect` includes the invisible
+/// resize borders, so DWM's bounds are preferred.
+pub fn window_bounds(hwnd: isize) -> Option<RECT> {
+    let hwnd = HWND(hwnd as *mut c_void);
+    let mut rect = RECT::default();
+    let dwm = unsafe {
+        DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut RECT as *mut c_void,
+            std::mem::size_of::<RECT>() as u32,
+        )
+    };
+    if dwm.is_ok() {
+        return Some(rect);
+    }
+    unsafe { GetWindowRect(hwnd, &mut rect) }.ok()?;
+    Some(rect)
+}
+
+/// HMONITOR of the monitor showing the largest part of the window
+pub fn window_monitor(hwnd: isize) -> isize {
+    unsafe { MonitorFromWindow(HWND(hwnd as *mut c_void), MONITOR_DEFAULTTONEAREST) }.0 as isize
+}
+
+/// DPI of the window relative to 96, e.g. 1.5 at 150% scaling
+pub fn dpi_scale(hwnd: isize) -> f32 {
+    match unsafe { GetDpiForWindow(HWND(hwnd as *mut c_void)) } {
+        0 => 1.0,
+        dpi => dpi as f32 / 96.0,
+    }
+}
+
+/// Whether DWM hides the window even though it's "visible", e.g. suspended UWP apps or
+/// windows on another virtual desktop
+pub fn is_cloaked(hwnd: isize) -> bool {
+    let mut cloaked = 0u32;
+    let result = unsafe {
+        DwmGetWindowAttribute(
+            HWND(hwnd as *mut c_void),
+            DWMWA_CLOAKED,
+            &mut cloaked as *mut u32 as *mut c_void,
+            std::mem::size_of::<u32>() as u32,
+        )
+    };
+    result.is_ok() && cloaked != 0
+}
+
+/// Answers which virtual desktop windows are on. Keeps COM initialized while alive.
+pub struct VirtualDesktops {
+    manager: Option<IVirtualDesktopManager>,
+    _com: ComApartment,
+}
+
+impl VirtualDesktops {
+    pub fn open() -> Self {
+        let com = ComApartment::enter();
+        let manager = unsafe { CoCreateInstance(&VirtualDesktopManager, None, CLSCTX_ALL) }
+            .map_err(|e| eprintln!("Virtual desktop information unavailable: {}", e))
+            .ok();
+        Self { manager, _com: com }
+    }
+
+    /// False only when the window is known to be on another virtual desktop
+    pub fn is_on_current(&self, hwnd: isize) -> bool {
+        let Some(manager) = &self.manager else {
+            return true;
+        };
+        unsafe { manager.IsWindowOnCurrentVirtualDesktop(HWND(hwnd as *mut c_void)) }
+            .map_or(true, |current| current.as_bool())
+    }
+}
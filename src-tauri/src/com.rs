@@ -0,0 +1,22 @@
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+/// Keeps COM initialized on the current thread while alive
+pub struct ComApartment {
+    initialized: bool,
+}
+
+impl ComApartment {
+    pub fn enter() -> Self {
+        // Fails harmlessly if the thread already joined a different apartment
+        let initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.is_ok();
+        Self { initialized }
+    }
+}
+
+impl Drop for ComApartment {
+    fn drop(&mut self) {
+        if self.initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::audio::{self, AudioCapture, AudioDevice, AudioInput};
+
+/// Gets the active audio output and input devices
+#[tauri::command]
+pub async fn get_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    audio::list_devices().map_err(|e| e.to_string())
+}
+
+/// Starts emitting `audio-levels` for the given inputs without recording them,
+/// replacing any monitor that is already running
+#[tauri::command]
+pub async fn start_audio_monitor(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<Option<AudioCapture>>>,
+    audio: Vec<AudioInput>,
+) -> Result<String, String> {
+    let mut monitor_guard = state.lock().unwrap();
+    if let Some(mut monitor) = monitor_guard.take() {
+        monitor.stop();
+    }
+    if !audio.is_empty() {
+        *monitor_guard = Some(AudioCapture::monitor(audio, app));
+    }
+    Ok("Audio monitor started".into())
+}
+
+/// Stops the level preview started by `start_audio_monitor`
+#[tauri::command]
+pub async fn stop_audio_monitor(
+    state: State<'_, Mutex<Option<AudioCapture>>>,
+) -> Result<String, String> {
+    if let Some(mut monitor) = state.lock().unwrap().take() {
+        monitor.stop();
+    }
+    Ok("Audio monitor stopped".into())
+}
@@ -1,7 +1,73 @@
-use crate::{capture::CaptureSourceManager, types::CaptureSource};
+use std::sync::Mutex;
+
+use tauri::{ipc::Channel, State};
+
+use crate::{
+    capture::{
+        self, CaptureSourceManager, PreviewFrame, PreviewSettings, SourceThumbnail, WindowInfo,
+        WindowSelector,
+    },
+    recording::RecordingSession,
+    types::{CaptureSource, CaptureSourceType, Region},
+};
+
+/// Longest side of a thumbnail when the caller doesn't give one
+const DEFAULT_THUMBNAIL_SIZE: u32 = 320;
 
 /// Gets all available capture sources (monitors and windows)
 #[tauri::command]
 pub async fn get_capture_sources() -> Result<Vec<CaptureSource>, String> {
     CaptureSourceManager::get_all_capture_sources().map_err(|e| e.to_string())
 }
+
+/// Finds the window a selector describes, so scripts can check a selector before recording
+#[tauri::command]
+pub async fn find_window(selector: WindowSelector) -> Result<WindowInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || selector.resolve())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Gets a small JPEG of what a monitor or window currently shows
+#[tauri::command]
+pub async fn get_source_thumbnail(
+    handle: isize,
+    source_type: CaptureSourceType,
+    max_size: Option<u32>,
+) -> Result<SourceThumbnail, String> {
+    let max_size = max_size.unwrap_or(DEFAULT_THUMBNAIL_SIZE);
+    // Capturing blocks for up to the timeout, so keep it off the async workers
+    tauri::async_runtime::spawn_blocking(move || {
+        capture::get_source_thumbnail(handle, source_type, max_size)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Streams downscaled JPEG frames of a source to `on_frame` until `stop_preview` is called.
+/// While recording, the recorder feeds the preview instead of a separate capture.
+#[tauri::command]
+pub async fn start_preview(
+    state: State<'_, Mutex<Option<RecordingSession>>>,
+    handle: isize,
+    source_type: CaptureSourceType,
+    region: Option<Region>,
+    settings: Option<PreviewSettings>,
+    on_frame: Channel<PreviewFrame>,
+) -> Result<(), String> {
+    let recording = state.lock().unwrap().is_some();
+    let item = if recording {
+        None
+    } else {
+        Some(capture::capture_item(handle, source_type)?)
+    };
+    capture::start_preview(item, region, settings.unwrap_or_default(), on_frame)
+}
+
+/// Stops sending preview frames
+#[tauri::command]
+pub async fn stop_preview() -> Result<(), String> {
+    capture::stop_preview();
+    Ok(())
+}
@@ -1,7 +1,25 @@
+mod audio_commands;
 mod capture_commands;
 mod overlay_commands;
+mod preset_commands;
+mod profile_commands;
 mod recording_commands;
+mod settings_commands;
+mod webcam_commands;
 
-pub use capture_commands::get_capture_sources;
-pub use overlay_commands::{close_region_selector, open_region_selector, region_selected};
-pub use recording_commands::{start_recording, stop_recording};
+pub use audio_commands::{get_audio_devices, start_audio_monitor, stop_audio_monitor};
+pub use capture_commands::{
+    find_window, get_capture_sources, get_source_thumbnail, start_preview, stop_preview,
+};
+pub use overlay_commands::{
+    close_region_selector, get_snap_windows, open_region_selector, region_selected,
+};
+pub use preset_commands::{
+    apply_region_preset, delete_region_preset, list_region_presets, save_region_preset,
+};
+pub use profile_commands::{
+    delete_profile, export_profiles, get_profile, import_profiles, list_profiles, save_profile,
+};
+pub use recording_commands::{pause_recording, resume_recording, start_recording, stop_recording};
+pub use settings_commands::{get_settings, update_settings};
+pub use webcam_commands::get_webcam_devices;
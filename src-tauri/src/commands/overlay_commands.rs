@@ -2,8 +2,9 @@ use crate::{
     types::Region,
     window::{
         close_region_selector as close_region_selector_handler,
+        get_snap_windows as get_snap_windows_handler,
         open_region_selector as open_region_selector_handler,
-        region_selected as region_selected_handler,
+        region_selected as region_selected_handler, SnapWindow,
     },
 };
 
@@ -27,3 +28,9 @@ pub async fn close_region_selector(app: tauri::AppHandle) -> Result<(), String>
 pub async fn region_selected(app: tauri::AppHandle, coordinates: Region) -> Result<(), String> {
     region_selected_handler(app, coordinates).await
 }
+
+/// Lists the windows the region selector can snap to, topmost first
+#[tauri::command]
+pub fn get_snap_windows() -> Vec<SnapWindow> {
+    get_snap_windows_handler()
+}
@@ -0,0 +1,66 @@
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::{
+    capture::{CaptureSourceManager, MonitorInfo},
+    config::{RegionPresetStatus, RegionPresets},
+    types::Region,
+};
+
+fn monitors() -> Result<Vec<MonitorInfo>, String> {
+    CaptureSourceManager::get_monitors().map_err(|e| e.to_string())
+}
+
+/// Lists the region presets, flagging those that don't fit the current monitor layout
+#[tauri::command]
+pub async fn list_region_presets(
+    presets: State<'_, Mutex<RegionPresets>>,
+) -> Result<Vec<RegionPresetStatus>, String> {
+    let monitors = monitors()?;
+    Ok(presets.lock().unwrap().list(&monitors))
+}
+
+/// Saves a region of a monitor under `name`, replacing any preset with that name
+#[tauri::command]
+pub async fn save_region_preset(
+    presets: State<'_, Mutex<RegionPresets>>,
+    name: String,
+    monitor_handle: isize,
+    region: Region,
+) -> Result<RegionPresetStatus, String> {
+    let monitors = monitors()?;
+    presets
+        .lock()
+        .unwrap()
+        .save(&name, monitor_handle, region, &monitors)
+        .map_err(|e| e.to_string())
+}
+
+/// Looks up a preset for the monitors connected now. The result's `monitor_handle` and
+/// `region` are what to record.
+#[tauri::command]
+pub async fn apply_region_preset(
+    presets: State<'_, Mutex<RegionPresets>>,
+    name: String,
+) -> Result<RegionPresetStatus, String> {
+    let monitors = monitors()?;
+    presets
+        .lock()
+        .unwrap()
+        .resolve(&name, &monitors)
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes the preset named `name`
+#[tauri::command]
+pub async fn delete_region_preset(
+    presets: State<'_, Mutex<RegionPresets>>,
+    name: String,
+) -> Result<(), String> {
+    presets
+        .lock()
+        .unwrap()
+        .delete(&name)
+        .map_err(|e| e.to_string())
+}
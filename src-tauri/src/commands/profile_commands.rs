@@ -0,0 +1,83 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use tauri::State;
+
+use crate::config::{RecordingProfile, RecordingProfiles};
+
+/// Lists the recording profiles, sorted by name
+#[tauri::command]
+pub async fn list_profiles(
+    profiles: State<'_, Mutex<RecordingProfiles>>,
+) -> Result<Vec<RecordingProfile>, String> {
+    Ok(profiles.lock().unwrap().list())
+}
+
+/// Gets the profile named `name`
+#[tauri::command]
+pub async fn get_profile(
+    profiles: State<'_, Mutex<RecordingProfiles>>,
+    name: String,
+) -> Result<RecordingProfile, String> {
+    profiles
+        .lock()
+        .unwrap()
+        .get(&name)
+        .cloned()
+        .map_err(|e| e.to_string())
+}
+
+/// Creates a profile, or replaces the one with the same name
+#[tauri::command]
+pub async fn save_profile(
+    profiles: State<'_, Mutex<RecordingProfiles>>,
+    profile: RecordingProfile,
+) -> Result<RecordingProfile, String> {
+    profiles
+        .lock()
+        .unwrap()
+        .save(profile)
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes the profile named `name`
+#[tauri::command]
+pub async fn delete_profile(
+    profiles: State<'_, Mutex<RecordingProfiles>>,
+    name: String,
+) -> Result<(), String> {
+    profiles
+        .lock()
+        .unwrap()
+        .delete(&name)
+        .map_err(|e| e.to_string())
+}
+
+/// Writes the profiles named in `names`, or all of them, to a JSON file.
+/// Returns how many were written.
+#[tauri::command]
+pub async fn export_profiles(
+    profiles: State<'_, Mutex<RecordingProfiles>>,
+    path: PathBuf,
+    names: Option<Vec<String>>,
+) -> Result<usize, String> {
+    profiles
+        .lock()
+        .unwrap()
+        .export(&path, names.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Adds the profiles from a file written by `export_profiles`, replacing existing ones of
+/// the same name only if `overwrite` is set. Returns the names of the profiles added.
+#[tauri::command]
+pub async fn import_profiles(
+    profiles: State<'_, Mutex<RecordingProfiles>>,
+    path: PathBuf,
+    overwrite: Option<bool>,
+) -> Result<Vec<String>, String> {
+    profiles
+        .lock()
+        .unwrap()
+        .import(&path, overwrite.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
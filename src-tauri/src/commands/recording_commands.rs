@@ -2,66 +2,544 @@ use std::{
     sync::{atomic::AtomicBool, Arc, Mutex},
     thread,
 };
-use tauri::State;
+use tauri::{Manager, State};
 use windows_capture::{monitor::Monitor, window::Window, WindowsCaptureGraphicsCaptureItem};
 
+use super::find_window;
 use crate::{
-    recording::{start_recording as start_screen_recording, RecordingSession},
-    types::{CaptureSourceType, Region},
+    audio::{is_audio_file, open_normalized_writer, AudioCapture, AudioInput},
+    capture::{
+        self, CaptureSourceManager, DesktopCompositor, Scene, SceneCompositor, WindowSelector,
+    },
+    config::{ProfileSource, RecordingProfiles, RegionPresets, SettingsStore},
+    recording::{
+        is_gif_file, start_audio_recording, start_composite_recording,
+        start_recording as start_screen_recording, CaptureTarget, CompositeConfig, GifExport,
+        RecordingSession, TrackFiles,
+    },
+    types::{CaptureSourceType, RecordingOptions, Region},
+    window::{exclude_app_windows, restore_app_windows},
 };
 
-/// Starts a new recording session
+/// Frame rate desktop recordings are composited at
+const DESKTOP_FPS: u32 = 30;
+
+/// Starts a new recording session. The source is either `handle` and `source_type`, or a
+/// window `selector` for callers that don't know the HWND. Desktop recordings cover the
+/// `monitors` given, or all of them, and scene recordings arrange the layers of `scene`.
+/// Without `options` the saved settings' recording options are used.
+///
+/// Alternatively `profile` names a recording profile that stands in for all of these
+/// except `output_path`, which still overrides the profile's file naming when given.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn start_recording(
+    app: tauri::AppHandle,
     state: State<'_, Mutex<Option<RecordingSession>>>,
-    handle: isize,
-    source_type: CaptureSourceType,
-    output_path: String,
+    monitor: State<'_, Mutex<Option<AudioCapture>>>,
+    settings: State<'_, Mutex<SettingsStore>>,
+    profiles: State<'_, Mutex<RecordingProfiles>>,
+    presets: State<'_, Mutex<RegionPresets>>,
+    profile: Option<String>,
+    handle: Option<isize>,
+    source_type: Option<CaptureSourceType>,
+    selector: Option<WindowSelector>,
+    output_path: Option<String>,
     region: Option<Region>,
+    options: Option<RecordingOptions>,
+    audio: Option<Vec<AudioInput>>,
+    monitors: Option<Vec<isize>>,
+    scene: Option<Scene>,
 ) -> Result<String, String> {
+    let (handle, source_type, selector, output_path, region, options, audio, scene) = match profile
+    {
+        Some(name) => {
+            let recording = resolve_profile(&app, &profiles, &presets, &name)?;
+            println!("Recording with profile \"{}\"", name);
+            (
+                recording.handle,
+                Some(recording.source_type),
+                recording.selector,
+                output_path.unwrap_or(recording.output_path),
+                recording.region,
+                Some(recording.options),
+                Some(recording.audio),
+                recording.scene,
+            )
+        }
+        None => (
+            handle,
+            source_type,
+            selector,
+            output_path.ok_or("No output path given")?,
+            region,
+            options,
+            audio,
+            scene,
+        ),
+    };
+
+    let (handle, source_type) = match (selector, handle, source_type) {
+        (Some(selector), _, _) => {
+            let window = find_window(selector).await?;
+            println!("Selected window \"{}\" ({:#x})", window.title, window.hwnd);
+            (window.hwnd, CaptureSourceType::Window)
+        }
+        (None, Some(handle), Some(source_type)) => (handle, source_type),
+        // These don't record a single monitor or window, so they have no handle
+        (
+            None,
+            None,
+            Some(
+                source_type @ (CaptureSourceType::Audio
+                | CaptureSourceType::Desktop
+                | CaptureSourceType::Scene),
+            ),
+        ) => (0, source_type),
+        (None, None, Some(source_type)) => {
+            return Err(format!("A {:?} recording needs a handle", source_type))
+        }
+        (None, _, None) => return Err("No capture source given".into()),
+    };
+
+    // Windows picked by title may take a while to appear, so look them up before locking
+    let scene = match scene {
+        Some(scene) => Some(
+            tauri::async_runtime::spawn_blocking(move || scene.resolve_windows())
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?,
+        ),
+        None => None,
+    };
+
     // Used to prevent concurrent recordings
     let mut session_guard = state.lock().unwrap();
     if session_guard.is_some() {
         return Err("Recording already in progress".into());
     }
 
-    let item = match source_type {
+    let mut options = options.unwrap_or_else(|| settings.lock().unwrap().get().recording.clone());
+    let audio = audio.unwrap_or_default();
+    // GIFs are recorded to mp4 and converted once the recording is finished
+    let (gif, output_path) = if is_gif_file(&output_path) {
+        if !audio.is_empty() {
+            return Err("GIFs have no sound, so a GIF recording can't have audio inputs".into());
+        }
+        let (gif, video_path) = GifExport::prepare(output_path, &mut options);
+        (Some(gif), video_path)
+    } else {
+        (None, output_path)
+    };
+    // The recording opens the devices itself, so the level preview has to let go of them
+    if let Some(mut audio_monitor) = monitor.lock().unwrap().take() {
+        audio_monitor.stop();
+    }
+
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let pause_signal = Arc::new(AtomicBool::new(false));
+
+    let (target, item) = match source_type {
         CaptureSourceType::Monitor => {
-            let monitor = Monitor::from_raw_hmonitor(handle as *mut _);
-            WindowsCaptureGraphicsCaptureItem::try_from(monitor)
-                .map_err(|e| format!("Failed to create capture item: {}", e))?
+            let monitor = CaptureSourceManager::get_monitors()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|monitor| monitor.hmonitor == handle);
+            let target = CaptureTarget::Monitor {
+                left: monitor.as_ref().map_or(0, |m| m.left),
+                top: monitor.as_ref().map_or(0, |m| m.top),
+            };
+            let item = WindowsCaptureGraphicsCaptureItem::try_from(Monitor::from_raw_hmonitor(
+                handle as *mut _,
+            ))
+            .map_err(|e| format!("Failed to create capture item: {}", e))?;
+            (target, item)
         }
         CaptureSourceType::Window => {
-            let win = Window::from_raw_hwnd(handle as *mut _);
-            WindowsCaptureGraphicsCaptureItem::try_from(win)
-                .map_err(|e| format!("Failed to create capture item: {}", e))?
+            let item = WindowsCaptureGraphicsCaptureItem::try_from(Window::from_raw_hwnd(
+                handle as *mut _,
+            ))
+            .map_err(|e| format!("Failed to create capture item: {}", e))?;
+            (CaptureTarget::Window { hwnd: handle }, item)
+        }
+        CaptureSourceType::Audio => {
+            let thread_handle = start_audio_only(
+                app,
+                output_path,
+                audio,
+                options.separate_audio_tracks,
+                Arc::clone(&stop_signal),
+                Arc::clone(&pause_signal),
+            )?;
+            *session_guard = Some(RecordingSession::new(
+                stop_signal,
+                pause_signal,
+                thread_handle,
+                Vec::new(),
+            ));
+            return Ok("Recording started".into());
+        }
+        CaptureSourceType::Desktop => {
+            let thread_handle = start_desktop(
+                app,
+                output_path,
+                gif,
+                monitors.unwrap_or_default(),
+                region,
+                options,
+                audio,
+                Arc::clone(&stop_signal),
+                Arc::clone(&pause_signal),
+            )?;
+            *session_guard = Some(RecordingSession::new(
+                stop_signal,
+                pause_signal,
+                thread_handle,
+                vec![(source_type, handle)],
+            ));
+            return Ok("Recording started".into());
+        }
+        CaptureSourceType::Scene => {
+            let scene = scene.ok_or("A scene recording needs a scene")?;
+            Scene::check_options(&options)?;
+            let sources = scene.sources();
+            let thread_handle = start_scene(
+                app,
+                output_path,
+                gif,
+                scene,
+                options,
+                audio,
+                Arc::clone(&stop_signal),
+                Arc::clone(&pause_signal),
+            )?;
+            *session_guard = Some(RecordingSession::new(
+                stop_signal,
+                pause_signal,
+                thread_handle,
+                sources,
+            ));
+            return Ok("Recording started".into());
         }
     };
 
-    let stop_signal = Arc::new(AtomicBool::new(false));
-    let thread_handle = thread::spawn({
+    take_over_capture(
+        &app,
+        options.exclude_own_windows && matches!(target, CaptureTarget::Monitor { .. }),
+    );
+    let thread_handle = spawn_recording(app.clone(), Arc::clone(&stop_signal), gif, {
         let stop_signal = Arc::clone(&stop_signal);
+        let pause_signal = Arc::clone(&pause_signal);
         move || {
-            if let Err(e) = start_screen_recording(item, output_path, stop_signal, region) {
-                eprintln!("Recording error: {}", e);
-            }
+            start_screen_recording(
+                app,
+                item,
+                output_path,
+                stop_signal,
+                pause_signal,
+                region,
+                target,
+                options,
+                audio,
+            )
         }
     });
 
-    *session_guard = Some(RecordingSession::new(stop_signal, thread_handle));
+    *session_guard = Some(RecordingSession::new(
+        stop_signal,
+        pause_signal,
+        thread_handle,
+        vec![(source_type, handle)],
+    ));
     Ok("Recording started".into())
 }
 
+/// Starts recording several monitors composited into one video
+#[allow(clippy::too_many_arguments)]
+fn start_desktop(
+    app: tauri::AppHandle,
+    output_path: String,
+    gif: Option<GifExport>,
+    monitors: Vec<isize>,
+    region: Option<Region>,
+    options: RecordingOptions,
+    audio: Vec<AudioInput>,
+    stop_signal: Arc<AtomicBool>,
+    pause_signal: Arc<AtomicBool>,
+) -> Result<thread::JoinHandle<()>, String> {
+    let compositor =
+        DesktopCompositor::start(&monitors, region, options.cursor.capture_settings())?;
+    take_over_capture(&app, options.exclude_own_windows);
+    let config = CompositeConfig {
+        output_path,
+        fps: DESKTOP_FPS,
+        stop_signal,
+        pause_signal,
+        options,
+        audio,
+        app: app.clone(),
+    };
+
+    Ok(spawn_recording(
+        app,
+        Arc::clone(&config.stop_signal),
+        gif,
+        move || start_composite_recording(Box::new(compositor), config),
+    ))
+}
+
+/// What a profile records, in terms of `start_recording`'s parameters
+struct ProfileRecording {
+    handle: Option<isize>,
+    source_type: CaptureSourceType,
+    selector: Option<WindowSelector>,
+    output_path: String,
+    region: Option<Region>,
+    options: RecordingOptions,
+    audio: Vec<AudioInput>,
+    scene: Option<Scene>,
+}
+
+/// Finds the profile's source among the monitors and presets there are now, and names the
+/// output file
+fn resolve_profile(
+    app: &tauri::AppHandle,
+    profiles: &Mutex<RecordingProfiles>,
+    presets: &Mutex<RegionPresets>,
+    name: &str,
+) -> Result<ProfileRecording, String> {
+    let profile = profiles
+        .lock()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .map_err(|e| e.to_string())?;
+    let monitors = || CaptureSourceManager::get_monitors().map_err(|e| e.to_string());
+
+    let mut recording = ProfileRecording {
+        handle: None,
+        source_type: CaptureSourceType::Monitor,
+        selector: None,
+        output_path: String::new(),
+        region: None,
+        options: profile.options,
+        audio: profile.audio,
+        scene: None,
+    };
+    match profile.source {
+        ProfileSource::Monitor { monitor_id, region } => {
+            let monitors = monitors()?;
+            // The primary monitor is the one at the origin
+            let monitor = match &monitor_id {
+                Some(id) => monitors.iter().find(|monitor| &monitor.stable_id == id),
+                None => monitors
+                    .iter()
+                    .find(|monitor| monitor.left == 0 && monitor.top == 0)
+                    .or(monitors.first()),
+            }
+            .ok_or("The profile's monitor isn't connected")?;
+            recording.handle = Some(monitor.hmonitor);
+            recording.region = region;
+        }
+        ProfileSource::RegionPreset { name } => {
+            let preset = presets
+                .lock()
+                .unwrap()
+                .resolve(&name, &monitors()?)
+                .map_err(|e| e.to_string())?;
+            recording.handle = preset.monitor_handle;
+            recording.region = Some(preset.preset.region);
+        }
+        ProfileSource::Window { selector } => {
+            recording.source_type = CaptureSourceType::Window;
+            recording.selector = Some(selector);
+        }
+        ProfileSource::Desktop => recording.source_type = CaptureSourceType::Desktop,
+        ProfileSource::Scene { scene } => {
+            recording.source_type = CaptureSourceType::Scene;
+            recording.scene = Some(scene);
+        }
+        ProfileSource::Audio => recording.source_type = CaptureSourceType::Audio,
+    }
+
+    let videos = app.path().video_dir().map_err(|e| e.to_string())?;
+    let output_path = profile.output.path_for(&profile.name, &videos);
+    if let Some(dir) = output_path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    recording.output_path = output_path.to_string_lossy().into_owned();
+    Ok(recording)
+}
+
+/// Starts recording the sources of a scene arranged into one video
+#[allow(clippy::too_many_arguments)]
+fn start_scene(
+    app: tauri::AppHandle,
+    output_path: String,
+    gif: Option<GifExport>,
+    scene: Scene,
+    options: RecordingOptions,
+    audio: Vec<AudioInput>,
+    stop_signal: Arc<AtomicBool>,
+    pause_signal: Arc<AtomicBool>,
+) -> Result<thread::JoinHandle<()>, String> {
+    let fps = scene.fps;
+    // Validates the scene and opens its sources, either of which can fail
+    let compositor = SceneCompositor::start(scene, options.cursor.capture_settings())?;
+    take_over_capture(&app, options.exclude_own_windows);
+    let config = CompositeConfig {
+        output_path,
+        fps,
+        stop_signal,
+        pause_signal,
+        options,
+        audio,
+        app: app.clone(),
+    };
+
+    Ok(spawn_recording(
+        app,
+        Arc::clone(&config.stop_signal),
+        gif,
+        move || start_composite_recording(Box::new(compositor), config),
+    ))
+}
+
+/// Starts recording the audio inputs to a WAV, FLAC or Opus file without capturing video
+fn start_audio_only(
+    app: tauri::AppHandle,
+    output_path: String,
+    audio: Vec<AudioInput>,
+    separate_tracks: bool,
+    stop_signal: Arc<AtomicBool>,
+    pause_signal: Arc<AtomicBool>,
+) -> Result<thread::JoinHandle<()>, String> {
+    if audio.is_empty() {
+        return Err("Audio-only recording needs at least one audio input".into());
+    }
+    if !is_audio_file(&output_path) {
+        return Err("Audio-only recordings must be saved as .wav, .flac or .opus".into());
+    }
+    // The mix is normalized to the first loudness target among the inputs, if any has one
+    let loudness = audio
+        .iter()
+        .find_map(|input| input.filters.loudness.clone());
+    // Opened here so a bad path is reported before the recording starts
+    let writer = open_normalized_writer(&output_path, loudness).map_err(|e| e.to_string())?;
+    let tracks = (separate_tracks && audio.len() > 1)
+        .then(|| TrackFiles::create(&output_path, &audio))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    Ok(spawn_recording(
+        app.clone(),
+        Arc::clone(&stop_signal),
+        None,
+        move || {
+            start_audio_recording(
+                app,
+                writer,
+                tracks,
+                output_path,
+                audio,
+                stop_signal,
+                pause_signal,
+            )
+        },
+    ))
+}
+
+/// Hands the capture over to a recording that is about to start. Called once nothing can
+/// fail before `spawn_recording`, whose thread puts the windows back.
+fn take_over_capture(app: &tauri::AppHandle, exclude_own_windows: bool) {
+    // Two captures of the same source would compete, so the recorder feeds the preview now
+    capture::stop_preview_capture();
+    if exclude_own_windows {
+        exclude_app_windows(app);
+    }
+}
+
+/// Runs a recording on its own thread. However the recording ends, whether stopped, failed
+/// or because its source closed, framelet's windows are made capturable again afterwards
+/// and the session, if it is still the one stopped by `stop_signal`, is cleared.
+/// Recordings that finish cleanly are then converted to `gif` if one was asked for.
+fn spawn_recording<F, E>(
+    app: tauri::AppHandle,
+    stop_signal: Arc<AtomicBool>,
+    gif: Option<GifExport>,
+    record: F,
+) -> thread::JoinHandle<()>
+where
+    F: FnOnce() -> Result<(), E> + Send + 'static,
+    E: std::fmt::Display,
+{
+    thread::spawn(move || {
+        let recorded = record();
+        if let Err(e) = &recorded {
+            eprintln!("Recording error: {}", e);
+        }
+        restore_app_windows(&app);
+
+        // Converting takes a while, so it shouldn't hold up stopping or the next recording
+        if let (Ok(()), Some(gif)) = (&recorded, gif) {
+            let app = app.clone();
+            thread::spawn(move || gif.run(&app));
+        }
+
+        // Waits for the session to be stored if the recording ended before start_recording did
+        let state = app.state::<Mutex<Option<RecordingSession>>>();
+        let mut session = state.lock().unwrap();
+        if session
+            .as_ref()
+            .is_some_and(|session| session.is_stopped_by(&stop_signal))
+        {
+            println!("Recording ended on its own");
+            *session = None;
+        }
+    })
+}
+
 /// Stops the current recording session
 #[tauri::command]
 pub async fn stop_recording(
     state: State<'_, Mutex<Option<RecordingSession>>>,
 ) -> Result<String, String> {
-    let mut session_guard = state.lock().unwrap();
-    if let Some(mut session) = session_guard.take() {
+    // Taken out so the lock isn't held while the recording finishes
+    let session = state.lock().unwrap().take();
+    if let Some(mut session) = session {
+        // The recording thread makes the app windows capturable again as it exits
         session.stop()?;
         Ok("Recording stopped".into())
     } else {
         Err("No recording in progress".into())
     }
 }
+
+/// Pauses the current recording session
+#[tauri::command]
+pub async fn pause_recording(
+    state: State<'_, Mutex<Option<RecordingSession>>>,
+) -> Result<String, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(session) => {
+            session.pause();
+            Ok("Recording paused".into())
+        }
+        None => Err("No recording in progress".into()),
+    }
+}
+
+/// Resumes a paused recording session
+#[tauri::command]
+pub async fn resume_recording(
+    state: State<'_, Mutex<Option<RecordingSession>>>,
+) -> Result<String, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(session) => {
+            session.resume();
+            Ok("Recording resumed".into())
+        }
+        None => Err("No recording in progress".into()),
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Mutex;
+
+use tauri::{Emitter, State};
+
+use crate::config::{Settings, SettingsStore};
+
+/// Gets the saved settings
+#[tauri::command]
+pub async fn get_settings(settings: State<'_, Mutex<SettingsStore>>) -> Result<Settings, String> {
+    Ok(settings.lock().unwrap().get().clone())
+}
+
+/// Merges `changes`, a partial settings object, into the settings and saves them.
+/// Emits `settings-changed` with the new settings if any change was saved. Invalid changes
+/// are left out and reported in the error, while the valid ones are still saved.
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    settings: State<'_, Mutex<SettingsStore>>,
+    changes: serde_json::Value,
+) -> Result<Settings, String> {
+    let (updated, update) = {
+        let mut settings = settings.lock().unwrap();
+        let update = settings.update(changes).map_err(|e| e.to_string())?;
+        (settings.get().clone(), update)
+    };
+
+    if update.saved {
+        app.emit("settings-changed", &updated)
+            .map_err(|e| format!("Failed to emit settings-changed event: {}", e))?;
+    }
+    if !update.rejected.is_empty() {
+        let reasons: Vec<String> = update
+            .rejected
+            .iter()
+            .map(|change| format!("{}: {}", change.field, change.reason))
+            .collect();
+        return Err(format!(
+            "Some settings weren't saved: {}",
+            reasons.join("; ")
+        ));
+    }
+    Ok(updated)
+}
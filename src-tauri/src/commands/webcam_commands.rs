@@ -0,0 +1,7 @@
+use crate::webcam::{self, WebcamDevice};
+
+/// Gets the video capture devices that can be used for the webcam overlay
+#[tauri::command]
+pub async fn get_webcam_devices() -> Result<Vec<WebcamDevice>, String> {
+    webcam::list_devices().map_err(|e| e.to_string())
+}
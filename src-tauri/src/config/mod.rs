@@ -0,0 +1,16 @@
+mod profiles;
+mod region_presets;
+mod settings;
+mod store;
+
+pub use profiles::{
+    OutputNaming, ProfileError, ProfileSource, RecordingProfile, RecordingProfiles, PROFILES_FILE,
+};
+pub use region_presets::{
+    PresetError, RegionPreset, RegionPresetStatus, RegionPresets, REGION_PRESETS_FILE,
+};
+pub use settings::{
+    AudioSettings, CaptureMode, OutputSettings, RejectedChange, Settings, SettingsError,
+    SettingsStore, SettingsUpdate, SourceSettings, WebcamSettings, SETTINGS_FILE, SETTINGS_VERSION,
+};
+pub use store::{config_file, read_json, write_json, StoreError};
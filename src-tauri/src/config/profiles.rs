@@ -0,0 +1,602 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use windows::Win32::System::SystemInformation::GetLocalTime;
+
+use super::store::{move_aside, read_json, write_json, StoreError};
+use crate::{
+    audio::{is_audio_file, AudioInput},
+    capture::{Scene, WindowSelector},
+    recording::is_gif_file,
+    types::{RecordingOptions, Region},
+};
+
+pub const PROFILES_FILE: &str = "profiles.json";
+
+/// Version of the export format, bumped when older files can't be read as they are
+const EXPORT_VERSION: u32 = 1;
+
+/// Everything needed to start a recording, saved under a name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingProfile {
+    pub name: String,
+    pub source: ProfileSource,
+    #[serde(default)]
+    pub options: RecordingOptions,
+    #[serde(default)]
+    pub audio: Vec<AudioInput>,
+    #[serde(default)]
+    pub output: OutputNaming,
+}
+
+/// What a profile records. Handles don't survive restarts, so sources are described instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProfileSource {
+    /// A monitor by stable ID, or the primary one when unset; all of it unless `region` is set
+    Monitor {
+        #[serde(default)]
+        monitor_id: Option<String>,
+        #[serde(default)]
+        region: Option<Region>,
+    },
+    /// A saved region preset
+    RegionPreset {
+        name: String,
+    },
+    Window {
+        selector: WindowSelector,
+    },
+    /// All monitors composited together
+    Desktop,
+    Scene {
+        scene: Scene,
+    },
+    /// No video, only the profile's audio inputs
+    Audio,
+}
+
+/// Where a profile's recordings are saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputNaming {
+    /// The user's Videos folder when empty
+    pub directory: String,
+    /// File name without the extension. `{profile}`, `{date}` and `{time}` are filled in.
+    pub pattern: String,
+    /// `mp4` or `gif`, or `wav`, `flac`, `opus` or `ogg` for audio-only profiles
+    pub extension: String,
+}
+
+impl Default for OutputNaming {
+    fn default() -> Self {
+        Self {
+            directory: String::new(),
+            pattern: "{profile} {date} {time}".into(),
+            extension: "mp4".into(),
+        }
+    }
+}
+
+/// File written by `export` and read by `import`
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileExport {
+    version: u32,
+    profiles: Vec<RecordingProfile>,
+}
+
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    #[error("a profile needs a name")]
+    EmptyName,
+
+    #[error("no profile named \"{0}\"")]
+    NotFound(String),
+
+    #[error("profile \"{name}\" is invalid: {reason}")]
+    Invalid { name: String, reason: String },
+
+    #[error("the file has more than one profile named \"{0}\"")]
+    Duplicate(String),
+
+    #[error("profile export version {0} is newer than this version of the app supports")]
+    TooNew(u32),
+
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl Serialize for ProfileError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl RecordingProfile {
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        if self.name.trim().is_empty() {
+            return Err(ProfileError::EmptyName);
+        }
+        let invalid = |reason: &str| ProfileError::Invalid {
+            name: self.name.clone(),
+            reason: reason.to_string(),
+        };
+
+        let file_name = format!("recording.{}", self.output.extension);
+        match &self.source {
+            ProfileSource::Audio => {
+                if !is_audio_file(&file_name) {
+                    return Err(invalid("audio-only output must be wav, flac, opus or ogg"));
+                }
+                if self.audio.is_empty() {
+                    return Err(invalid("an audio-only profile needs audio inputs"));
+                }
+            }
+            _ if is_gif_file(&file_name) && !self.audio.is_empty() => {
+                return Err(invalid(
+                    "GIFs have no sound, so a GIF profile can't have audio inputs",
+                ));
+            }
+            _ if !self.output.extension.eq_ignore_ascii_case("mp4") && !is_gif_file(&file_name) => {
+                return Err(invalid("video output must be mp4 or gif"));
+            }
+            ProfileSource::Monitor {
+                region: Some(region),
+                ..
+            } if region.width == 0 || region.height == 0 => {
+                return Err(invalid("the region is empty"));
+            }
+            ProfileSource::Scene { scene } => {
+                scene.validate().map_err(|e| invalid(e.as_str()))?;
+                Scene::check_options(&self.options).map_err(|e| invalid(e.as_str()))?;
+            }
+            _ => {}
+        }
+        if self.output.pattern.trim().is_empty() {
+            return Err(invalid("the file name pattern is empty"));
+        }
+        Ok(())
+    }
+}
+
+impl OutputNaming {
+    /// Path for a recording starting now, numbered if a file of that name already exists.
+    /// `default_dir` is used when no directory is set.
+    pub fn path_for(&self, profile: &str, default_dir: &Path) -> PathBuf {
+        let now = unsafe { GetLocalTime() };
+        let date = format!("{:04}-{:02}-{:02}", now.wYear, now.wMonth, now.wDay);
+        let time = format!("{:02}-{:02}-{:02}", now.wHour, now.wMinute, now.wSecond);
+        let stem = self.file_stem(profile, &date, &time);
+
+        let dir = match self.directory.trim() {
+            "" => default_dir.to_path_buf(),
+            dir => PathBuf::from(dir),
+        };
+        let mut path = dir.join(format!("{}.{}", stem, self.extension));
+        let mut number = 2;
+        while path.exists() {
+            path = dir.join(format!("{} ({}).{}", stem, number, self.extension));
+            number += 1;
+        }
+        path
+    }
+
+    /// The pattern filled in, with characters Windows doesn't allow in file names replaced
+    fn file_stem(&self, profile: &str, date: &str, time: &str) -> String {
+        let stem: String = self
+            .pattern
+            .replace("{profile}", profile)
+            .replace("{date}", date)
+            .replace("{time}", time)
+            .chars()
+            .map(|c| match c {
+                '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+        stem.trim().to_string()
+    }
+}
+
+/// Recording profiles, saved to a JSON file whenever they change
+pub struct RecordingProfiles {
+    path: PathBuf,
+    profiles: Vec<RecordingProfile>,
+}
+
+impl RecordingProfiles {
+    /// Loads the profiles, starting empty if the file is missing or unreadable. An unreadable
+    /// file is moved aside so saving doesn't overwrite it.
+    pub fn load(path: PathBuf) -> Self {
+        let profiles = read_json(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load recording profiles: {}", e);
+            move_aside(&path);
+            None
+        });
+        Self {
+            path,
+            profiles: profiles.unwrap_or_default(),
+        }
+    }
+
+    /// All profiles, sorted by name
+    pub fn list(&self) -> Vec<RecordingProfile> {
+        let mut profiles = self.profiles.clone();
+        profiles.sort_by_key(|profile| profile.name.to_lowercase());
+        profiles
+    }
+
+    pub fn get(&self, name: &str) -> Result<&RecordingProfile, ProfileError> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| ProfileError::NotFound(name.to_string()))
+    }
+
+    /// Adds the profile, replacing any with the same name
+    pub fn save(
+        &mut self,
+        mut profile: RecordingProfile,
+    ) -> Result<RecordingProfile, ProfileError> {
+        profile.name = profile.name.trim().to_string();
+        profile.validate()?;
+
+        let mut profiles = self.profiles.clone();
+        profiles.retain(|existing| existing.name != profile.name);
+        profiles.push(profile.clone());
+        self.write(profiles)?;
+        Ok(profile)
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), ProfileError> {
+        self.get(name)?;
+        let mut profiles = self.profiles.clone();
+        profiles.retain(|profile| profile.name != name);
+        self.write(profiles)
+    }
+
+    /// Writes the named profiles, or all of them, to a file that `import` can read
+    pub fn export(&self, path: &Path, names: Option<&[String]>) -> Result<usize, ProfileError> {
+        let profiles = match names {
+            Some(names) => names
+                .iter()
+                .map(|name| self.get(name).cloned())
+                .collect::<Result<Vec<_>, _>>()?,
+            None => self.list(),
+        };
+        let count = profiles.len();
+        write_json(
+            path,
+            &ProfileExport {
+                version: EXPORT_VERSION,
+                profiles,
+            },
+        )?;
+        Ok(count)
+    }
+
+    /// Adds the profiles from an exported file. Profiles whose name is taken are skipped
+    /// unless `overwrite` is set. Returns the names of the profiles added.
+    pub fn import(&mut self, path: &Path, overwrite: bool) -> Result<Vec<String>, ProfileError> {
+        let mut export: ProfileExport = read_json(path)?.ok_or_else(|| StoreError::Io {
+            path: path.to_owned(),
+            source: std::io::ErrorKind::NotFound.into(),
+        })?;
+        if export.version > EXPORT_VERSION {
+            return Err(ProfileError::TooNew(export.version));
+        }
+        // Checked up front so a bad file doesn't leave a partial import behind
+        let mut names = HashSet::new();
+        for profile in &mut export.profiles {
+            profile.name = profile.name.trim().to_string();
+            profile.validate()?;
+            if !names.insert(profile.name.clone()) {
+                return Err(ProfileError::Duplicate(profile.name.clone()));
+            }
+        }
+
+        let mut profiles = self.profiles.clone();
+        let mut imported = Vec::new();
+        for profile in export.profiles {
+            let exists = profiles
+                .iter()
+                .any(|existing| existing.name == profile.name);
+            if exists && !overwrite {
+                println!(
+                    "Skipping profile \"{}\", which already exists",
+                    profile.name
+                );
+                continue;
+            }
+            profiles.retain(|existing| existing.name != profile.name);
+            imported.push(profile.name.clone());
+            profiles.push(profile);
+        }
+        self.write(profiles)?;
+        Ok(imported)
+    }
+
+    fn write(&mut self, profiles: Vec<RecordingProfile>) -> Result<(), ProfileError> {
+        write_json(&self.path, &profiles)?;
+        self.profiles = profiles;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// A fresh temporary directory
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("framelet-profiles-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn profile(name: &str, source: ProfileSource) -> RecordingProfile {
+        RecordingProfile {
+            name: name.into(),
+            source,
+            options: RecordingOptions::default(),
+            audio: Vec::new(),
+            output: OutputNaming::default(),
+        }
+    }
+
+    fn monitor() -> ProfileSource {
+        ProfileSource::Monitor {
+            monitor_id: None,
+            region: None,
+        }
+    }
+
+    fn invalid(profile: &RecordingProfile) -> String {
+        match profile.validate() {
+            Err(ProfileError::Invalid { reason, .. }) => reason,
+            other => panic!("expected an invalid profile, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fills_in_and_sanitises_the_file_name() {
+        let naming = OutputNaming::default();
+        assert_eq!(
+            naming.file_stem("Talk", "2026-10-18", "09-30-00"),
+            "Talk 2026-10-18 09-30-00"
+        );
+
+        let naming = OutputNaming {
+            pattern: "  {profile}: a/b\\c <d>|\"e\"?*\t ".into(),
+            ..OutputNaming::default()
+        };
+        assert_eq!(naming.file_stem("x", "", ""), "x_ a_b_c _d___e____");
+    }
+
+    #[test]
+    fn numbers_names_that_are_taken() {
+        let dir = temp_dir("numbering");
+        let naming = OutputNaming {
+            directory: dir.to_string_lossy().into_owned(),
+            pattern: "{profile}".into(),
+            ..OutputNaming::default()
+        };
+        let first = naming.path_for("Talk", Path::new("unused"));
+        assert_eq!(first, dir.join("Talk.mp4"));
+
+        fs::write(&first, b"").unwrap();
+        let second = naming.path_for("Talk", Path::new("unused"));
+        assert_eq!(second, dir.join("Talk (2).mp4"));
+
+        fs::write(&second, b"").unwrap();
+        assert_eq!(
+            naming.path_for("Talk", Path::new("unused")),
+            dir.join("Talk (3).mp4")
+        );
+    }
+
+    #[test]
+    fn uses_the_default_directory_when_none_is_set() {
+        let dir = temp_dir("default-dir");
+        let naming = OutputNaming {
+            directory: "  ".into(),
+            pattern: "{profile}".into(),
+            ..OutputNaming::default()
+        };
+        assert_eq!(naming.path_for("Talk", &dir), dir.join("Talk.mp4"));
+    }
+
+    #[test]
+    fn accepts_a_plain_monitor_profile() {
+        profile("Talk", monitor()).validate().unwrap();
+    }
+
+    #[test]
+    fn needs_a_name() {
+        assert!(matches!(
+            profile("  ", monitor()).validate(),
+            Err(ProfileError::EmptyName)
+        ));
+    }
+
+    #[test]
+    fn checks_audio_only_profiles() {
+        let mut podcast = profile("Podcast", ProfileSource::Audio);
+        podcast.output.extension = "flac".into();
+        assert_eq!(
+            invalid(&podcast),
+            "an audio-only profile needs audio inputs"
+        );
+
+        podcast.audio.push(AudioInput::default());
+        podcast.validate().unwrap();
+
+        podcast.output.extension = "mp4".into();
+        assert_eq!(
+            invalid(&podcast),
+            "audio-only output must be wav, flac, opus or ogg"
+        );
+    }
+
+    #[test]
+    fn video_must_be_mp4_or_gif() {
+        let mut talk = profile("Talk", monitor());
+        talk.output.extension = "mkv".into();
+        assert_eq!(invalid(&talk), "video output must be mp4 or gif");
+
+        talk.output.extension = "MP4".into();
+        talk.validate().unwrap();
+        talk.output.extension = "gif".into();
+        talk.validate().unwrap();
+    }
+
+    #[test]
+    fn gif_profiles_have_no_audio() {
+        let mut repro = profile("Bug repro", monitor());
+        repro.output.extension = "gif".into();
+        repro.options.video.frame_rate = Some(10);
+        repro.validate().unwrap();
+
+        repro.audio.push(AudioInput::default());
+        assert_eq!(
+            invalid(&repro),
+            "GIFs have no sound, so a GIF profile can't have audio inputs"
+        );
+    }
+
+    #[test]
+    fn rejects_empty_regions_and_patterns() {
+        let mut talk = profile(
+            "Talk",
+            ProfileSource::Monitor {
+                monitor_id: None,
+                region: Some(Region {
+                    x: 0,
+                    y: 0,
+                    width: 0,
+                    height: 720,
+                }),
+            },
+        );
+        assert_eq!(invalid(&talk), "the region is empty");
+
+        talk.source = monitor();
+        talk.output.pattern = " ".into();
+        assert_eq!(invalid(&talk), "the file name pattern is empty");
+    }
+
+    #[test]
+    fn round_trips_through_export_and_import() {
+        let dir = temp_dir("round-trip");
+        let mut source = RecordingProfiles::load(dir.join("source.json"));
+        source.save(profile("Talk", monitor())).unwrap();
+        source
+            .save(profile("Desktop", ProfileSource::Desktop))
+            .unwrap();
+
+        let file = dir.join("export.json");
+        assert_eq!(source.export(&file, None).unwrap(), 2);
+
+        let mut target = RecordingProfiles::load(dir.join("target.json"));
+        assert_eq!(target.import(&file, false).unwrap(), ["Desktop", "Talk"]);
+        let names: Vec<_> = target.list().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["Desktop", "Talk"]);
+        assert!(matches!(
+            target.get("Desktop").unwrap().source,
+            ProfileSource::Desktop
+        ));
+
+        // Saved, so a fresh load sees them too
+        assert_eq!(
+            RecordingProfiles::load(dir.join("target.json"))
+                .list()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn skips_existing_profiles_unless_overwriting() {
+        let dir = temp_dir("overwrite");
+        let file = dir.join("export.json");
+        let mut source = RecordingProfiles::load(dir.join("source.json"));
+        source
+            .save(profile("Talk", ProfileSource::Desktop))
+            .unwrap();
+        source.export(&file, Some(&["Talk".into()])).unwrap();
+
+        let mut target = RecordingProfiles::load(dir.join("target.json"));
+        target.save(profile("Talk", monitor())).unwrap();
+        assert!(target.import(&file, false).unwrap().is_empty());
+        assert!(matches!(
+            target.get("Talk").unwrap().source,
+            ProfileSource::Monitor { .. }
+        ));
+
+        assert_eq!(target.import(&file, true).unwrap(), ["Talk"]);
+        assert!(matches!(
+            target.get("Talk").unwrap().source,
+            ProfileSource::Desktop
+        ));
+    }
+
+    #[test]
+    fn trims_imported_names_and_rejects_duplicates() {
+        let dir = temp_dir("import-names");
+        let file = dir.join("export.json");
+        let export = |names: &[&str]| {
+            let profiles = names.iter().map(|name| profile(name, monitor())).collect();
+            write_json(
+                &file,
+                &ProfileExport {
+                    version: EXPORT_VERSION,
+                    profiles,
+                },
+            )
+            .unwrap();
+        };
+
+        let mut profiles = RecordingProfiles::load(dir.join(PROFILES_FILE));
+        export(&[" Talk "]);
+        assert_eq!(profiles.import(&file, false).unwrap(), ["Talk"]);
+
+        export(&["Demo", " Demo"]);
+        assert!(matches!(
+            profiles.import(&file, true),
+            Err(ProfileError::Duplicate(name)) if name == "Demo"
+        ));
+        // Nothing from the bad file was added
+        assert!(profiles.get("Demo").is_err());
+    }
+
+    #[test]
+    fn refuses_newer_exports() {
+        let dir = temp_dir("too-new");
+        let file = dir.join("export.json");
+        write_json(
+            &file,
+            &ProfileExport {
+                version: EXPORT_VERSION + 1,
+                profiles: Vec::new(),
+            },
+        )
+        .unwrap();
+
+        let mut profiles = RecordingProfiles::load(dir.join(PROFILES_FILE));
+        assert!(matches!(
+            profiles.import(&file, false),
+            Err(ProfileError::TooNew(version)) if version == EXPORT_VERSION + 1
+        ));
+    }
+}
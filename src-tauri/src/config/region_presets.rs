@@ -0,0 +1,310 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::store::{move_aside, read_json, write_json, StoreError};
+use crate::{capture::MonitorInfo, types::Region};
+
+pub const REGION_PRESETS_FILE: &str = "region-presets.json";
+
+/// A named region on a particular monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionPreset {
+    pub name: String,
+    /// Stable ID of the monitor, see `MonitorInfo::stable_id`
+    pub monitor_id: String,
+    /// Shown while the monitor isn't connected
+    pub monitor_name: String,
+    /// Relative to the monitor's top left corner, in physical pixels
+    pub region: Region,
+}
+
+/// A preset checked against the monitors connected now
+#[derive(Debug, Clone, Serialize)]
+pub struct RegionPresetStatus {
+    #[serde(flatten)]
+    pub preset: RegionPreset,
+    /// HMONITOR of the preset's monitor; `None` when it isn't connected
+    pub monitor_handle: Option<isize>,
+    /// Why the preset can't be applied; `None` when it can
+    pub invalid_reason: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum PresetError {
+    #[error("a preset needs a name")]
+    EmptyName,
+
+    #[error("no preset named \"{0}\"")]
+    NotFound(String),
+
+    #[error("no connected monitor with handle {0:#x}")]
+    UnknownMonitor(isize),
+
+    #[error("preset \"{name}\" can't be used: {reason}")]
+    Invalid { name: String, reason: String },
+
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl Serialize for PresetError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Region presets, saved to a JSON file whenever they change
+pub struct RegionPresets {
+    path: PathBuf,
+    presets: Vec<RegionPreset>,
+}
+
+impl RegionPresets {
+    /// Loads the presets, starting empty if the file is missing or unreadable. An unreadable
+    /// file is moved aside so saving doesn't overwrite it.
+    pub fn load(path: PathBuf) -> Self {
+        let presets = read_json(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load region presets: {}", e);
+            move_aside(&path);
+            None
+        });
+        Self {
+            path,
+            presets: presets.unwrap_or_default(),
+        }
+    }
+
+    /// All presets, sorted by name, with whether they still fit the monitor layout
+    pub fn list(&self, monitors: &[MonitorInfo]) -> Vec<RegionPresetStatus> {
+        let mut statuses: Vec<_> = self
+            .presets
+            .iter()
+            .map(|preset| check(preset, monitors))
+            .collect();
+        statuses.sort_by_key(|status| status.preset.name.to_lowercase());
+        statuses
+    }
+
+    /// Saves `region` on the monitor `monitor_handle` under `name`, replacing any preset
+    /// with that name
+    pub fn save(
+        &mut self,
+        name: &str,
+        monitor_handle: isize,
+        region: Region,
+        monitors: &[MonitorInfo],
+    ) -> Result<RegionPresetStatus, PresetError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(PresetError::EmptyName);
+        }
+        let monitor = monitors
+            .iter()
+            .find(|monitor| monitor.hmonitor == monitor_handle)
+            .ok_or(PresetError::UnknownMonitor(monitor_handle))?;
+
+        let preset = RegionPreset {
+            name: name.to_string(),
+            monitor_id: monitor.stable_id.clone(),
+            monitor_name: monitor.name.clone(),
+            region,
+        };
+        if let Some(reason) = does_not_fit(&preset.region, monitor) {
+            return Err(PresetError::Invalid {
+                name: preset.name,
+                reason,
+            });
+        }
+
+        let mut presets = self.presets.clone();
+        presets.retain(|existing| existing.name != preset.name);
+        presets.push(preset.clone());
+        write_json(&self.path, &presets)?;
+        self.presets = presets;
+
+        Ok(check(&preset, monitors))
+    }
+
+    /// The preset named `name`, if it can be applied to the current monitors
+    pub fn resolve(
+        &self,
+        name: &str,
+        monitors: &[MonitorInfo],
+    ) -> Result<RegionPresetStatus, PresetError> {
+        let preset = self
+            .presets
+            .iter()
+            .find(|preset| preset.name == name)
+            .ok_or_else(|| PresetError::NotFound(name.to_string()))?;
+
+        let status = check(preset, monitors);
+        match status.invalid_reason {
+            Some(reason) => Err(PresetError::Invalid {
+                name: name.to_string(),
+                reason,
+            }),
+            None => Ok(status),
+        }
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), PresetError> {
+        if !self.presets.iter().any(|preset| preset.name == name) {
+            return Err(PresetError::NotFound(name.to_string()));
+        }
+        let mut presets = self.presets.clone();
+        presets.retain(|preset| preset.name != name);
+        write_json(&self.path, &presets)?;
+        self.presets = presets;
+        Ok(())
+    }
+}
+
+fn check(preset: &RegionPreset, monitors: &[MonitorInfo]) -> RegionPresetStatus {
+    let monitor = monitors
+        .iter()
+        .find(|monitor| monitor.stable_id == preset.monitor_id);
+    let invalid_reason = match monitor {
+        Some(monitor) => does_not_fit(&preset.region, monitor),
+        None => Some(format!("{} isn't connected", preset.monitor_name)),
+    };
+
+    RegionPresetStatus {
+        preset: preset.clone(),
+        monitor_handle: monitor.map(|monitor| monitor.hmonitor),
+        invalid_reason,
+    }
+}
+
+/// Why the region doesn't fit on the monitor, e.g. after a resolution change
+fn does_not_fit(region: &Region, monitor: &MonitorInfo) -> Option<String> {
+    let right = region.x as i64 + region.width as i64;
+    let bottom = region.y as i64 + region.height as i64;
+    if region.width == 0 || region.height == 0 {
+        Some("the region is empty".into())
+    } else if region.x < 0
+        || region.y < 0
+        || right > monitor.width as i64
+        || bottom > monitor.height as i64
+    {
+        Some(format!(
+            "the region doesn't fit on {} at {}x{}",
+            monitor.name, monitor.width, monitor.height
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(width: u32, height: u32) -> MonitorInfo {
+        MonitorInfo {
+            id: 0,
+            hmonitor: 0x1234,
+            name: "Dell U2720Q".into(),
+            stable_id: r"\\?\DISPLAY#DEL1234#1".into(),
+            width,
+            height,
+            left: -(width as i32),
+            top: 0,
+        }
+    }
+
+    fn region(x: i32, y: i32, width: u32, height: u32) -> Region {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn preset(region: Region) -> RegionPreset {
+        RegionPreset {
+            name: "Slides".into(),
+            monitor_id: monitor(0, 0).stable_id,
+            monitor_name: "Dell U2720Q".into(),
+            region,
+        }
+    }
+
+    #[test]
+    fn accepts_regions_inside_the_monitor() {
+        let monitor = monitor(2560, 1440);
+        assert_eq!(does_not_fit(&region(0, 0, 2560, 1440), &monitor), None);
+        assert_eq!(does_not_fit(&region(100, 200, 1280, 720), &monitor), None);
+        // Relative to the monitor, so its negative screen position doesn't matter
+        assert_eq!(does_not_fit(&region(1280, 720, 1280, 720), &monitor), None);
+    }
+
+    #[test]
+    fn rejects_empty_regions() {
+        let monitor = monitor(2560, 1440);
+        let reason = does_not_fit(&region(10, 10, 0, 100), &monitor);
+        assert_eq!(reason.as_deref(), Some("the region is empty"));
+        assert!(does_not_fit(&region(10, 10, 100, 0), &monitor).is_some());
+    }
+
+    #[test]
+    fn rejects_regions_past_any_edge() {
+        let monitor = monitor(1920, 1080);
+        for outside in [
+            region(-1, 0, 100, 100),
+            region(0, -1, 100, 100),
+            region(1821, 0, 100, 100),
+            region(0, 981, 100, 100),
+            region(0, 0, 2560, 1440),
+        ] {
+            let reason = does_not_fit(&outside, &monitor);
+            assert_eq!(
+                reason.as_deref(),
+                Some("the region doesn't fit on Dell U2720Q at 1920x1080"),
+                "{:?}",
+                outside
+            );
+        }
+    }
+
+    #[test]
+    fn does_not_overflow_on_huge_regions() {
+        let monitor = monitor(1920, 1080);
+        assert!(does_not_fit(&region(i32::MAX, 0, u32::MAX, 100), &monitor).is_some());
+    }
+
+    #[test]
+    fn check_finds_the_monitor_by_stable_id() {
+        let status = check(&preset(region(0, 0, 1280, 720)), &[monitor(1920, 1080)]);
+        assert_eq!(status.monitor_handle, Some(0x1234));
+        assert_eq!(status.invalid_reason, None);
+    }
+
+    #[test]
+    fn check_reports_a_disconnected_monitor() {
+        let mut other = monitor(1920, 1080);
+        other.stable_id = r"\\?\DISPLAY#SAM5678#1".into();
+        let status = check(&preset(region(0, 0, 1280, 720)), &[other]);
+        assert_eq!(status.monitor_handle, None);
+        assert_eq!(
+            status.invalid_reason.as_deref(),
+            Some("Dell U2720Q isn't connected")
+        );
+    }
+
+    #[test]
+    fn check_reports_a_region_that_no_longer_fits() {
+        // Saved at 2560x1440, the monitor now runs at 1920x1080
+        let status = check(
+            &preset(region(1280, 720, 1280, 720)),
+            &[monitor(1920, 1080)],
+        );
+        assert_eq!(status.monitor_handle, Some(0x1234));
+        assert!(status.invalid_reason.is_some());
+    }
+}
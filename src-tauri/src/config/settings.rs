@@ -0,0 +1,573 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::store::{move_aside, read_json, write_json, StoreError};
+use crate::{
+    audio::is_audio_file,
+    types::{CaptureSourceType, RecordingOptions, Region},
+};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Version written to new settings files. Bump it and add a migration whenever a change
+/// would make older files load differently.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Upgrades a settings file by one version, `MIGRATIONS[n]` taking version `n` to `n + 1`
+type Migration = fn(&mut Map<String, Value>);
+
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] = [migrate_unversioned];
+
+/// Everything the app remembers between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub output: OutputSettings,
+    pub source: SourceSettings,
+    pub audio: AudioSettings,
+    pub webcam: WebcamSettings,
+    /// Used for recordings unless the caller passes options of its own
+    pub recording: RecordingOptions,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputSettings {
+    pub path: String,
+}
+
+/// The last source recorded. Handles change between runs, so sources are found by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceSettings {
+    pub source_type: Option<CaptureSourceType>,
+    pub name: Option<String>,
+    pub capture_mode: CaptureMode,
+    /// Part of the monitor to record in `custom` mode
+    pub region: Option<Region>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    #[default]
+    Full,
+    Custom,
+}
+
+/// Audio inputs to record; a `None` device means the system default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub system_audio: bool,
+    pub system_device: Option<String>,
+    pub microphone: bool,
+    pub microphone_device: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebcamSettings {
+    pub enabled: bool,
+    /// Device ID, or `pattern` for the test pattern
+    pub device: Option<String>,
+}
+
+/// A change `SettingsStore::update` left out because the settings would be invalid with it
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedChange {
+    /// Dotted path of the field, e.g. `output.path`
+    pub field: String,
+    pub reason: String,
+}
+
+/// What `SettingsStore::update` did with a set of changes
+#[derive(Debug, Clone, Default)]
+pub struct SettingsUpdate {
+    /// Whether any change was accepted and the settings saved
+    pub saved: bool,
+    pub rejected: Vec<RejectedChange>,
+}
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("settings version {0} is newer than this version of the app supports")]
+    TooNew(u32),
+
+    #[error("invalid settings: {0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl Serialize for SettingsError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let path = self.output.path.trim();
+        if !path.is_empty() && !path.to_ascii_lowercase().ends_with(".mp4") && !is_audio_file(path)
+        {
+            return Err(SettingsError::Invalid(
+                "the output must be an .mp4, .wav, .flac, .opus or .ogg file".into(),
+            ));
+        }
+        if let Some(region) = &self.source.region {
+            if region.width == 0 || region.height == 0 {
+                return Err(SettingsError::Invalid("the region is empty".into()));
+            }
+        }
+        if self.source.capture_mode == CaptureMode::Custom && self.source.region.is_none() {
+            return Err(SettingsError::Invalid(
+                "custom capture mode needs a region".into(),
+            ));
+        }
+        let devices = [
+            &self.audio.system_device,
+            &self.audio.microphone_device,
+            &self.webcam.device,
+        ];
+        if devices
+            .iter()
+            .any(|device| device.as_deref().is_some_and(|id| id.trim().is_empty()))
+        {
+            return Err(SettingsError::Invalid(
+                "device IDs can't be empty; leave them unset for the default".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses a settings file of any supported version
+    fn from_json(value: Value) -> Result<Self, SettingsError> {
+        let Value::Object(mut object) = value else {
+            return Err(SettingsError::Invalid(
+                "settings must be a JSON object".into(),
+            ));
+        };
+
+        let version = match object.remove("version") {
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| SettingsError::Invalid("the version isn't a number".into()))?
+                as u32,
+            None => 0,
+        };
+        if version > SETTINGS_VERSION {
+            return Err(SettingsError::TooNew(version));
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&mut object);
+        }
+
+        serde_json::from_value(Value::Object(object))
+            .map_err(|e| SettingsError::Invalid(e.to_string()))
+    }
+
+    /// The settings as written to disk, tagged with the current version
+    fn to_json(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Value::Object(object) = &mut value {
+            object.insert("version".into(), SETTINGS_VERSION.into());
+        }
+        value
+    }
+}
+
+/// Files from before versioning may give the output as a bare path; version 1 nests it
+/// so other output settings can sit beside it
+fn migrate_unversioned(settings: &mut Map<String, Value>) {
+    if let Some(Value::String(path)) = settings.get("output") {
+        let output = Value::Object(Map::from_iter([("path".into(), path.clone().into())]));
+        settings.insert("output".into(), output);
+    }
+}
+
+/// The settings, saved to a JSON file whenever they change
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Settings,
+}
+
+impl SettingsStore {
+    /// Loads and migrates the settings. A file that can't be used is moved aside so saving
+    /// doesn't overwrite it, and the defaults are used instead.
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match read_json::<Value>(&path)
+            .map_err(SettingsError::from)
+            .and_then(|value| value.map(Settings::from_json).transpose())
+        {
+            Ok(settings) => settings.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("Failed to load settings: {}", e);
+                move_aside(&path);
+                Settings::default()
+            }
+        };
+        Self { path, settings }
+    }
+
+    pub fn get(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Merges `changes` into the settings, objects key by key, and saves them. A change that
+    /// would make the settings invalid is left out without holding back the others; the ones
+    /// left out are returned. Nothing is written when every change is left out.
+    pub fn update(&mut self, changes: Value) -> Result<SettingsUpdate, SettingsError> {
+        if !changes.is_object() {
+            return Err(SettingsError::Invalid(
+                "changes must be a JSON object".into(),
+            ));
+        }
+        let mut value = serde_json::to_value(&self.settings).unwrap_or_default();
+
+        let mut merged = value.clone();
+        merge(&mut merged, changes.clone());
+        if let Ok(settings) = parse_valid(merged) {
+            write_json(&self.path, &settings.to_json())?;
+            self.settings = settings;
+            return Ok(SettingsUpdate {
+                saved: true,
+                rejected: Vec::new(),
+            });
+        }
+
+        let mut pending = Vec::new();
+        split_changes(&value, changes, &mut Vec::new(), &mut pending);
+        let mut settings = None;
+        // A change can depend on another, e.g. custom capture mode on the region it needs,
+        // so rejected changes are tried again as long as others are still being accepted
+        let rejected = loop {
+            let tried = pending.len();
+            let mut rejected = Vec::new();
+            for (path, change) in pending {
+                let mut candidate = value.clone();
+                merge(&mut candidate, nest(&path, change.clone()));
+                match parse_valid(candidate.clone()) {
+                    Ok(parsed) => {
+                        value = candidate;
+                        settings = Some(parsed);
+                    }
+                    Err(e) => rejected.push((path, change, e)),
+                }
+            }
+            if rejected.is_empty() || rejected.len() == tried {
+                break rejected;
+            }
+            pending = rejected
+                .into_iter()
+                .map(|(path, change, _)| (path, change))
+                .collect();
+        };
+
+        let saved = settings.is_some();
+        if let Some(settings) = settings {
+            write_json(&self.path, &settings.to_json())?;
+            self.settings = settings;
+        }
+        Ok(SettingsUpdate {
+            saved,
+            rejected: rejected
+                .into_iter()
+                .map(|(path, _, e)| RejectedChange {
+                    field: path.join("."),
+                    reason: match e {
+                        SettingsError::Invalid(reason) => reason,
+                        e => e.to_string(),
+                    },
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Parses merged settings and checks them
+fn parse_valid(value: Value) -> Result<Settings, SettingsError> {
+    let settings: Settings =
+        serde_json::from_value(value).map_err(|e| SettingsError::Invalid(e.to_string()))?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+/// Splits a merge patch into the single fields it changes. Objects are only split where
+/// the current settings have an object too, so e.g. a region replacing `null` stays whole.
+fn split_changes(
+    current: &Value,
+    changes: Value,
+    path: &mut Vec<String>,
+    fields: &mut Vec<(Vec<String>, Value)>,
+) {
+    match (current, changes) {
+        (Value::Object(current), Value::Object(changes)) => {
+            for (key, change) in changes {
+                path.push(key.clone());
+                split_changes(
+                    current.get(&key).unwrap_or(&Value::Null),
+                    change,
+                    path,
+                    fields,
+                );
+                path.pop();
+            }
+        }
+        (_, change) => fields.push((path.clone(), change)),
+    }
+}
+
+/// The patch that sets only the field at `path`
+fn nest(path: &[String], change: Value) -> Value {
+    path.iter().rev().fold(change, |change, key| {
+        Value::Object(Map::from_iter([(key.clone(), change)]))
+    })
+}
+
+/// JSON merge patch: objects are merged recursively, anything else replaces the old value
+fn merge(target: &mut Value, changes: Value) {
+    match (target, changes) {
+        (Value::Object(target), Value::Object(changes)) => {
+            for (key, value) in changes {
+                merge(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, changes) => *target = changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// A store in a fresh temporary directory, with the file it saves to
+    fn store(name: &str) -> (SettingsStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("framelet-settings-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join(SETTINGS_FILE);
+        (SettingsStore::load(path.clone()), path)
+    }
+
+    fn region() -> Value {
+        json!({ "x": 0, "y": 0, "width": 1280, "height": 720 })
+    }
+
+    #[test]
+    fn migrates_unversioned_files() {
+        let settings = Settings::from_json(json!({
+            "output": "C:\\Videos\\talk.mp4",
+            "audio": { "microphone": true },
+        }))
+        .unwrap();
+        assert_eq!(settings.output.path, "C:\\Videos\\talk.mp4");
+        assert!(settings.audio.microphone);
+
+        // Files already in the version 1 layout are left as they are
+        let settings = Settings::from_json(json!({
+            "output": { "path": "C:\\Videos\\talk.mp4" },
+        }))
+        .unwrap();
+        assert_eq!(settings.output.path, "C:\\Videos\\talk.mp4");
+    }
+
+    #[test]
+    fn runs_every_migration_after_the_file_version() {
+        assert_eq!(MIGRATIONS.len(), SETTINGS_VERSION as usize);
+        for version in 0..=SETTINGS_VERSION {
+            let settings = Settings::from_json(json!({
+                "version": version,
+                "webcam": { "enabled": true },
+            }));
+            assert!(settings.unwrap().webcam.enabled, "version {}", version);
+        }
+
+        // The bare output path is only migrated in files from before version 1
+        let unversioned = Settings::from_json(json!({ "version": 0, "output": "talk.mp4" }));
+        assert_eq!(unversioned.unwrap().output.path, "talk.mp4");
+        let current = Settings::from_json(json!({ "version": 1, "output": "talk.mp4" }));
+        assert!(matches!(current, Err(SettingsError::Invalid(_))));
+    }
+
+    #[test]
+    fn refuses_files_from_newer_versions() {
+        let result = Settings::from_json(json!({ "version": SETTINGS_VERSION + 1 }));
+        assert!(matches!(result, Err(SettingsError::TooNew(v)) if v == SETTINGS_VERSION + 1));
+    }
+
+    #[test]
+    fn refuses_malformed_files() {
+        for value in [
+            json!([]),
+            json!({ "version": "1" }),
+            json!({ "audio": { "microphone": "yes" } }),
+        ] {
+            assert!(
+                matches!(
+                    Settings::from_json(value.clone()),
+                    Err(SettingsError::Invalid(_))
+                ),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_with_the_current_version() {
+        let mut settings = Settings::default();
+        settings.output.path = "out.mp4".into();
+        settings.source.capture_mode = CaptureMode::Custom;
+        settings.source.region = Some(Region {
+            x: 10,
+            y: 20,
+            width: 640,
+            height: 480,
+        });
+
+        let value = settings.to_json();
+        assert_eq!(value["version"], SETTINGS_VERSION);
+        let read = Settings::from_json(value).unwrap();
+        assert_eq!(read.output.path, "out.mp4");
+        assert_eq!(read.source.capture_mode, CaptureMode::Custom);
+        assert_eq!(read.source.region.unwrap().width, 640);
+    }
+
+    #[test]
+    fn validates_the_output_path() {
+        let mut settings = Settings::default();
+        for path in [
+            "",
+            "  ",
+            "talk.mp4",
+            "talk.MP4",
+            "talk.wav",
+            "talk.flac",
+            "talk.opus",
+        ] {
+            settings.output.path = path.into();
+            assert!(settings.validate().is_ok(), "{:?}", path);
+        }
+        for path in ["talk.mkv", "talk", "talk.mp4.txt"] {
+            settings.output.path = path.into();
+            assert!(settings.validate().is_err(), "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn validates_the_region_and_capture_mode() {
+        let mut settings = Settings::default();
+        settings.source.capture_mode = CaptureMode::Custom;
+        assert!(settings.validate().is_err());
+
+        settings.source.region = Some(Region {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 720,
+        });
+        assert!(settings.validate().is_err());
+
+        settings.source.region = Some(Region {
+            x: 0,
+            y: 0,
+            width: 1280,
+            height: 720,
+        });
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn validates_device_ids() {
+        let mut settings = Settings::default();
+        settings.audio.microphone_device = Some("{0.0.1.00000000}.{abc}".into());
+        assert!(settings.validate().is_ok());
+        settings.webcam.device = Some(" ".into());
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn saves_valid_changes_and_rejects_invalid_ones() {
+        let (mut store, path) = store("partial");
+        let update = store
+            .update(json!({
+                "output": { "path": "talk.txt" },
+                "audio": { "microphone": true, "system_audio": "yes" },
+                "webcam": { "device": "pattern" },
+            }))
+            .unwrap();
+
+        assert!(update.saved);
+        let mut fields: Vec<_> = update
+            .rejected
+            .iter()
+            .map(|change| change.field.as_str())
+            .collect();
+        fields.sort();
+        assert_eq!(fields, ["audio.system_audio", "output.path"]);
+        assert!(store.get().audio.microphone);
+        assert_eq!(store.get().webcam.device.as_deref(), Some("pattern"));
+        assert_eq!(store.get().output.path, "");
+
+        let saved = SettingsStore::load(path);
+        assert!(saved.get().audio.microphone);
+        assert_eq!(saved.get().output.path, "");
+    }
+
+    #[test]
+    fn accepts_changes_that_depend_on_each_other() {
+        let (mut store, _) = store("dependent");
+        // Custom mode is checked first, when there is no region for it yet
+        let update = store
+            .update(json!({
+                "source": { "capture_mode": "custom", "region": region() },
+                "output": { "path": "talk.mkv" },
+            }))
+            .unwrap();
+        assert_eq!(update.rejected.len(), 1);
+        assert_eq!(store.get().source.capture_mode, CaptureMode::Custom);
+        assert!(store.get().source.region.is_some());
+
+        // Clearing the region along with the mode that needs it is accepted as a whole
+        let update = store
+            .update(json!({ "source": { "region": null, "capture_mode": "full" } }))
+            .unwrap();
+        assert!(update.saved);
+        assert!(update.rejected.is_empty());
+        assert!(store.get().source.region.is_none());
+    }
+
+    #[test]
+    fn does_not_write_when_nothing_is_accepted() {
+        let (mut store, path) = store("rejected");
+        let update = store
+            .update(json!({ "source": { "capture_mode": "custom" } }))
+            .unwrap();
+        assert!(!update.saved);
+        assert_eq!(update.rejected[0].field, "source.capture_mode");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn moves_unreadable_files_aside() {
+        let (_, path) = store("unreadable");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{ not json").unwrap();
+
+        let store = SettingsStore::load(path.clone());
+        assert_eq!(store.get().output.path, "");
+        assert!(!path.exists());
+        assert!(path.with_extension("json.bak").exists());
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::Manager;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("no config directory: {0}")]
+    NoConfigDir(#[source] tauri::Error),
+
+    #[error("failed to access {}: {source}", .path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("{} is not valid: {source}", .path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+impl Serialize for StoreError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Path of a file in the app's config directory
+pub fn config_file(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, StoreError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(StoreError::NoConfigDir)?;
+    Ok(dir.join(name))
+}
+
+/// Reads a JSON file, or `None` if it doesn't exist yet
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, StoreError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(StoreError::Io {
+                path: path.to_owned(),
+                source,
+            })
+        }
+    };
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|source| StoreError::Parse {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+/// Moves a file that couldn't be loaded to `<name>.json.bak`, so saving doesn't overwrite it
+pub fn move_aside(path: &Path) {
+    let backup = path.with_extension("json.bak");
+    if let Err(e) = fs::rename(path, &backup) {
+        eprintln!("Failed to move {} aside: {}", path.display(), e);
+    }
+}
+
+/// Writes a JSON file through a temporary file, so a crash never leaves half of one behind
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), StoreError> {
+    let io_error = |source| StoreError::Io {
+        path: path.to_owned(),
+        source,
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    let json = serde_json::to_vec_pretty(value).map_err(|source| StoreError::Parse {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    let temp = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&temp).map_err(io_error)?;
+    file.write_all(&json).map_err(io_error)?;
+    file.sync_all().map_err(io_error)?;
+    drop(file);
+    fs::rename(&temp, path).map_err(io_error)
+}
@@ -1,7 +1,12 @@
+pub mod audio;
 pub mod capture;
+pub mod com;
 pub mod commands;
+pub mod config;
+pub mod pipeline;
 pub mod recording;
 pub mod types;
+pub mod webcam;
 pub mod window;
 
 pub use types::*;
@@ -11,9 +16,18 @@ use tauri::Manager;
 use tauri_plugin_decorum::WebviewWindowExt;
 
 use crate::{
+    audio::AudioCapture,
     commands::{
-        close_region_selector, get_capture_sources, open_region_selector, region_selected,
-        start_recording, stop_recording,
+        apply_region_preset, close_region_selector, delete_profile, delete_region_preset,
+        export_profiles, find_window, get_audio_devices, get_capture_sources, get_profile,
+        get_settings, get_snap_windows, get_source_thumbnail, get_webcam_devices, import_profiles,
+        list_profiles, list_region_presets, open_region_selector, pause_recording, region_selected,
+        resume_recording, save_profile, save_region_preset, start_audio_monitor, start_preview,
+        start_recording, stop_audio_monitor, stop_preview, stop_recording, update_settings,
+    },
+    config::{
+        config_file, RecordingProfiles, RegionPresets, SettingsStore, PROFILES_FILE,
+        REGION_PRESETS_FILE, SETTINGS_FILE,
     },
     recording::RecordingSession,
 };
@@ -25,13 +39,37 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_decorum::init())
         .manage(Mutex::new(None::<RecordingSession>))
+        .manage(Mutex::new(None::<AudioCapture>))
         .invoke_handler(tauri::generate_handler![
+            find_window,
+            get_audio_devices,
             get_capture_sources,
+            get_source_thumbnail,
+            get_webcam_devices,
+            start_preview,
+            stop_preview,
+            start_audio_monitor,
+            stop_audio_monitor,
             start_recording,
             stop_recording,
+            pause_recording,
+            resume_recording,
             open_region_selector,
             close_region_selector,
-            region_selected
+            get_snap_windows,
+            region_selected,
+            list_region_presets,
+            save_region_preset,
+            apply_region_preset,
+            delete_region_preset,
+            get_settings,
+            update_settings,
+            list_profiles,
+            get_profile,
+            save_profile,
+            delete_profile,
+            export_profiles,
+            import_profiles
         ])
         .setup(|app| {
             // Create a custom titlebar for main window using https://github.com/clearlysid/tauri-plugin-decorum/
@@ -43,6 +81,16 @@ pub fn run() {
             #[cfg(target_os = "macos")]
             main_window.set_traffic_lights_inset(16.0, 20.0).unwrap();
 
+            let presets_path = config_file(app.handle(), REGION_PRESETS_FILE)?;
+            app.manage(Mutex::new(RegionPresets::load(presets_path)));
+            let settings_path = config_file(app.handle(), SETTINGS_FILE)?;
+            app.manage(Mutex::new(SettingsStore::load(settings_path)));
+            let profiles_path = config_file(app.handle(), PROFILES_FILE)?;
+            app.manage(Mutex::new(RecordingProfiles::load(profiles_path)));
+
+            audio::watch_devices(app.handle().clone());
+            capture::watch_capture_sources(app.handle().clone());
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,334 @@
+use std::{
+    collections::VecDeque,
+    ffi::c_void,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use windows::Win32::{
+    Graphics::Gdi::{DeleteObject, GetObjectW, BITMAP, HGDIOBJ},
+    UI::{
+        Input::KeyboardAndMouse::{GetAsyncKeyState, VK_LBUTTON, VK_RBUTTON},
+        WindowsAndMessaging::{
+            DrawIconEx, GetCursorInfo, GetIconInfo, CURSORINFO, CURSOR_SHOWING, DI_NORMAL, HCURSOR,
+            HICON, ICONINFO,
+        },
+    },
+};
+use windows_capture::settings::CursorCaptureSettings;
+
+use super::{gdi, FrameContext, FrameProcessor, RgbaFrame};
+
+/// How often the cursor position and mouse buttons are sampled
+const POLL_INTERVAL: Duration = Duration::from_millis(8);
+/// How long a click ripple stays on screen
+const RIPPLE_DURATION: Duration = Duration::from_millis(450);
+const RIPPLE_COLOR: [u8; 4] = [255, 255, 255, 220];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CursorHighlight {
+    pub color: [u8; 4],
+    pub radius: u32,
+}
+
+impl Default for CursorHighlight {
+    fn default() -> Self {
+        Self {
+            color: [255, 220, 0, 90],
+            radius: 28,
+        }
+    }
+}
+
+/// How the cursor appears in the recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CursorSettings {
+    pub hidden: bool,
+    /// Cursor size multiplier, clamped to 1.0–3.0
+    pub scale: f32,
+    pub highlight: Option<CursorHighlight>,
+    pub click_ripples: bool,
+}
+
+impl Default for CursorSettings {
+    fn default() -> Self {
+        Self {
+            hidden: false,
+            scale: 1.0,
+            highlight: None,
+            click_ripples: false,
+        }
+    }
+}
+
+impl CursorSettings {
+    /// True when framelet draws the cursor itself instead of the capture API
+    pub fn needs_compositing(&self) -> bool {
+        !self.hidden && (self.scale > 1.0 || self.highlight.is_some() || self.click_ripples)
+    }
+
+    /// Cursor setting for the capture API. When framelet composites the cursor the capture API
+    /// must leave it out, otherwise it would be drawn twice.
+    pub fn capture_settings(&self) -> CursorCaptureSettings {
+        if self.hidden || self.needs_compositing() {
+            CursorCaptureSettings::WithoutCursor
+        } else {
+            CursorCaptureSettings::Default
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Click {
+    pub x: i32,
+    pub y: i32,
+    pub at: Instant,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct CursorState {
+    pub x: i32,
+    pub y: i32,
+    pub visible: bool,
+    pub handle: isize,
+    /// Clicks young enough to still have a ripple on screen
+    pub clicks: VecDeque<Click>,
+    pub last_click: Option<Click>,
+}
+
+/// Samples the cursor on a background thread so short clicks aren't missed between frames.
+/// Shared by every processor that follows the cursor.
+pub struct CursorTracker {
+    state: Arc<Mutex<CursorState>>,
+    stop_signal: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl CursorTracker {
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(CursorState::default()));
+        let stop_signal = Arc::new(AtomicBool::new(false));
+
+        let thread = thread::spawn({
+            let state = Arc::clone(&state);
+            let stop_signal = Arc::clone(&stop_signal);
+            move || {
+                let mut buttons_down = false;
+                while !stop_signal.load(Ordering::Relaxed) {
+                    let mut info = CURSORINFO {
+                        cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+                        ..Default::default()
+                    };
+                    let has_info = unsafe { GetCursorInfo(&mut info) }.is_ok();
+
+                    let pressed = unsafe {
+                        GetAsyncKeyState(VK_LBUTTON.0 as i32) < 0
+                            || GetAsyncKeyState(VK_RBUTTON.0 as i32) < 0
+                    };
+
+                    if has_info {
+                        let mut state = state.lock().unwrap();
+                        state.x = info.ptScreenPos.x;
+                        state.y = info.ptScreenPos.y;
+                        state.visible = info.flags.0 & CURSOR_SHOWING.0 != 0;
+                        state.handle = info.hCursor.0 as isize;
+
+                        if pressed && !buttons_down {
+                            let click = Click {
+                                x: info.ptScreenPos.x,
+                                y: info.ptScreenPos.y,
+                                at: Instant::now(),
+                            };
+                            state.clicks.push_back(click);
+                            state.last_click = Some(click);
+                        }
+                        while state
+                            .clicks
+                            .front()
+                            .is_some_and(|click| click.at.elapsed() > RIPPLE_DURATION)
+                        {
+                            state.clicks.pop_front();
+                        }
+                    }
+
+                    buttons_down = pressed;
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        });
+
+        Self {
+            state,
+            stop_signal,
+            thread: Some(thread),
+        }
+    }
+
+    pub(super) fn snapshot(&self) -> CursorState {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+impl Drop for CursorTracker {
+    fn drop(&mut self) {
+        self.stop_signal.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A rasterized cursor shape at the configured scale
+struct CursorImage {
+    handle: isize,
+    image: RgbaFrame,
+    hotspot_x: i32,
+    hotspot_y: i32,
+}
+
+/// Draws the cursor, its highlight and click ripples into each frame
+pub struct CursorOverlay {
+    settings: CursorSettings,
+    tracker: Arc<CursorTracker>,
+    cached: Option<CursorImage>,
+}
+
+impl CursorOverlay {
+    pub fn new(settings: CursorSettings, tracker: Arc<CursorTracker>) -> Self {
+        Self {
+            settings,
+            tracker,
+            cached: None,
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        self.settings.scale.clamp(1.0, 3.0)
+    }
+
+    fn cursor_image(&mut self, handle: isize) -> Option<&CursorImage> {
+        if self.cached.as_ref().is_none_or(|c| c.handle != handle) {
+            self.cached = rasterize_cursor(handle, self.scale());
+        }
+        self.cached.as_ref()
+    }
+}
+
+impl FrameProcessor for CursorOverlay {
+    fn process(&mut self, frame: &mut RgbaFrame, ctx: &FrameContext) {
+        let state = self.tracker.snapshot();
+        let scale = self.scale();
+
+        if self.settings.click_ripples {
+            for click in &state.clicks {
+                let progress = click.at.elapsed().as_secs_f32() / RIPPLE_DURATION.as_secs_f32();
+                if progress >= 1.0 {
+                    continue;
+                }
+
+                let radius = (8.0 + 32.0 * progress) * scale;
+                let mut color = RIPPLE_COLOR;
+                color[3] = (color[3] as f32 * (1.0 - progress)) as u8;
+                frame.fill_ring(
+                    (click.x - ctx.origin_x) as f32,
+                    (click.y - ctx.origin_y) as f32,
+                    radius - 2.0 * scale,
+                    radius,
+                    color,
+                );
+            }
+        }
+
+        if !state.visible {
+            return;
+        }
+
+        let x = state.x - ctx.origin_x;
+        let y = state.y - ctx.origin_y;
+
+        if let Some(highlight) = self.settings.highlight {
+            frame.fill_ring(
+                x as f32,
+                y as f32,
+                0.0,
+                highlight.radius as f32 * scale,
+                highlight.color,
+            );
+        }
+
+        if let Some(cursor) = self.cursor_image(state.handle) {
+            frame.draw_image(
+                &cursor.image,
+                x - cursor.hotspot_x,
+                y - cursor.hotspot_y,
+                1.0,
+            );
+        }
+    }
+}
+
+/// Renders an HCURSOR to RGBA at the given scale
+fn rasterize_cursor(handle: isize, scale: f32) -> Option<CursorImage> {
+    let hcursor = HCURSOR(handle as *mut c_void);
+    let mut icon_info = ICONINFO::default();
+    unsafe { GetIconInfo(HICON(hcursor.0), &mut icon_info) }.ok()?;
+
+    // Monochrome cursors store the AND and XOR masks stacked in a single double-height bitmap
+    let is_monochrome = icon_info.hbmColor.is_invalid();
+    let measured = if is_monochrome {
+        bitmap_size(icon_info.hbmMask.into()).map(|(w, h)| (w, h / 2))
+    } else {
+        bitmap_size(icon_info.hbmColor.into())
+    };
+
+    unsafe {
+        let _ = DeleteObject(icon_info.hbmMask.into());
+        if !is_monochrome {
+            let _ = DeleteObject(icon_info.hbmColor.into());
+        }
+    }
+
+    let (width, height) = measured?;
+    let scaled_width = (width as f32 * scale).round() as u32;
+    let scaled_height = (height as f32 * scale).round() as u32;
+
+    let image = gdi::rasterize(scaled_width, scaled_height, |dc| unsafe {
+        let _ = DrawIconEx(
+            dc,
+            0,
+            0,
+            HICON(hcursor.0),
+            scaled_width as i32,
+            scaled_height as i32,
+            0,
+            None,
+            DI_NORMAL,
+        );
+    })?;
+
+    Some(CursorImage {
+        handle,
+        image,
+        hotspot_x: (icon_info.xHotspot as f32 * scale).round() as i32,
+        hotspot_y: (icon_info.yHotspot as f32 * scale).round() as i32,
+    })
+}
+
+fn bitmap_size(bitmap: HGDIOBJ) -> Option<(u32, u32)> {
+    let mut info = BITMAP::default();
+    let copied = unsafe {
+        GetObjectW(
+            bitmap,
+            std::mem::size_of::<BITMAP>() as i32,
+            Some(&mut info as *mut BITMAP as *mut c_void),
+        )
+    };
+
+    (copied != 0).then_some((info.bmWidth as u32, info.bmHeight as u32))
+}
@@ -0,0 +1,355 @@
+/// A top-down RGBA8 frame owned by the processing pipeline
+#[derive(Debug, Clone)]
+pub struct RgbaFrame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// A rectangle that has already been clipped to the bounds of a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RgbaFrame {
+    /// Wraps an existing tightly packed RGBA buffer
+    pub fn new(width: u32, height: u32, data: Vec<u8>) -> Self {
+        debug_assert_eq!(data.len(), width as usize * height as usize * 4);
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Creates an opaque black frame
+    pub fn blank(width: u32, height: u32) -> Self {
+        let mut data = vec![0u8; width as usize * height as usize * 4];
+        data.chunks_exact_mut(4).for_each(|px| px[3] = 255);
+        Self::new(width, height, data)
+    }
+
+    /// Clips a rectangle given in frame coordinates to the frame bounds.
+    /// Returns `None` if nothing of the rectangle is visible.
+    pub fn clip(&self, x: i32, y: i32, width: u32, height: u32) -> Option<PixelRect> {
+        let left = x.max(0) as i64;
+        let top = y.max(0) as i64;
+        let right = (x as i64 + width as i64).min(self.width as i64);
+        let bottom = (y as i64 + height as i64).min(self.height as i64);
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        Some(PixelRect {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        })
+    }
+
+    /// Byte offset of the pixel at (x, y)
+    #[inline]
+    pub fn offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    /// Fills a rectangle with a solid color
+    pub fn fill_rect(&mut self, rect: PixelRect, color: [u8; 4]) {
+        for row in rect.y..rect.y + rect.height {
+            let start = self.offset(rect.x, row);
+            let end = start + rect.width as usize * 4;
+            self.data[start..end]
+                .chunks_exact_mut(4)
+                .for_each(|px| px.copy_from_slice(&color));
+        }
+    }
+
+    /// Alpha-blends a straight-alpha color onto the pixel at (x, y), scaled by `coverage` (0..=1)
+    #[inline]
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: [u8; 4], coverage: f32) {
+        let alpha = (color[3] as f32 / 255.0) * coverage.clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+
+        let offset = self.offset(x, y);
+        let px = &mut self.data[offset..offset + 4];
+        for (dst, src) in px[..3].iter_mut().zip(&color[..3]) {
+            *dst = (*src as f32 * alpha + *dst as f32 * (1.0 - alpha)).round() as u8;
+        }
+        px[3] = 255;
+    }
+
+    /// Alpha-blends another frame on top of this one with its top-left corner at (x, y),
+    /// multiplying the image alpha by `opacity`
+    pub fn draw_image(&mut self, image: &RgbaFrame, x: i32, y: i32, opacity: f32) {
+        let Some(rect) = self.clip(x, y, image.width, image.height) else {
+            return;
+        };
+
+        for dst_y in rect.y..rect.y + rect.height {
+            let src_y = (dst_y as i32 - y) as u32;
+            for dst_x in rect.x..rect.x + rect.width {
+                let src_x = (dst_x as i32 - x) as u32;
+                let offset = image.offset(src_x, src_y);
+                let mut color = [0u8; 4];
+                color.copy_from_slice(&image.data[offset..offset + 4]);
+                self.blend_pixel(dst_x, dst_y, color, opacity);
+            }
+        }
+    }
+
+    /// Draws an anti-aliased annulus between `inner` and `outer` radii around (cx, cy).
+    /// An `inner` radius of zero gives a filled circle.
+    pub fn fill_ring(&mut self, cx: f32, cy: f32, inner: f32, outer: f32, color: [u8; 4]) {
+        let reach = outer.ceil() as i32 + 1;
+        let Some(rect) = self.clip(
+            cx as i32 - reach,
+            cy as i32 - reach,
+            reach as u32 * 2,
+            reach as u32 * 2,
+        ) else {
+            return;
+        };
+
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                let distance = (dx * dx + dy * dy).sqrt();
+
+                // One pixel of linear falloff on each edge for anti-aliasing
+                let outer_coverage = (outer - distance + 0.5).clamp(0.0, 1.0);
+                let inner_coverage = if inner > 0.0 {
+                    (distance - inner + 0.5).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+
+                self.blend_pixel(x, y, color, outer_coverage * inner_coverage);
+            }
+        }
+    }
+
+    /// Fills an anti-aliased rectangle with rounded corners
+    pub fn fill_rounded_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        radius: f32,
+        color: [u8; 4],
+    ) {
+        let Some(rect) = self.clip(x, y, width, height) else {
+            return;
+        };
+
+        let radius = radius.min(width.min(height) as f32 / 2.0).max(0.0);
+        let (left, top) = (x as f32, y as f32);
+        let (right, bottom) = (left + width as f32, top + height as f32);
+
+        for py in rect.y..rect.y + rect.height {
+            for px in rect.x..rect.x + rect.width {
+                let fx = px as f32 + 0.5;
+                let fy = py as f32 + 0.5;
+
+                let coverage = rounded_rect_coverage(fx, fy, (left, top, right, bottom), radius);
+                self.blend_pixel(px, py, color, coverage);
+            }
+        }
+    }
+
+    /// Makes everything outside a rounded rectangle spanning the whole frame transparent,
+    /// anti-aliasing the corners. A radius of half the shorter side gives a circle or pill.
+    pub fn mask_rounded(&mut self, radius: f32) {
+        let radius = radius
+            .min(self.width.min(self.height) as f32 / 2.0)
+            .max(0.0);
+        let bounds = (0.0, 0.0, self.width as f32, self.height as f32);
+        let width = self.width;
+
+        for (i, px) in self.data.chunks_exact_mut(4).enumerate() {
+            let fx = (i as u32 % width) as f32 + 0.5;
+            let fy = (i as u32 / width) as f32 + 0.5;
+            let coverage = rounded_rect_coverage(fx, fy, bounds, radius);
+            px[3] = (px[3] as f32 * coverage).round() as u8;
+        }
+    }
+
+    /// Mirrors the frame left to right
+    pub fn flip_horizontal(&mut self) {
+        for row in self.data.chunks_exact_mut(self.width as usize * 4) {
+            let pixels = row.len() / 4;
+            for x in 0..pixels / 2 {
+                let mirrored = pixels - 1 - x;
+                for channel in 0..4 {
+                    row.swap(x * 4 + channel, mirrored * 4 + channel);
+                }
+            }
+        }
+    }
+
+    /// Bilinearly resamples the source rectangle (in fractional pixels) to a new frame
+    pub fn resample(
+        &self,
+        src_x: f32,
+        src_y: f32,
+        src_width: f32,
+        src_height: f32,
+        out_width: u32,
+        out_height: u32,
+    ) -> RgbaFrame {
+        let mut data = vec![0u8; out_width as usize * out_height as usize * 4];
+        let scale_x = src_width / out_width as f32;
+        let scale_y = src_height / out_height as f32;
+        let max_x = self.width.saturating_sub(1) as f32;
+        let max_y = self.height.saturating_sub(1) as f32;
+
+        for out_y in 0..out_height {
+            let fy = (src_y + (out_y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, max_y);
+            let y0 = fy.floor() as u32;
+            let y1 = (y0 + 1).min(self.height - 1);
+            let wy = fy - y0 as f32;
+
+            for out_x in 0..out_width {
+                let fx = (src_x + (out_x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, max_x);
+                let x0 = fx.floor() as u32;
+                let x1 = (x0 + 1).min(self.width - 1);
+                let wx = fx - x0 as f32;
+
+                let (p00, p10) = (self.offset(x0, y0), self.offset(x1, y0));
+                let (p01, p11) = (self.offset(x0, y1), self.offset(x1, y1));
+                let dst = (out_y as usize * out_width as usize + out_x as usize) * 4;
+
+                for channel in 0..4 {
+                    let top = self.data[p00 + channel] as f32 * (1.0 - wx)
+                        + self.data[p10 + channel] as f32 * wx;
+                    let bottom = self.data[p01 + channel] as f32 * (1.0 - wx)
+                        + self.data[p11 + channel] as f32 * wx;
+                    data[dst + channel] = (top * (1.0 - wy) + bottom * wy).round() as u8;
+                }
+            }
+        }
+
+        RgbaFrame::new(out_width, out_height, data)
+    }
+
+    /// Scales the frame down to fit within the given size, keeping its aspect ratio
+    pub fn downscale_to_fit(&self, max_width: u32, max_height: u32) -> RgbaFrame {
+        let scale = (max_width as f32 / self.width as f32)
+            .min(max_height as f32 / self.height as f32)
+            .min(1.0);
+        let width = ((self.width as f32 * scale).round() as u32).max(1);
+        let height = ((self.height as f32 * scale).round() as u32).max(1);
+
+        // Halving averages each 2x2 block, which avoids the aliasing a single large bilinear
+        // step would leave
+        let mut halved: Option<RgbaFrame> = None;
+        loop {
+            let current = halved.as_ref().unwrap_or(self);
+            if current.width / 2 < width || current.height / 2 < height {
+                break;
+            }
+            let half = current.resample(
+                0.0,
+                0.0,
+                current.width as f32,
+                current.height as f32,
+                current.width / 2,
+                current.height / 2,
+            );
+            halved = Some(half);
+        }
+
+        let current = halved.as_ref().unwrap_or(self);
+        current.resample(
+            0.0,
+            0.0,
+            current.width as f32,
+            current.height as f32,
+            width,
+            height,
+        )
+    }
+
+    /// Scales the frame to fit within `width` x `height` keeping its aspect ratio, centered
+    /// on black bars
+    pub fn letterbox(&self, width: u32, height: u32) -> RgbaFrame {
+        let scale = (width as f32 / self.width as f32).min(height as f32 / self.height as f32);
+        let fit_width = ((self.width as f32 * scale).round() as u32).clamp(1, width);
+        let fit_height = ((self.height as f32 * scale).round() as u32).clamp(1, height);
+        let scaled = if scale < 1.0 {
+            self.downscale_to_fit(fit_width, fit_height)
+        } else {
+            self.resample(
+                0.0,
+                0.0,
+                self.width as f32,
+                self.height as f32,
+                fit_width,
+                fit_height,
+            )
+        };
+
+        let mut output = RgbaFrame::blank(width, height);
+        output.blit(
+            &scaled,
+            ((width - scaled.width) / 2) as i32,
+            ((height - scaled.height) / 2) as i32,
+        );
+        output
+    }
+
+    /// Copies another frame over this one with its top-left corner at (x, y), ignoring alpha
+    pub fn blit(&mut self, image: &RgbaFrame, x: i32, y: i32) {
+        let Some(rect) = self.clip(x, y, image.width, image.height) else {
+            return;
+        };
+
+        let row_bytes = rect.width as usize * 4;
+        for dst_y in rect.y..rect.y + rect.height {
+            let src = image.offset((rect.x as i32 - x) as u32, (dst_y as i32 - y) as u32);
+            let dst = self.offset(rect.x, dst_y);
+            self.data[dst..dst + row_bytes].copy_from_slice(&image.data[src..src + row_bytes]);
+        }
+    }
+
+    /// Copies out a rectangle of the frame. Parts outside the frame come out black.
+    pub fn crop(&self, x: i32, y: i32, width: u32, height: u32) -> RgbaFrame {
+        let mut output = RgbaFrame::blank(width, height);
+        output.blit(self, -x, -y);
+        output
+    }
+
+    /// Returns the rows in bottom-up order, which is what the encoder expects for raw buffers
+    pub fn to_bottom_up(&self) -> Vec<u8> {
+        let bytes_per_row = self.width as usize * 4;
+        let mut flipped = Vec::with_capacity(self.data.len());
+        for row in self.data.chunks_exact(bytes_per_row).rev() {
+            flipped.extend_from_slice(row);
+        }
+        flipped
+    }
+}
+
+/// Coverage (0..=1) of the pixel centered at (fx, fy) by a rounded rectangle given as
+/// (left, top, right, bottom), with one pixel of falloff on the curved edges
+fn rounded_rect_coverage(fx: f32, fy: f32, bounds: (f32, f32, f32, f32), radius: f32) -> f32 {
+    if radius <= 0.0 {
+        return 1.0;
+    }
+
+    let (left, top, right, bottom) = bounds;
+    // Distance outside the rounded corner, measured from the nearest corner center
+    let cx = fx.clamp(left + radius, right - radius);
+    let cy = fy.clamp(top + radius, bottom - radius);
+    let distance = ((fx - cx).powi(2) + (fy - cy).powi(2)).sqrt();
+    (radius - distance + 0.5).clamp(0.0, 1.0)
+}
@@ -0,0 +1,159 @@
+use std::{ffi::c_void, ptr};
+
+use windows::{
+    core::w,
+    Win32::{
+        Foundation::{COLORREF, RECT, SIZE},
+        Graphics::Gdi::{
+            CreateCompatibleDC, CreateDIBSection, CreateFontW, DeleteDC, DeleteObject, DrawTextW,
+            GdiFlush, GetDC, GetTextExtentPoint32W, ReleaseDC, SelectObject, SetBkMode,
+            SetTextColor, ANTIALIASED_QUALITY, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+            CLIP_DEFAULT_PRECIS, DEFAULT_CHARSET, DEFAULT_PITCH, DIB_RGB_COLORS, DT_NOPREFIX,
+            DT_SINGLELINE, FF_SWISS, FW_SEMIBOLD, HDC, OUT_DEFAULT_PRECIS, TRANSPARENT,
+        },
+    },
+};
+
+use super::RgbaFrame;
+
+/// Rasterizes GDI drawing into an RGBA image with a proper alpha channel.
+///
+/// GDI has no real alpha support, so the drawing is done twice, once over black and once over
+/// white, and the coverage of each pixel is recovered from the difference between the two.
+pub fn rasterize(width: u32, height: u32, draw: impl Fn(HDC)) -> Option<RgbaFrame> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32,
+            // Negative height gives a top-down DIB
+            biHeight: -(height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let len = width as usize * height as usize * 4;
+
+    unsafe {
+        let screen_dc = GetDC(None);
+        let dc = CreateCompatibleDC(Some(screen_dc));
+        ReleaseDC(None, screen_dc);
+
+        let mut bits: *mut c_void = ptr::null_mut();
+        let Ok(bitmap) = CreateDIBSection(Some(dc), &info, DIB_RGB_COLORS, &mut bits, None, 0)
+        else {
+            let _ = DeleteDC(dc);
+            return None;
+        };
+        let previous = SelectObject(dc, bitmap.into());
+        let pixels = std::slice::from_raw_parts_mut(bits as *mut u8, len);
+
+        pixels.fill(0);
+        draw(dc);
+        let _ = GdiFlush();
+        let on_black = pixels.to_vec();
+
+        pixels.fill(255);
+        draw(dc);
+        let _ = GdiFlush();
+        let on_white = pixels.to_vec();
+
+        SelectObject(dc, previous);
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(dc);
+
+        Some(combine_passes(width, height, &on_black, &on_white))
+    }
+}
+
+/// Recovers straight-alpha RGBA from BGRA renders over black and white
+fn combine_passes(width: u32, height: u32, on_black: &[u8], on_white: &[u8]) -> RgbaFrame {
+    let mut data = vec![0u8; on_black.len()];
+
+    for ((out, black), white) in data
+        .chunks_exact_mut(4)
+        .zip(on_black.chunks_exact(4))
+        .zip(on_white.chunks_exact(4))
+    {
+        // Coverage is how much of the white background was hidden, taken from the green channel
+        let alpha = 255 - (white[1] as i32 - black[1] as i32).clamp(0, 255);
+        if alpha == 0 {
+            continue;
+        }
+
+        let unpremultiply = |value: u8| ((value as i32 * 255) / alpha).min(255) as u8;
+        out[0] = unpremultiply(black[2]);
+        out[1] = unpremultiply(black[1]);
+        out[2] = unpremultiply(black[0]);
+        out[3] = alpha as u8;
+    }
+
+    RgbaFrame::new(width, height, data)
+}
+
+/// Renders a single line of white text in the system UI font
+pub fn render_text(text: &str, font_height: u32) -> Option<RgbaFrame> {
+    let wide: Vec<u16> = text.encode_utf16().collect();
+
+    unsafe {
+        let font = CreateFontW(
+            -(font_height as i32),
+            0,
+            0,
+            0,
+            FW_SEMIBOLD.0 as i32,
+            0,
+            0,
+            0,
+            DEFAULT_CHARSET,
+            OUT_DEFAULT_PRECIS,
+            CLIP_DEFAULT_PRECIS,
+            // Greyscale anti-aliasing; ClearType fringes would break the alpha recovery
+            ANTIALIASED_QUALITY,
+            DEFAULT_PITCH.0 as u32 | FF_SWISS.0 as u32,
+            w!("Segoe UI"),
+        );
+
+        // Measure with a throwaway DC before allocating the bitmap
+        let measure_dc = CreateCompatibleDC(None);
+        let previous = SelectObject(measure_dc, font.into());
+        let mut size = SIZE::default();
+        let measured = GetTextExtentPoint32W(measure_dc, &wide, &mut size).as_bool();
+        SelectObject(measure_dc, previous);
+        let _ = DeleteDC(measure_dc);
+
+        let image = if measured {
+            let (width, height) = (size.cx.max(1) as u32, size.cy.max(1) as u32);
+            rasterize(width, height, |dc| {
+                let previous = SelectObject(dc, font.into());
+                SetBkMode(dc, TRANSPARENT);
+                SetTextColor(dc, COLORREF(0x00FF_FFFF));
+                let mut rect = RECT {
+                    left: 0,
+                    top: 0,
+                    right: width as i32,
+                    bottom: height as i32,
+                };
+                DrawTextW(
+                    dc,
+                    &mut wide.clone(),
+                    &mut rect,
+                    DT_SINGLELINE | DT_NOPREFIX,
+                );
+                SelectObject(dc, previous);
+            })
+        } else {
+            None
+        };
+
+        let _ = DeleteObject(font.into());
+        image
+    }
+}
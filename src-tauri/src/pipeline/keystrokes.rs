@@ -0,0 +1,352 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use windows::Win32::{
+    Foundation::{LPARAM, LRESULT, WPARAM},
+    System::Threading::GetCurrentThreadId,
+    UI::{
+        Input::KeyboardAndMouse::{
+            GetAsyncKeyState, GetKeyNameTextW, VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_DELETE,
+            VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_F24, VK_HOME, VK_INSERT, VK_LCONTROL, VK_LEFT,
+            VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MENU, VK_NEXT, VK_PRIOR, VK_RCONTROL, VK_RETURN,
+            VK_RIGHT, VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SHIFT, VK_SPACE, VK_TAB, VK_UP,
+        },
+        WindowsAndMessaging::{
+            CallNextHookEx, GetMessageW, PostThreadMessageW, SetWindowsHookExW,
+            UnhookWindowsHookEx, HC_ACTION, KBDLLHOOKSTRUCT, LLKHF_EXTENDED, MSG, WH_KEYBOARD_LL,
+            WM_KEYDOWN, WM_QUIT, WM_SYSKEYDOWN,
+        },
+    },
+};
+
+use super::{gdi, Corner, FrameContext, FrameProcessor, RgbaFrame};
+
+const FADE_DURATION: Duration = Duration::from_millis(300);
+const BADGE_COLOR: [u8; 4] = [24, 24, 27, 210];
+const MARGIN: u32 = 32;
+
+lazy_static! {
+    /// The low-level hook callback has no user data pointer, so events go through a global
+    static ref KEY_SENDER: Mutex<Option<Sender<KeyCombo>>> = Mutex::new(None);
+}
+
+/// Which key presses are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeystrokeFilter {
+    /// Every key press, including plain typing
+    All,
+    /// Only presses with Ctrl, Alt or Win held
+    #[default]
+    ModifierCombos,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeystrokeOverlaySettings {
+    pub corner: Corner,
+    pub filter: KeystrokeFilter,
+    /// How long a badge stays fully visible before fading out
+    pub display_ms: u64,
+    pub font_size: u32,
+    /// Maximum number of badges stacked at once
+    pub max_visible: usize,
+}
+
+impl Default for KeystrokeOverlaySettings {
+    fn default() -> Self {
+        Self {
+            corner: Corner::BottomRight,
+            filter: KeystrokeFilter::ModifierCombos,
+            display_ms: 1500,
+            font_size: 28,
+            max_visible: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyCombo {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    win: bool,
+    key: String,
+}
+
+impl KeyCombo {
+    fn has_command_modifier(&self) -> bool {
+        self.ctrl || self.alt || self.win
+    }
+
+    /// Human readable form, e.g. "Ctrl+Shift+P"
+    fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.ctrl {
+            parts.push("Ctrl");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        if self.win {
+            parts.push("Win");
+        }
+        parts.push(&self.key);
+        parts.join("+")
+    }
+}
+
+/// Installs a low-level keyboard hook on its own message-loop thread for as long as it lives
+struct KeyHook {
+    thread_id: u32,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl KeyHook {
+    fn start(sender: Sender<KeyCombo>) -> Self {
+        *KEY_SENDER.lock().unwrap() = Some(sender);
+
+        let (id_sender, id_receiver) = mpsc::channel();
+        let thread = thread::spawn(move || unsafe {
+            let hook = SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook), None, 0);
+            if let Err(e) = &hook {
+                eprintln!("Failed to install keyboard hook: {}", e);
+            }
+            let _ = id_sender.send(GetCurrentThreadId());
+
+            // Low-level hooks are only called while this thread pumps messages
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {}
+
+            if let Ok(hook) = hook {
+                let _ = UnhookWindowsHookEx(hook);
+            }
+        });
+
+        Self {
+            thread_id: id_receiver.recv().unwrap_or(0),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for KeyHook {
+    fn drop(&mut self) {
+        let _ = unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        *KEY_SENDER.lock().unwrap() = None;
+    }
+}
+
+unsafe extern "system" fn keyboard_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let message = wparam.0 as u32;
+    if code == HC_ACTION as i32 && (message == WM_KEYDOWN || message == WM_SYSKEYDOWN) {
+        let event = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        if let Some(combo) = combo_from_event(event) {
+            if let Some(sender) = KEY_SENDER.lock().unwrap().as_ref() {
+                let _ = sender.send(combo);
+            }
+        }
+    }
+
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+fn combo_from_event(event: &KBDLLHOOKSTRUCT) -> Option<KeyCombo> {
+    let vk = VIRTUAL_KEY(event.vkCode as u16);
+    if matches!(
+        vk,
+        VK_SHIFT
+            | VK_LSHIFT
+            | VK_RSHIFT
+            | VK_CONTROL
+            | VK_LCONTROL
+            | VK_RCONTROL
+            | VK_MENU
+            | VK_LMENU
+            | VK_RMENU
+            | VK_LWIN
+            | VK_RWIN
+    ) {
+        return None;
+    }
+
+    // The async state still reflects modifiers held before this key went down
+    let held = |key: VIRTUAL_KEY| unsafe { GetAsyncKeyState(key.0 as i32) } < 0;
+
+    Some(KeyCombo {
+        ctrl: held(VK_CONTROL),
+        alt: held(VK_MENU),
+        shift: held(VK_SHIFT),
+        win: held(VK_LWIN) || held(VK_RWIN),
+        key: key_name(vk, event)?,
+    })
+}
+
+fn key_name(vk: VIRTUAL_KEY, event: &KBDLLHOOKSTRUCT) -> Option<String> {
+    let name = match vk {
+        VIRTUAL_KEY(code @ (0x30..=0x39 | 0x41..=0x5A)) => (code as u8 as char).to_string(),
+        VIRTUAL_KEY(code) if (VK_F1.0..=VK_F24.0).contains(&code) => {
+            format!("F{}", code - VK_F1.0 + 1)
+        }
+        VK_BACK => "Backspace".into(),
+        VK_TAB => "Tab".into(),
+        VK_RETURN => "Enter".into(),
+        VK_ESCAPE => "Esc".into(),
+        VK_SPACE => "Space".into(),
+        VK_PRIOR => "PgUp".into(),
+        VK_NEXT => "PgDn".into(),
+        VK_END => "End".into(),
+        VK_HOME => "Home".into(),
+        VK_LEFT => "←".into(),
+        VK_UP => "↑".into(),
+        VK_RIGHT => "→".into(),
+        VK_DOWN => "↓".into(),
+        VK_INSERT => "Ins".into(),
+        VK_DELETE => "Del".into(),
+        _ => {
+            let extended = event.flags.contains(LLKHF_EXTENDED) as u32;
+            let lparam = (event.scanCode << 16) | (extended << 24);
+            let mut buffer = [0u16; 64];
+            let len = unsafe { GetKeyNameTextW(lparam as i32, &mut buffer) };
+            if len <= 0 {
+                return None;
+            }
+            String::from_utf16_lossy(&buffer[..len as usize])
+        }
+    };
+
+    Some(name)
+}
+
+struct Badge {
+    label: String,
+    repeats: u32,
+    shown_at: Instant,
+    image: Option<RgbaFrame>,
+}
+
+impl Badge {
+    fn text(&self) -> String {
+        if self.repeats > 1 {
+            format!("{} ×{}", self.label, self.repeats)
+        } else {
+            self.label.clone()
+        }
+    }
+}
+
+/// Shows recent key combos as fading badges in a corner of the output
+pub struct KeystrokeOverlay {
+    settings: KeystrokeOverlaySettings,
+    receiver: Receiver<KeyCombo>,
+    badges: VecDeque<Badge>,
+    _hook: KeyHook,
+}
+
+impl KeystrokeOverlay {
+    pub fn new(settings: KeystrokeOverlaySettings) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            settings,
+            receiver,
+            badges: VecDeque::new(),
+            _hook: KeyHook::start(sender),
+        }
+    }
+
+    fn display_duration(&self) -> Duration {
+        Duration::from_millis(self.settings.display_ms)
+    }
+
+    fn push_combo(&mut self, combo: KeyCombo) {
+        if self.settings.filter == KeystrokeFilter::ModifierCombos && !combo.has_command_modifier()
+        {
+            return;
+        }
+
+        let label = combo.label();
+        let display = self.display_duration();
+
+        // Held or repeated shortcuts collapse into a counter on the newest badge
+        if let Some(last) = self.badges.back_mut() {
+            if last.label == label && last.shown_at.elapsed() < display {
+                last.repeats += 1;
+                last.shown_at = Instant::now();
+                last.image = None;
+                return;
+            }
+        }
+
+        self.badges.push_back(Badge {
+            label,
+            repeats: 1,
+            shown_at: Instant::now(),
+            image: None,
+        });
+        while self.badges.len() > self.settings.max_visible.max(1) {
+            self.badges.pop_front();
+        }
+    }
+}
+
+impl FrameProcessor for KeystrokeOverlay {
+    fn process(&mut self, frame: &mut RgbaFrame, _ctx: &FrameContext) {
+        while let Ok(combo) = self.receiver.try_recv() {
+            self.push_combo(combo);
+        }
+
+        let display = self.display_duration();
+        self.badges
+            .retain(|badge| badge.shown_at.elapsed() < display + FADE_DURATION);
+
+        let font_size = self.settings.font_size.max(8);
+        let padding_x = font_size * 2 / 3;
+        let padding_y = font_size / 3;
+        let stacks_down = matches!(self.settings.corner, Corner::TopLeft | Corner::TopRight);
+        let mut offset = 0i32;
+
+        // Newest badge sits closest to the corner
+        for badge in self.badges.iter_mut().rev() {
+            if badge.image.is_none() {
+                badge.image = gdi::render_text(&badge.text(), font_size);
+            }
+            let Some(text) = &badge.image else {
+                continue;
+            };
+
+            let age = badge.shown_at.elapsed();
+            let opacity = if age > display {
+                1.0 - (age - display).as_secs_f32() / FADE_DURATION.as_secs_f32()
+            } else {
+                1.0
+            };
+
+            let width = text.width + padding_x * 2;
+            let height = text.height + padding_y * 2;
+            let (x, y) = self.settings.corner.place(frame, width, height, MARGIN);
+            let y = if stacks_down { y + offset } else { y - offset };
+
+            let mut background = BADGE_COLOR;
+            background[3] = (background[3] as f32 * opacity) as u8;
+            frame.fill_rounded_rect(x, y, width, height, height as f32 / 4.0, background);
+            frame.draw_image(text, x + padding_x as i32, y + padding_y as i32, opacity);
+
+            offset += height as i32 + 8;
+        }
+    }
+}
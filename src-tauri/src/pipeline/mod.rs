@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use windows_capture::window::Window;
+
+mod cursor;
+mod frame;
+mod gdi;
+mod keystrokes;
+mod own_windows;
+mod redaction;
+mod webcam;
+mod zoom;
+
+pub use cursor::{CursorHighlight, CursorOverlay, CursorSettings, CursorTracker};
+pub use frame::{PixelRect, RgbaFrame};
+pub use keystrokes::{KeystrokeFilter, KeystrokeOverlay, KeystrokeOverlaySettings};
+pub use own_windows::OwnWindowMask;
+pub use redaction::{RedactionRule, RedactionStyle, RedactionTarget, Redactor};
+pub use webcam::{WebcamOverlay, WebcamOverlaySettings, WebcamShape};
+pub use zoom::{AutoZoom, AutoZoomSettings, Easing};
+
+/// Corner of the output frame that an overlay is anchored to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl Corner {
+    /// Top-left position of a `width`x`height` box placed in this corner with a margin
+    pub fn place(&self, frame: &RgbaFrame, width: u32, height: u32, margin: u32) -> (i32, i32) {
+        let left = margin as i32;
+        let top = margin as i32;
+        let right = frame.width as i32 - width as i32 - margin as i32;
+        let bottom = frame.height as i32 - height as i32 - margin as i32;
+
+        match self {
+            Corner::TopLeft => (left, top),
+            Corner::TopRight => (right, top),
+            Corner::BottomLeft => (left, bottom),
+            Corner::BottomRight => (right, bottom),
+        }
+    }
+}
+
+/// Per-frame information shared with every processor
+#[derive(Debug, Clone, Copy)]
+pub struct FrameContext {
+    /// Frame timestamp in 100ns units, on the same clock as `Frame::timespan`
+    pub timestamp: i64,
+    /// Screen-space position of the frame's top-left pixel
+    pub origin_x: i32,
+    pub origin_y: i32,
+}
+
+/// A CPU-side stage that modifies frames before they reach the encoder
+pub trait FrameProcessor: Send {
+    fn process(&mut self, frame: &mut RgbaFrame, ctx: &FrameContext);
+}
+
+/// Ordered list of processors applied to every captured frame
+#[derive(Default)]
+pub struct FramePipeline {
+    processors: Vec<Box<dyn FrameProcessor>>,
+}
+
+impl FramePipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a processor to the end of the pipeline
+    pub fn push<P: FrameProcessor + 'static>(&mut self, processor: P) {
+        self.processors.push(Box::new(processor));
+    }
+
+    /// True when frames can skip the CPU path entirely
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Runs every processor over the frame in order
+    pub fn process(&mut self, frame: &mut RgbaFrame, ctx: &FrameContext) {
+        for processor in self.processors.iter_mut() {
+            processor.process(frame, ctx);
+        }
+    }
+}
+
+/// Current on-screen rectangle of a window, clipped to the frame.
+/// Returns `None` if the window is gone or entirely outside the frame.
+pub fn window_rect_in_frame(
+    frame: &RgbaFrame,
+    ctx: &FrameContext,
+    window: &Window,
+) -> Option<PixelRect> {
+    let rect = window.rect().ok()?;
+    frame.clip(
+        rect.left - ctx.origin_x,
+        rect.top - ctx.origin_y,
+        (rect.right - rect.left).max(0) as u32,
+        (rect.bottom - rect.top).max(0) as u32,
+    )
+}
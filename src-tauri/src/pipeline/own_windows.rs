@@ -0,0 +1,18 @@
+use windows_capture::window::Window;
+
+use super::{window_rect_in_frame, FrameContext, FrameProcessor, RgbaFrame};
+use crate::window::masked_windows;
+
+/// Blacks out framelet's own windows when the OS capture exclusion isn't available
+pub struct OwnWindowMask;
+
+impl FrameProcessor for OwnWindowMask {
+    fn process(&mut self, frame: &mut RgbaFrame, ctx: &FrameContext) {
+        for hwnd in masked_windows() {
+            let window = Window::from_raw_hwnd(hwnd as *mut _);
+            if let Some(rect) = window_rect_in_frame(frame, ctx, &window) {
+                frame.fill_rect(rect, [0, 0, 0, 255]);
+            }
+        }
+    }
+}
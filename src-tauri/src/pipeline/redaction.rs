@@ -0,0 +1,300 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use windows_capture::window::Window;
+
+use super::{window_rect_in_frame, FrameContext, FrameProcessor, PixelRect, RgbaFrame};
+use crate::capture::{process_name, window_process_id};
+use crate::recording::screen_recorder;
+use crate::types::Region;
+
+/// How often the window list is re-enumerated to pick up newly opened windows
+const WINDOW_REFRESH_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// How redacted pixels are obscured. Blur radii are clamped to 1–64 pixels.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RedactionStyle {
+    Pixelate { block_size: u32 },
+    Blur { radius: u32 },
+    BlackOut,
+}
+
+impl Default for RedactionStyle {
+    fn default() -> Self {
+        RedactionStyle::Pixelate { block_size: 16 }
+    }
+}
+
+/// What part of the capture a rule covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RedactionTarget {
+    /// A fixed rectangle relative to the recorded output
+    Rect { region: Region },
+    /// Every window whose title contains `pattern` (case-insensitive)
+    WindowTitle { pattern: String },
+    /// Every window owned by a process with this executable name, e.g. `slack.exe`
+    Process { name: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionRule {
+    pub target: RedactionTarget,
+    #[serde(default)]
+    pub style: RedactionStyle,
+}
+
+impl RedactionRule {
+    /// `process` is the window's executable name, only looked up when a process rule exists
+    fn matches_window(&self, title: &str, process: Option<&str>) -> bool {
+        match &self.target {
+            RedactionTarget::Rect { .. } => false,
+            RedactionTarget::WindowTitle { pattern } => {
+                title.to_lowercase().contains(&pattern.to_lowercase())
+            }
+            RedactionTarget::Process { name } => {
+                process.is_some_and(|process| process.eq_ignore_ascii_case(name))
+            }
+        }
+    }
+}
+
+/// (rule index, HWND) pairs whose live position is followed every frame
+type MatchedWindows = Arc<Mutex<Vec<(usize, isize)>>>;
+
+/// Obscures static rectangles and matching windows before frames reach the encoder
+pub struct Redactor {
+    rules: Vec<RedactionRule>,
+    matched_windows: MatchedWindows,
+    stop: Arc<AtomicBool>,
+}
+
+impl Redactor {
+    /// Window rules are matched once up front, so the first frame is already covered, and then
+    /// on a background thread that keeps the capture thread free of window enumeration
+    pub fn new(rules: Vec<RedactionRule>) -> Self {
+        let matched_windows = MatchedWindows::default();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let has_window_rules = rules
+            .iter()
+            .any(|rule| !matches!(rule.target, RedactionTarget::Rect { .. }));
+        if has_window_rules {
+            let mut scanner = WindowScanner::new(rules.clone());
+            *matched_windows.lock().unwrap() = scanner.scan();
+
+            thread::spawn({
+                let matched_windows = Arc::clone(&matched_windows);
+                let stop = Arc::clone(&stop);
+                move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        thread::sleep(WINDOW_REFRESH_INTERVAL);
+                        *matched_windows.lock().unwrap() = scanner.scan();
+                    }
+                }
+            });
+        }
+
+        Self {
+            rules,
+            matched_windows,
+            stop,
+        }
+    }
+}
+
+impl Drop for Redactor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl FrameProcessor for Redactor {
+    fn process(&mut self, frame: &mut RgbaFrame, ctx: &FrameContext) {
+        for rule in &self.rules {
+            if let RedactionTarget::Rect { region } = &rule.target {
+                if let Some(rect) = frame.clip(region.x, region.y, region.width, region.height) {
+                    apply_style(frame, rect, rule.style);
+                }
+            }
+        }
+
+        let matched_windows = self.matched_windows.lock().unwrap().clone();
+        for (index, hwnd) in matched_windows {
+            // Windows can move or close between scans, so read the position every frame
+            let window = Window::from_raw_hwnd(hwnd as *mut _);
+            if let Some(rect) = window_rect_in_frame(frame, ctx, &window) {
+                apply_style(frame, rect, self.rules[index].style);
+            }
+        }
+    }
+}
+
+/// Finds the windows the rules match
+struct WindowScanner {
+    rules: Vec<RedactionRule>,
+    has_process_rules: bool,
+    /// Executable names by PID, so each process is only opened once
+    process_names: HashMap<u32, Option<String>>,
+}
+
+impl WindowScanner {
+    fn new(rules: Vec<RedactionRule>) -> Self {
+        let has_process_rules = rules
+            .iter()
+            .any(|rule| matches!(rule.target, RedactionTarget::Process { .. }));
+        Self {
+            rules,
+            has_process_rules,
+            process_names: HashMap::new(),
+        }
+    }
+
+    fn scan(&mut self) -> Vec<(usize, isize)> {
+        let windows = match screen_recorder::get_available_windows() {
+            Ok(windows) => windows,
+            Err(e) => {
+                eprintln!("Failed to enumerate windows for redaction: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut matched = Vec::new();
+        let mut live_pids = HashSet::new();
+        for window in windows {
+            let hwnd = window.as_raw_hwnd() as isize;
+            let title = window.title().unwrap_or_default();
+            let process = if self.has_process_rules {
+                let pid = window_process_id(hwnd);
+                live_pids.insert(pid);
+                self.process_names
+                    .entry(pid)
+                    .or_insert_with(|| process_name(pid))
+                    .clone()
+            } else {
+                None
+            };
+
+            let index = self
+                .rules
+                .iter()
+                .position(|rule| rule.matches_window(&title, process.as_deref()));
+            if let Some(index) = index {
+                matched.push((index, hwnd));
+            }
+        }
+
+        // PIDs are reused, so processes that have gone are looked up afresh next time
+        self.process_names.retain(|pid, _| live_pids.contains(pid));
+        matched
+    }
+}
+
+/// Applies a redaction style to an already clipped rectangle
+pub fn apply_style(frame: &mut RgbaFrame, rect: PixelRect, style: RedactionStyle) {
+    match style {
+        RedactionStyle::BlackOut => frame.fill_rect(rect, [0, 0, 0, 255]),
+        RedactionStyle::Pixelate { block_size } => pixelate(frame, rect, block_size.max(1)),
+        RedactionStyle::Blur { radius } => {
+            // Three box blur passes approximate a gaussian
+            for _ in 0..3 {
+                box_blur(frame, rect, radius.clamp(1, 64));
+            }
+        }
+    }
+}
+
+fn pixelate(frame: &mut RgbaFrame, rect: PixelRect, block_size: u32) {
+    let mut block_y = rect.y;
+    while block_y < rect.y + rect.height {
+        let block_h = block_size.min(rect.y + rect.height - block_y);
+        let mut block_x = rect.x;
+        while block_x < rect.x + rect.width {
+            let block_w = block_size.min(rect.x + rect.width - block_x);
+            let block = PixelRect {
+                x: block_x,
+                y: block_y,
+                width: block_w,
+                height: block_h,
+            };
+
+            let mut sum = [0u64; 4];
+            for row in block.y..block.y + block.height {
+                let start = frame.offset(block.x, row);
+                let end = start + block.width as usize * 4;
+                for px in frame.data[start..end].chunks_exact(4) {
+                    for (channel, value) in sum.iter_mut().zip(px) {
+                        *channel += *value as u64;
+                    }
+                }
+            }
+
+            let count = (block.width * block.height) as u64;
+            let average = sum.map(|channel| (channel / count) as u8);
+            frame.fill_rect(block, average);
+
+            block_x += block_size;
+        }
+        block_y += block_size;
+    }
+}
+
+fn box_blur(frame: &mut RgbaFrame, rect: PixelRect, radius: u32) {
+    let width = rect.width as usize;
+    let height = rect.height as usize;
+    let mut scratch = vec![0u8; width * height * 4];
+
+    // Horizontal pass into scratch
+    for y in 0..height {
+        let row = frame.offset(rect.x, rect.y + y as u32);
+        let src = &frame.data[row..row + width * 4];
+        blur_line(
+            src,
+            &mut scratch[y * width * 4..(y + 1) * width * 4],
+            4,
+            width,
+            radius,
+        );
+    }
+
+    // Vertical pass back into the frame
+    let mut column = vec![0u8; height * 4];
+    let mut blurred = vec![0u8; height * 4];
+    for x in 0..width {
+        for y in 0..height {
+            let src = (y * width + x) * 4;
+            column[y * 4..y * 4 + 4].copy_from_slice(&scratch[src..src + 4]);
+        }
+        blur_line(&column, &mut blurred, 4, height, radius);
+        for y in 0..height {
+            let dst = frame.offset(rect.x + x as u32, rect.y + y as u32);
+            frame.data[dst..dst + 4].copy_from_slice(&blurred[y * 4..y * 4 + 4]);
+        }
+    }
+}
+
+/// Sliding-window box blur over `len` pixels of `stride` bytes, clamping at the edges
+fn blur_line(src: &[u8], dst: &mut [u8], stride: usize, len: usize, radius: u32) {
+    let radius = radius as i64;
+    let window = (radius * 2 + 1) as u32;
+    let last = len as i64 - 1;
+    let sample = |i: i64, channel: usize| src[i.clamp(0, last) as usize * stride + channel] as u32;
+
+    for channel in 0..stride {
+        let mut sum: u32 = (-radius..=radius).map(|i| sample(i, channel)).sum();
+        for i in 0..len as i64 {
+            dst[i as usize * stride + channel] = (sum / window) as u8;
+            sum += sample(i + radius + 1, channel);
+            sum -= sample(i - radius, channel);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{Corner, FrameContext, FrameProcessor, RgbaFrame};
+use crate::webcam::{WebcamFeed, WebcamSource};
+
+const MARGIN: u32 = 32;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebcamShape {
+    /// A centered square of the camera image, masked to a circle
+    #[default]
+    Circle,
+    /// The full camera image with rounded corners
+    RoundedRect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebcamOverlaySettings {
+    pub source: WebcamSource,
+    pub corner: Corner,
+    pub shape: WebcamShape,
+    /// Overlay width as a fraction of the output width, clamped to 0.05–0.5
+    pub size: f32,
+    pub border_width: u32,
+    pub border_color: [u8; 4],
+    /// Flips the image horizontally so it reads like a mirror
+    pub mirror: bool,
+}
+
+impl Default for WebcamOverlaySettings {
+    fn default() -> Self {
+        Self {
+            source: WebcamSource::Pattern,
+            corner: Corner::BottomRight,
+            shape: WebcamShape::Circle,
+            size: 0.2,
+            border_width: 4,
+            border_color: [255, 255, 255, 255],
+            mirror: true,
+        }
+    }
+}
+
+/// The last camera frame, already shaped for compositing
+struct ShapedImage {
+    source: Arc<RgbaFrame>,
+    image: RgbaFrame,
+}
+
+/// Composites the webcam picture-in-picture into a corner of the output
+pub struct WebcamOverlay {
+    settings: WebcamOverlaySettings,
+    feed: WebcamFeed,
+    cached: Option<ShapedImage>,
+}
+
+impl WebcamOverlay {
+    pub fn new(settings: WebcamOverlaySettings, app: AppHandle) -> Self {
+        Self {
+            feed: WebcamFeed::start(settings.source.clone(), app),
+            settings,
+            cached: None,
+        }
+    }
+
+    fn corner_radius(&self, width: u32, height: u32) -> f32 {
+        let shorter = width.min(height) as f32;
+        match self.settings.shape {
+            WebcamShape::Circle => shorter / 2.0,
+            WebcamShape::RoundedRect => shorter / 8.0,
+        }
+    }
+
+    /// Crops, scales, mirrors and masks a camera frame to the overlay shape
+    fn shape(&self, source: &RgbaFrame, output_width: u32) -> RgbaFrame {
+        let width = (output_width as f32 * self.settings.size.clamp(0.05, 0.5)).round() as u32;
+        let width = width.max(16);
+
+        let (src_width, src_height) = (source.width as f32, source.height as f32);
+        let (src_x, src_y, crop_width, crop_height) = match self.settings.shape {
+            WebcamShape::Circle => {
+                let side = src_width.min(src_height);
+                (
+                    (src_width - side) / 2.0,
+                    (src_height - side) / 2.0,
+                    side,
+                    side,
+                )
+            }
+            WebcamShape::RoundedRect => (0.0, 0.0, src_width, src_height),
+        };
+        let height = ((width as f32 * crop_height / crop_width).round() as u32).max(1);
+
+        let mut image = source.resample(src_x, src_y, crop_width, crop_height, width, height);
+        if self.settings.mirror {
+            image.flip_horizontal();
+        }
+        image.mask_rounded(self.corner_radius(width, height));
+        image
+    }
+}
+
+impl FrameProcessor for WebcamOverlay {
+    fn process(&mut self, frame: &mut RgbaFrame, _ctx: &FrameContext) {
+        let Some(source) = self.feed.latest() else {
+            return;
+        };
+        if source.width == 0 || source.height == 0 {
+            return;
+        }
+
+        // Camera frames usually arrive slower than screen frames, so reshape only on change
+        if self
+            .cached
+            .as_ref()
+            .is_none_or(|cached| !Arc::ptr_eq(&cached.source, &source))
+        {
+            let image = self.shape(&source, frame.width);
+            self.cached = Some(ShapedImage { source, image });
+        }
+        let Some(cached) = &self.cached else {
+            return;
+        };
+
+        let image = &cached.image;
+        let border = self.settings.border_width;
+        let (width, height) = (image.width + border * 2, image.height + border * 2);
+        let (x, y) = self.settings.corner.place(frame, width, height, MARGIN);
+
+        if border > 0 {
+            let radius = self.corner_radius(image.width, image.height) + border as f32;
+            frame.fill_rounded_rect(x, y, width, height, radius, self.settings.border_color);
+        }
+        frame.draw_image(image, x + border as i32, y + border as i32, 1.0);
+    }
+}
@@ -0,0 +1,202 @@
+use std::{sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::{cursor::CursorTracker, FrameContext, FrameProcessor, RgbaFrame};
+
+/// Fraction of the view around its center in which cursor movement doesn't trigger a pan
+const DEAD_ZONE: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// A virtual camera that follows the cursor inside a larger capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoZoomSettings {
+    pub output_width: u32,
+    pub output_height: u32,
+    /// Magnification while idle; 1.0 maps one captured pixel to one output pixel
+    pub zoom: f32,
+    /// Magnification right after a click
+    pub click_zoom: f32,
+    /// How long the click zoom is held after the last click
+    pub click_hold_ms: u64,
+    /// Length of each pan/zoom transition
+    pub transition_ms: u64,
+    pub easing: Easing,
+}
+
+impl Default for AutoZoomSettings {
+    fn default() -> Self {
+        Self {
+            output_width: 1920,
+            output_height: 1080,
+            zoom: 1.0,
+            click_zoom: 1.6,
+            click_hold_ms: 1500,
+            transition_ms: 600,
+            easing: Easing::EaseInOut,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Camera {
+    center_x: f32,
+    center_y: f32,
+    zoom: f32,
+}
+
+impl Camera {
+    fn lerp(&self, to: &Camera, t: f32) -> Camera {
+        Camera {
+            center_x: self.center_x + (to.center_x - self.center_x) * t,
+            center_y: self.center_y + (to.center_y - self.center_y) * t,
+            zoom: self.zoom + (to.zoom - self.zoom) * t,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Transition {
+    from: Camera,
+    to: Camera,
+    started_at: i64,
+}
+
+/// Crops a time-varying rectangle out of each frame and scales it to a fixed output size
+pub struct AutoZoom {
+    settings: AutoZoomSettings,
+    tracker: Arc<CursorTracker>,
+    transition: Option<Transition>,
+}
+
+impl AutoZoom {
+    pub fn new(settings: AutoZoomSettings, tracker: Arc<CursorTracker>) -> Self {
+        Self {
+            settings,
+            tracker,
+            transition: None,
+        }
+    }
+
+    /// Size of the encoded output
+    pub fn output_size(settings: &AutoZoomSettings) -> (u32, u32) {
+        (settings.output_width.max(2), settings.output_height.max(2))
+    }
+
+    /// How far along the transition is at `timestamp`, from 0.0 to 1.0 before easing
+    fn progress(&self, transition: &Transition, timestamp: i64) -> f32 {
+        let elapsed =
+            Duration::from_nanos(((timestamp - transition.started_at).max(0) * 100) as u64);
+        let duration = Duration::from_millis(self.settings.transition_ms.max(1));
+        (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Camera position at `timestamp` along the current transition
+    fn camera_at(&self, timestamp: i64) -> Option<Camera> {
+        let transition = self.transition?;
+        let t = self
+            .settings
+            .easing
+            .apply(self.progress(&transition, timestamp));
+        Some(transition.from.lerp(&transition.to, t))
+    }
+
+    /// Keeps the crop rectangle inside the frame, returning (x, y, width, height)
+    fn crop_rect(&self, camera: &Camera, frame: &RgbaFrame) -> (f32, f32, f32, f32) {
+        let (out_width, out_height) = Self::output_size(&self.settings);
+
+        // Never zoom out past the point where the crop would need more pixels than exist
+        let min_zoom =
+            (out_width as f32 / frame.width as f32).max(out_height as f32 / frame.height as f32);
+        let zoom = camera.zoom.max(min_zoom);
+
+        let width = out_width as f32 / zoom;
+        let height = out_height as f32 / zoom;
+        let x = (camera.center_x - width / 2.0).clamp(0.0, (frame.width as f32 - width).max(0.0));
+        let y =
+            (camera.center_y - height / 2.0).clamp(0.0, (frame.height as f32 - height).max(0.0));
+        (x, y, width, height)
+    }
+}
+
+impl FrameProcessor for AutoZoom {
+    fn process(&mut self, frame: &mut RgbaFrame, ctx: &FrameContext) {
+        let state = self.tracker.snapshot();
+        let cursor_x = (state.x - ctx.origin_x) as f32;
+        let cursor_y = (state.y - ctx.origin_y) as f32;
+
+        let click_active = state.last_click.is_some_and(|click| {
+            click.at.elapsed() < Duration::from_millis(self.settings.click_hold_ms)
+        });
+
+        let current = self.camera_at(ctx.timestamp).unwrap_or(Camera {
+            center_x: cursor_x,
+            center_y: cursor_y,
+            zoom: self.settings.zoom,
+        });
+
+        let target_zoom = if click_active {
+            self.settings.click_zoom
+        } else {
+            self.settings.zoom
+        };
+
+        // Pan only once the cursor leaves the central dead zone of the visible area
+        let (x, y, width, height) = self.crop_rect(&current, frame);
+        let inside_dead_zone = (cursor_x - (x + width / 2.0)).abs() < width * DEAD_ZONE / 2.0
+            && (cursor_y - (y + height / 2.0)).abs() < height * DEAD_ZONE / 2.0;
+
+        let previous_target = self.transition.map(|t| t.to);
+        let mut target = previous_target.unwrap_or(current);
+        target.zoom = target_zoom;
+        if !inside_dead_zone
+            || (click_active && previous_target.map(|t| t.zoom) != Some(target_zoom))
+        {
+            target.center_x = cursor_x;
+            target.center_y = cursor_y;
+        }
+
+        // A transition still in flight is restarted from where the camera is now, so the
+        // view never jumps back to the old starting point
+        if previous_target != Some(target) {
+            self.transition = Some(Transition {
+                from: current,
+                to: target,
+                started_at: ctx.timestamp,
+            });
+        }
+
+        let camera = self.camera_at(ctx.timestamp).unwrap_or(current);
+        let (x, y, width, height) = self.crop_rect(&camera, frame);
+        let (out_width, out_height) = Self::output_size(&self.settings);
+        *frame = frame.resample(x, y, width, height, out_width, out_height);
+    }
+}
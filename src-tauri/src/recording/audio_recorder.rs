@@ -0,0 +1,87 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::AppHandle;
+
+use crate::audio::{
+    self, AudioCapture, AudioError, AudioInput, AudioWriter, ENCODER_CHANNELS, ENCODER_SAMPLE_RATE,
+};
+
+use super::{PauseTimeline, RecordingStats, TrackFiles};
+
+/// How often mixed audio is written to the file
+const WRITE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Records the audio inputs alone until stopped, with no video capture at all
+pub fn start_audio_recording(
+    app: AppHandle,
+    mut writer: Box<dyn AudioWriter>,
+    mut tracks: Option<TrackFiles>,
+    output_path: String,
+    inputs: Vec<AudioInput>,
+    stop_signal: Arc<AtomicBool>,
+    pause_signal: Arc<AtomicBool>,
+) -> Result<(), AudioError> {
+    let mut capture = AudioCapture::start(inputs, Some(app.clone()), tracks.is_some());
+    let started_at = audio::now();
+    let mut pauses = PauseTimeline::new();
+    let mut written_frames = 0u64;
+
+    while !stop_signal.load(Ordering::Relaxed) {
+        thread::sleep(WRITE_INTERVAL);
+        pauses.update(pause_signal.load(Ordering::Relaxed), audio::now());
+        written_frames += write_audio(&capture, &pauses, started_at, writer.as_mut())?;
+        if let Some(tracks) = tracks.as_mut() {
+            tracks.write(&capture, started_at, &pauses)?;
+        }
+    }
+
+    println!("\nStopping recording...");
+    capture.stop();
+    written_frames += write_audio(&capture, &pauses, started_at, writer.as_mut())?;
+    writer.finish()?;
+    let audio_tracks = match tracks {
+        Some(mut tracks) => {
+            tracks.write(&capture, started_at, &pauses)?;
+            tracks.finish()?
+        }
+        None => Vec::new(),
+    };
+
+    let stats = RecordingStats {
+        output_path,
+        duration_secs: written_frames as f64 / ENCODER_SAMPLE_RATE as f64,
+        paused_secs: pauses.total(audio::now()) as f64 / 10_000_000.0,
+        audio_drift: capture.drift_stats(),
+        audio_tracks,
+        ..Default::default()
+    };
+    stats.report(&app);
+    Ok(())
+}
+
+/// Writes the audio mixed since the last call, leaving out anything captured while paused.
+/// Returns the number of sample frames written.
+fn write_audio(
+    capture: &AudioCapture,
+    pauses: &PauseTimeline,
+    started_at: i64,
+    writer: &mut dyn AudioWriter,
+) -> Result<u64, AudioError> {
+    let mut frames = 0;
+    for chunk in capture.drain() {
+        if chunk.timestamp < started_at || pauses.map(chunk.timestamp).is_none() {
+            continue;
+        }
+        let samples = chunk.samples();
+        writer.write(&samples)?;
+        frames += (samples.len() / ENCODER_CHANNELS as usize) as u64;
+    }
+    Ok(frames)
+}
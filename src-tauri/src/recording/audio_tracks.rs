@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use crate::audio::{
+    is_audio_file, open_normalized_writer, AudioCapture, AudioError, AudioInput, AudioWriter,
+};
+
+use super::PauseTimeline;
+
+/// Writes each audio input to its own file beside the recording, so the sources can be
+/// edited separately afterwards. The mix still goes into the recording itself.
+pub struct TrackFiles {
+    writers: Vec<Box<dyn AudioWriter>>,
+    paths: Vec<String>,
+}
+
+impl TrackFiles {
+    /// Opens one file per input. Video recordings get WAV files; audio-only recordings use
+    /// the same format as the recording. Tracks of inputs with a loudness stage are
+    /// normalized again once the recording is finished.
+    pub fn create(output_path: &str, inputs: &[AudioInput]) -> Result<Self, AudioError> {
+        let paths: Vec<String> = (0..inputs.len())
+            .map(|track| track_path(output_path, track))
+            .collect();
+        let writers = paths
+            .iter()
+            .zip(inputs)
+            .map(|(path, input)| open_normalized_writer(path, input.filters.loudness.clone()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { writers, paths })
+    }
+
+    /// Writes the tracks captured since the last call. Audio from before `started_at` or
+    /// during a pause is dropped, as it is from the mix.
+    pub fn write(
+        &mut self,
+        capture: &AudioCapture,
+        started_at: i64,
+        pauses: &PauseTimeline,
+    ) -> Result<(), AudioError> {
+        for (track, chunk) in capture.drain_tracks() {
+            if chunk.timestamp < started_at || pauses.map(chunk.timestamp).is_none() {
+                continue;
+            }
+            if let Some(writer) = self.writers.get_mut(track) {
+                writer.write(&chunk.samples())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finishes every file, returning their paths
+    pub fn finish(self) -> Result<Vec<String>, AudioError> {
+        for writer in self.writers {
+            writer.finish()?;
+        }
+        Ok(self.paths)
+    }
+}
+
+/// `recording.mp4` becomes `recording.track1.wav`, `recording.flac` `recording.track1.flac`
+fn track_path(output_path: &str, track: usize) -> String {
+    let path = Path::new(output_path);
+    let extension = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if is_audio_file(output_path) => extension,
+        _ => "wav",
+    };
+    path.with_extension(format!("track{}.{}", track + 1, extension))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn video_recordings_get_wav_tracks() {
+        assert_eq!(track_path("talk.mp4", 0), "talk.track1.wav");
+        assert_eq!(track_path("talk.mkv", 1), "talk.track2.wav");
+        assert_eq!(track_path("talk.MOV", 0), "talk.track1.wav");
+    }
+
+    #[test]
+    fn audio_recordings_keep_their_format() {
+        assert_eq!(track_path("podcast.flac", 0), "podcast.track1.flac");
+        assert_eq!(track_path("podcast.opus", 2), "podcast.track3.opus");
+    }
+}
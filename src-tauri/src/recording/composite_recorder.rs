@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tauri::AppHandle;
+use windows_capture::encoder::{AudioSettingsBuilder, ContainerSettingsBuilder, VideoEncoder};
+
+use crate::{
+    audio::{self, AudioCapture, AudioInput},
+    capture::{offer_preview_frame, Compositor},
+    pipeline::{AutoZoom, FrameContext, RgbaFrame},
+    types::RecordingOptions,
+};
+
+use super::{
+    screen_recorder::{build_pipeline, send_queued_audio, video_settings},
+    PauseTimeline, RecordingStats, TrackFiles,
+};
+
+pub struct CompositeConfig {
+    pub output_path: String,
+    pub fps: u32,
+    pub stop_signal: Arc<AtomicBool>,
+    pub pause_signal: Arc<AtomicBool>,
+    pub options: RecordingOptions,
+    pub audio: Vec<AudioInput>,
+    pub app: AppHandle,
+}
+
+/// Records a compositor at a fixed frame rate until stopped. Each tick takes whatever the
+/// sources last delivered, so sources with different refresh rates never hold each other up.
+pub fn start_composite_recording(
+    mut compositor: Box<dyn Compositor>,
+    config: CompositeConfig,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (width, height) = match &config.options.auto_zoom {
+        Some(auto_zoom) => AutoZoom::output_size(auto_zoom),
+        None => compositor.size(),
+    };
+    let fps = config
+        .options
+        .video
+        .frame_rate
+        .unwrap_or(config.fps)
+        .clamp(1, 120);
+    println!("Compositing {}x{} at {} fps", width, height, fps);
+
+    let mut encoder = VideoEncoder::new(
+        video_settings(width, height, &config.options.video).frame_rate(fps),
+        AudioSettingsBuilder::default().disabled(config.audio.is_empty()),
+        ContainerSettingsBuilder::default(),
+        &config.output_path,
+    )?;
+    let mut pipeline = build_pipeline(&config.options, compositor.origin().is_some(), &config.app);
+    let separate_tracks = config.options.separate_audio_tracks && config.audio.len() > 1;
+    let mut tracks = separate_tracks
+        .then(|| TrackFiles::create(&config.output_path, &config.audio))
+        .transpose()?;
+    let mut audio = (!config.audio.is_empty())
+        .then(|| AudioCapture::start(config.audio, Some(config.app.clone()), separate_tracks));
+
+    let interval = Duration::from_secs(1) / fps;
+    let mut next_tick = Instant::now();
+    let mut pauses = PauseTimeline::new();
+    let mut first_timestamp = None;
+    let mut last_timestamp = 0;
+    let mut stats = RecordingStats {
+        output_path: config.output_path,
+        ..Default::default()
+    };
+
+    while !config.stop_signal.load(Ordering::Relaxed) {
+        next_tick += interval;
+        thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+        // After a stall, carry on from now instead of rendering the missed ticks in a burst
+        next_tick = next_tick.max(Instant::now() - interval);
+
+        let now = audio::now();
+        pauses.update(config.pause_signal.load(Ordering::Relaxed), now);
+
+        let (origin_x, origin_y) = compositor.origin().unwrap_or_default();
+        let frame = if pauses.is_paused() {
+            None
+        } else {
+            compositor.render()
+        };
+        if let Some(frame) = frame {
+            let timestamp = pauses.map(now).unwrap_or(now);
+            pipeline.process(
+                frame,
+                &FrameContext {
+                    timestamp: now,
+                    origin_x,
+                    origin_y,
+                },
+            );
+            let letterboxed;
+            let frame: &RgbaFrame = if (frame.width, frame.height) != (width, height) {
+                letterboxed = frame.letterbox(width, height);
+                &letterboxed
+            } else {
+                frame
+            };
+            encoder.send_frame_buffer(&frame.to_bottom_up(), timestamp)?;
+            offer_preview_frame(|| Some(frame.clone()));
+            first_timestamp.get_or_insert(now);
+            last_timestamp = now;
+            stats.video_frames += 1;
+        }
+
+        if let (Some(audio), Some(first_timestamp)) = (&audio, first_timestamp) {
+            send_queued_audio(&mut encoder, audio, first_timestamp, &pauses)?;
+            if let Some(tracks) = tracks.as_mut() {
+                tracks.write(audio, first_timestamp, &pauses)?;
+            }
+        }
+    }
+
+    println!("\nStopping recording...");
+    if let Some(audio) = audio.as_mut() {
+        audio.stop();
+        if let Some(first_timestamp) = first_timestamp {
+            send_queued_audio(&mut encoder, audio, first_timestamp, &pauses)?;
+            if let Some(tracks) = tracks.as_mut() {
+                tracks.write(audio, first_timestamp, &pauses)?;
+            }
+        }
+        stats.audio_drift = audio.drift_stats();
+    }
+    encoder.finish()?;
+    if let Some(tracks) = tracks {
+        stats.audio_tracks = tracks.finish()?;
+    }
+
+    if let Some(first_timestamp) = first_timestamp {
+        let end = pauses.map(last_timestamp).unwrap_or(last_timestamp);
+        stats.duration_secs = (end - first_timestamp) as f64 / 10_000_000.0;
+    }
+    stats.paused_secs = pauses.total(audio::now()) as f64 / 10_000_000.0;
+    stats.report(&config.app);
+    Ok(())
+}
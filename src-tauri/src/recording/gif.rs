@@ -0,0 +1,571 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::{pipeline::RgbaFrame, types::RecordingOptions, webcam::VideoFileReader};
+
+/// Frame rate GIFs are recorded at when the options don't set one
+const DEFAULT_FPS: u32 = 10;
+/// GIF delays are in hundredths of a second, and players slow down anything under two
+const MAX_FPS: u32 = 50;
+/// LZW codes are at most 12 bits wide
+const MAX_CODES: u16 = 4096;
+
+/// True if `path` asks for a GIF, which is recorded to mp4 and converted once finished
+pub fn is_gif_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"))
+}
+
+/// Payload of `gif-exported`
+#[derive(Debug, Clone, Serialize)]
+pub struct GifExported {
+    pub output_path: String,
+    /// Why the conversion failed, if it did
+    pub error: Option<String>,
+}
+
+/// Turns a finished recording into a GIF
+pub struct GifExport {
+    video_path: String,
+    gif_path: String,
+    fps: u32,
+}
+
+impl GifExport {
+    /// Sets up a GIF recording to `gif_path`, returning the mp4 path to record to first.
+    /// The frame rate is capped so the recording only holds frames the GIF will show.
+    pub fn prepare(gif_path: String, options: &mut RecordingOptions) -> (Self, String) {
+        let fps = options
+            .video
+            .frame_rate
+            .unwrap_or(DEFAULT_FPS)
+            .clamp(1, MAX_FPS);
+        options.video.frame_rate = Some(fps);
+
+        let video_path = Path::new(&gif_path)
+            .with_extension("gif.mp4")
+            .to_string_lossy()
+            .into_owned();
+        let export = Self {
+            video_path: video_path.clone(),
+            gif_path,
+            fps,
+        };
+        (export, video_path)
+    }
+
+    /// Converts the recording and deletes it, then emits `gif-exported`. Recordings that
+    /// were discarded are left alone.
+    pub fn run(self, app: &AppHandle) {
+        if !Path::new(&self.video_path).exists() {
+            return;
+        }
+        println!("Converting {} to GIF...", self.video_path);
+        let error = match self.convert() {
+            Ok(frames) => {
+                println!("Saved {} GIF frames to {}", frames, self.gif_path);
+                if let Err(e) = fs::remove_file(&self.video_path) {
+                    eprintln!("Failed to remove {}: {}", self.video_path, e);
+                }
+                None
+            }
+            Err(e) => {
+                eprintln!("Failed to convert {} to GIF: {}", self.video_path, e);
+                // The recording is kept so nothing is lost
+                let _ = fs::remove_file(&self.gif_path);
+                Some(e)
+            }
+        };
+
+        let exported = GifExported {
+            output_path: self.gif_path,
+            error,
+        };
+        if let Err(e) = app.emit("gif-exported", &exported) {
+            eprintln!("Failed to emit gif-exported event: {}", e);
+        }
+    }
+
+    /// Returns the number of GIF frames written
+    fn convert(&self) -> Result<usize, String> {
+        let mut frames = VideoFileReader::open(&self.video_path).map_err(|e| e.to_string())?;
+        let (first, timestamp) = match frames.next() {
+            Some(frame) => frame.map_err(|e| e.to_string())?,
+            None => return Err("the recording has no frames".into()),
+        };
+
+        let mut writer = GifWriter::create(&self.gif_path, first.width, first.height)
+            .map_err(|e| e.to_string())?;
+        writer.push(first, timestamp).map_err(|e| e.to_string())?;
+        for frame in frames {
+            let (frame, timestamp) = frame.map_err(|e| e.to_string())?;
+            writer.push(frame, timestamp).map_err(|e| e.to_string())?;
+        }
+        writer
+            .finish((100 / self.fps) as u16)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Writes an endlessly looping GIF89a. Each frame only covers the area that changed since
+/// the one before and has its own palette, which suits screen recordings well.
+pub struct GifWriter<W: Write = BufWriter<File>> {
+    out: W,
+    width: u32,
+    height: u32,
+    /// Frame waiting for the next one, which decides how long it is shown
+    pending: Option<(RgbaFrame, i64)>,
+    /// The frame shown before `pending`
+    shown: Option<RgbaFrame>,
+    frames: usize,
+}
+
+impl GifWriter {
+    pub fn create(path: &str, width: u32, height: u32) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), width, height)
+    }
+}
+
+impl<W: Write> GifWriter<W> {
+    fn new(mut out: W, width: u32, height: u32) -> io::Result<Self> {
+        if width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "GIFs can't be larger than 65535 pixels",
+            ));
+        }
+
+        out.write_all(b"GIF89a")?;
+        out.write_all(&(width as u16).to_le_bytes())?;
+        out.write_all(&(height as u16).to_le_bytes())?;
+        // No global color table; every frame brings its own
+        out.write_all(&[0, 0, 0])?;
+        // Loop forever
+        out.write_all(&[0x21, 0xFF, 11])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[3, 1, 0, 0, 0])?;
+
+        Ok(Self {
+            out,
+            width,
+            height,
+            pending: None,
+            shown: None,
+            frames: 0,
+        })
+    }
+
+    /// Adds a frame shown from `timestamp`, in 100ns units. Frames the same as the one
+    /// before only lengthen it, and frames of another size are dropped.
+    pub fn push(&mut self, frame: RgbaFrame, timestamp: i64) -> io::Result<()> {
+        if (frame.width, frame.height) != (self.width, self.height) {
+            return Ok(());
+        }
+        if let Some((pending, pending_timestamp)) = self.pending.take() {
+            if pending.data == frame.data {
+                self.pending = Some((pending, pending_timestamp));
+                return Ok(());
+            }
+            // Rounded on the timeline so the rounding doesn't add up over many frames
+            let delay = centiseconds(timestamp) - centiseconds(pending_timestamp);
+            self.write_frame(pending, delay.clamp(0, u16::MAX as i64) as u16)?;
+        }
+        self.pending = Some((frame, timestamp));
+        Ok(())
+    }
+
+    /// Writes the last frame, shown for `last_delay` hundredths of a second, and ends the
+    /// file. Returns the number of frames written.
+    pub fn finish(mut self, last_delay: u16) -> io::Result<usize> {
+        if let Some((pending, _)) = self.pending.take() {
+            self.write_frame(pending, last_delay)?;
+        }
+        self.out.write_all(&[0x3B])?;
+        self.out.flush()?;
+        Ok(self.frames)
+    }
+
+    fn write_frame(&mut self, frame: RgbaFrame, delay: u16) -> io::Result<()> {
+        let (left, top, width, height) = match &self.shown {
+            Some(shown) => changed_area(shown, &frame),
+            None => (0, 0, self.width, self.height),
+        };
+        let (palette, indices) = quantize(&frame, left, top, width, height);
+        // The color table holds a power of two entries, at least two
+        let bits = (usize::BITS - (palette.len() - 1).max(1).leading_zeros()) as u8;
+
+        // Graphic control extension: leave the frame in place for the next to draw over
+        self.out.write_all(&[0x21, 0xF9, 4, 1 << 2])?;
+        self.out.write_all(&delay.to_le_bytes())?;
+        self.out.write_all(&[0, 0])?;
+
+        self.out.write_all(&[0x2C])?;
+        for value in [left, top, width, height] {
+            self.out.write_all(&(value as u16).to_le_bytes())?;
+        }
+        self.out.write_all(&[0x80 | (bits - 1)])?;
+        for entry in 0..1usize << bits {
+            self.out
+                .write_all(&palette.get(entry).copied().unwrap_or_default())?;
+        }
+
+        let min_code_size = bits.max(2);
+        self.out.write_all(&[min_code_size])?;
+        for block in lzw(min_code_size, &indices).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])?;
+
+        self.shown = Some(frame);
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+fn centiseconds(timestamp: i64) -> i64 {
+    (timestamp + 50_000).div_euclid(100_000)
+}
+
+/// Smallest rectangle holding every pixel that differs between the frames, as left, top,
+/// width and height. A frame identical to the one before still covers one pixel.
+fn changed_area(before: &RgbaFrame, after: &RgbaFrame) -> (u32, u32, u32, u32) {
+    let row_len = after.width as usize * 4;
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    for (y, (old, new)) in before
+        .data
+        .chunks_exact(row_len)
+        .zip(after.data.chunks_exact(row_len))
+        .enumerate()
+    {
+        if old == new {
+            continue;
+        }
+        let pixels = || old.chunks_exact(4).zip(new.chunks_exact(4));
+        let first = pixels().position(|(a, b)| a != b).unwrap_or(0) as u32;
+        let last = (after.width as usize
+            - 1
+            - pixels().rev().position(|(a, b)| a != b).unwrap_or(0)) as u32;
+        let y = y as u32;
+        bounds = Some(match bounds {
+            Some((left, top, right, _)) => (left.min(first), top, right.max(last), y),
+            None => (first, y, last, y),
+        });
+    }
+    match bounds {
+        Some((left, top, right, bottom)) => (left, top, right - left + 1, bottom - top + 1),
+        None => (0, 0, 1, 1),
+    }
+}
+
+/// Colors at 4 bits per channel, the precision palettes are chosen at
+fn bucket(pixel: &[u8]) -> usize {
+    (pixel[0] as usize >> 4) << 8 | (pixel[1] as usize >> 4) << 4 | pixel[2] as usize >> 4
+}
+
+/// Picks up to 256 colors for the area and maps every pixel in it to one of them. The most
+/// common colors are kept, averaged within their bucket, and the rest use the nearest.
+fn quantize(
+    frame: &RgbaFrame,
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+) -> (Vec<[u8; 3]>, Vec<u8>) {
+    let rows = || {
+        (top..top + height).map(move |y| {
+            let start = (y as usize * frame.width as usize + left as usize) * 4;
+            &frame.data[start..start + width as usize * 4]
+        })
+    };
+
+    // Sums of red, green and blue, and the pixel count, per bucket
+    let mut buckets = vec![[0u64; 4]; 4096];
+    for pixel in rows().flat_map(|row| row.chunks_exact(4)) {
+        let sums = &mut buckets[bucket(pixel)];
+        for channel in 0..3 {
+            sums[channel] += pixel[channel] as u64;
+        }
+        sums[3] += 1;
+    }
+    let average =
+        |sums: &[u64; 4]| [0, 1, 2].map(|channel| ((sums[channel] + sums[3] / 2) / sums[3]) as u8);
+
+    let mut used: Vec<usize> = (0..buckets.len())
+        .filter(|&key| buckets[key][3] > 0)
+        .collect();
+    used.sort_by_key(|&key| std::cmp::Reverse(buckets[key][3]));
+    let palette: Vec<[u8; 3]> = used
+        .iter()
+        .take(256)
+        .map(|&key| average(&buckets[key]))
+        .collect();
+
+    let mut map = vec![0u8; buckets.len()];
+    for (rank, &key) in used.iter().enumerate() {
+        map[key] = if rank < palette.len() {
+            rank as u8
+        } else {
+            let color = average(&buckets[key]);
+            let distance = |entry: &[u8; 3]| {
+                (0..3)
+                    .map(|channel| (entry[channel] as i32 - color[channel] as i32).pow(2))
+                    .sum::<i32>()
+            };
+            (0..palette.len())
+                .min_by_key(|&index| distance(&palette[index]))
+                .unwrap_or(0) as u8
+        };
+    }
+
+    let indices = rows()
+        .flat_map(|row| row.chunks_exact(4))
+        .map(|pixel| map[bucket(pixel)])
+        .collect();
+    (palette, indices)
+}
+
+/// Packs codes least significant bit first, as GIF expects
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn push(&mut self, code: u16, width: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// LZW-compresses color indices the way GIF image data is stored, before it is split into
+/// blocks. The table starts over once it fills up.
+fn lzw(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut width = min_code_size as u32 + 1;
+    let mut next = end + 1;
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut bits = BitWriter::default();
+    bits.push(clear, width);
+
+    let mut current: Option<u16> = None;
+    for &index in indices {
+        let Some(prefix) = current else {
+            current = Some(index as u16);
+            continue;
+        };
+        if let Some(&code) = codes.get(&(prefix, index)) {
+            current = Some(code);
+            continue;
+        }
+
+        bits.push(prefix, width);
+        codes.insert((prefix, index), next);
+        next += 1;
+        // The decoder adds its entry a code later, so it widens a code later too
+        if next > 1 << width && width < 12 {
+            width += 1;
+        }
+        if next == MAX_CODES {
+            bits.push(clear, width);
+            codes.clear();
+            width = min_code_size as u32 + 1;
+            next = end + 1;
+        }
+        current = Some(index as u16);
+    }
+
+    if let Some(code) = current {
+        bits.push(code, width);
+        // The decoder adds an entry for this code too before reading the end code
+        if next + 1 > 1 << width && width < 12 {
+            width += 1;
+        }
+    }
+    bits.push(end, width);
+    bits.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads back what `lzw` wrote, following the GIF specification
+    fn unlzw(min_code_size: u8, bytes: &[u8]) -> Vec<u8> {
+        let clear = 1u16 << min_code_size;
+        let end = clear + 1;
+        let mut width = min_code_size as u32 + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            table.clear();
+            table.extend((0..clear).map(|index| vec![index as u8]));
+            // The clear and end codes
+            table.push(Vec::new());
+            table.push(Vec::new());
+        };
+        reset(&mut table);
+
+        let mut output = Vec::new();
+        let mut previous: Option<Vec<u8>> = None;
+        let (mut buffer, mut bits, mut bytes) = (0u32, 0u32, bytes.iter());
+        loop {
+            while bits < width {
+                buffer |= (*bytes.next().expect("ran out of data") as u32) << bits;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << width) - 1)) as u16;
+            buffer >>= width;
+            bits -= width;
+
+            if code == clear {
+                reset(&mut table);
+                width = min_code_size as u32 + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return output;
+            }
+
+            let entry = match (table.get(code as usize), &previous) {
+                (Some(entry), _) => entry.clone(),
+                // The code being defined right now
+                (None, Some(previous)) => [previous.clone(), vec![previous[0]]].concat(),
+                (None, None) => panic!("code {} isn't defined", code),
+            };
+            if let Some(previous) = previous {
+                if table.len() < MAX_CODES as usize {
+                    table.push([previous, vec![entry[0]]].concat());
+                }
+                if table.len() == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+            output.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    /// The same numbers on every run
+    fn pseudo_random(count: usize, range: u32) -> Vec<u8> {
+        let mut state = 0x2545_f491_u32;
+        (0..count)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state % range) as u8
+            })
+            .collect()
+    }
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> RgbaFrame {
+        RgbaFrame::new(width, height, color.repeat((width * height) as usize))
+    }
+
+    #[test]
+    fn lzw_round_trips() {
+        for (min_code_size, indices) in [
+            (2, vec![0, 1, 2, 3, 0, 0, 0, 0, 1, 1, 1, 1]),
+            (2, vec![1]),
+            (2, Vec::new()),
+            (8, vec![7; 10_000]),
+            // Enough varied input to fill the table and start over several times
+            (8, pseudo_random(100_000, 256)),
+            (4, pseudo_random(50_000, 16)),
+        ] {
+            assert_eq!(unlzw(min_code_size, &lzw(min_code_size, &indices)), indices);
+        }
+    }
+
+    #[test]
+    fn keeps_colors_exact_when_there_are_few() {
+        let mut frame = solid(4, 2, [200, 30, 30, 255]);
+        frame.data[4..8].copy_from_slice(&[16, 32, 48, 255]);
+        let (palette, indices) = quantize(&frame, 0, 0, 4, 2);
+        assert_eq!(palette, [[200, 30, 30], [16, 32, 48]]);
+        assert_eq!(indices, [0, 1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn limits_the_palette_to_256_colors() {
+        let data: Vec<u8> = pseudo_random(64 * 64 * 4, 256);
+        let frame = RgbaFrame::new(64, 64, data);
+        let (palette, indices) = quantize(&frame, 0, 0, 64, 64);
+        assert_eq!(palette.len(), 256);
+        assert_eq!(indices.len(), 64 * 64);
+    }
+
+    #[test]
+    fn finds_the_changed_area() {
+        let before = solid(8, 6, [0, 0, 0, 255]);
+        let mut after = before.clone();
+        for (x, y) in [(2, 1), (5, 3)] {
+            let at = (y * 8 + x) * 4;
+            after.data[at..at + 4].copy_from_slice(&[255, 255, 255, 255]);
+        }
+        assert_eq!(changed_area(&before, &after), (2, 1, 4, 3));
+        assert_eq!(changed_area(&before, &before), (0, 0, 1, 1));
+    }
+
+    #[test]
+    fn writes_a_looping_gif() {
+        let mut out = Vec::new();
+        let mut writer = GifWriter::new(&mut out, 8, 6).unwrap();
+        writer.push(solid(8, 6, [0, 0, 0, 255]), 0).unwrap();
+        // The same again, which only lengthens the first frame
+        writer.push(solid(8, 6, [0, 0, 0, 255]), 1_000_000).unwrap();
+        writer
+            .push(solid(8, 6, [255, 0, 0, 255]), 2_500_000)
+            .unwrap();
+        // Another size, dropped
+        writer
+            .push(solid(4, 4, [0, 255, 0, 255]), 3_000_000)
+            .unwrap();
+        assert_eq!(writer.finish(10).unwrap(), 2);
+
+        assert!(out.starts_with(b"GIF89a\x08\x00\x06\x00"));
+        assert!(out.windows(11).any(|window| window == b"NETSCAPE2.0"));
+        assert_eq!(out.last(), Some(&0x3B));
+
+        // The first frame is shown for the 0.25s until the red one, the last for 0.1s
+        let delays: Vec<u16> = out
+            .windows(6)
+            .filter(|window| window[..4] == [0x21, 0xF9, 4, 1 << 2])
+            .map(|window| u16::from_le_bytes([window[4], window[5]]))
+            .collect();
+        assert_eq!(delays, [25, 10]);
+    }
+
+    #[test]
+    fn recognizes_gif_paths() {
+        assert!(is_gif_file(r"C:\Videos\repro.gif"));
+        assert!(is_gif_file("repro.GIF"));
+        assert!(!is_gif_file("repro.mp4"));
+        assert!(!is_gif_file("gif"));
+    }
+}
@@ -1,5 +1,20 @@
+mod audio_recorder;
+mod audio_tracks;
+mod composite_recorder;
+mod gif;
+mod pause;
 pub mod screen_recorder;
 mod session;
+mod stats;
+mod window_state;
 
-pub use screen_recorder::{start_recording, RecordingConfig, ScreenRecorder};
-pub use session::RecordingSession;
+pub use audio_recorder::start_audio_recording;
+pub use audio_tracks::TrackFiles;
+pub use composite_recorder::{start_composite_recording, CompositeConfig};
+pub use gif::{is_gif_file, GifExport, GifExported};
+pub use pause::PauseTimeline;
+pub use screen_recorder::{
+    start_recording, CaptureTarget, RecordingConfig, ScreenRecorder, SourceClosed,
+};
+pub use session::{RecordingSession, SourceLost};
+pub use stats::RecordingStats;
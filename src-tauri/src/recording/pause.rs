@@ -0,0 +1,59 @@
+/// Pauses taken during a recording, used to close the gaps they leave in the output timeline.
+/// Times are in 100ns units on the capture clock.
+#[derive(Debug, Default)]
+pub struct PauseTimeline {
+    /// Start and end of each pause; the last one is open while paused
+    pauses: Vec<(i64, Option<i64>)>,
+}
+
+impl PauseTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts or ends a pause at `now` when `paused` differs from the current state
+    pub fn update(&mut self, paused: bool, now: i64) {
+        match (paused, self.is_paused()) {
+            (true, false) => self.pauses.push((now, None)),
+            (false, true) => {
+                if let Some((_, end)) = self.pauses.last_mut() {
+                    *end = Some(now);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(self.pauses.last(), Some((_, None)))
+    }
+
+    /// True once any pause has been taken, after which timestamps need shifting
+    pub fn has_paused(&self) -> bool {
+        !self.pauses.is_empty()
+    }
+
+    /// Total paused time in 100ns units, counting an open pause up to `now`
+    pub fn total(&self, now: i64) -> i64 {
+        self.pauses
+            .iter()
+            .map(|&(start, end)| end.unwrap_or(now).max(start) - start)
+            .sum()
+    }
+
+    /// Moves a capture timestamp onto the output timeline by removing the pauses before it.
+    /// Returns `None` for timestamps that fall inside a pause.
+    pub fn map(&self, timestamp: i64) -> Option<i64> {
+        let mut shift = 0;
+        for &(start, end) in &self.pauses {
+            if timestamp < start {
+                break;
+            }
+            match end {
+                Some(end) if timestamp >= end => shift += end - start,
+                _ => return None,
+            }
+        }
+        Some(timestamp - shift)
+    }
+}
@@ -1,37 +1,106 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
 };
 
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
 use windows::Win32::{Foundation::HWND, UI::WindowsAndMessaging::IsIconic};
 use windows_capture::{
     capture::{Context, GraphicsCaptureApiHandler},
     encoder::{AudioSettingsBuilder, ContainerSettingsBuilder, VideoEncoder, VideoSettingsBuilder},
     frame::Frame,
     graphics_capture_api::InternalCaptureControl,
-    settings::{ColorFormat, CursorCaptureSettings, DrawBorderSettings, Settings},
+    settings::{ColorFormat, DrawBorderSettings, Settings},
     window::Window,
     WindowsCaptureGraphicsCaptureItem,
 };
 
-use crate::types::Region;
+use crate::{
+    audio::{self, AudioCapture, AudioInput},
+    capture::{
+        is_cloaked, offer_captured_frame, offer_preview_frame, window_bounds, window_process_id,
+        GpuDownscaler, VirtualDesktops,
+    },
+    pipeline::{
+        AutoZoom, CursorOverlay, CursorTracker, FrameContext, FramePipeline, KeystrokeOverlay,
+        OwnWindowMask, Redactor, RgbaFrame, WebcamOverlay,
+    },
+    types::{
+        CloseBehavior, MinimizeBehavior, RecordingOptions, Region, ResizeBehavior,
+        SourceChangeSettings, VideoEncoderSettings,
+    },
+    window::masked_windows,
+};
+
+use super::{window_state::MinimizeWatcher, PauseTimeline, RecordingStats, TrackFiles};
 
-// TODO: Audio capture - use wasapi-rs with `send_frame_with_audio` or `send_audio_buffer` in windows-capture
+/// How often pauses and audio are handled while no frames arrive
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
 
 #[derive(Debug, Clone)]
 pub struct RecordingConfig {
     pub width: u32,
     pub height: u32,
     pub output_path: String,
-    pub stop_signal: Arc<AtomicBool>,
+    pub pause_signal: Arc<AtomicBool>,
     pub region: Option<Region>,
+    pub target: CaptureTarget,
+    pub options: RecordingOptions,
+    /// Audio inputs mixed into the recording; empty for a silent video
+    pub audio: Vec<AudioInput>,
+    /// Used to report audio levels to the frontend
+    pub app: AppHandle,
+}
+
+/// What is being captured, used to map screen coordinates into frame coordinates
+#[derive(Debug, Clone, Copy)]
+pub enum CaptureTarget {
+    Monitor { left: i32, top: i32 },
+    Window { hwnd: isize },
 }
 
 // Handles capture events.
 pub struct ScreenRecorder {
     encoder: Option<VideoEncoder>,
-    stop_signal: Arc<AtomicBool>,
+    pause_signal: Arc<AtomicBool>,
+    pauses: PauseTimeline,
     region: Option<Region>,
+    target: CaptureTarget,
+    pipeline: FramePipeline,
+    audio: Option<AudioCapture>,
+    tracks: Option<TrackFiles>,
+    /// Timestamp of the first video frame, which the encoder treats as time zero
+    first_timestamp: Option<i64>,
+    last_timestamp: i64,
+    stats: RecordingStats,
+    app: AppHandle,
+    source_changes: SourceChangeSettings,
+    /// Size of the source when the recording started, which every frame is fitted back into
+    source_size: (u32, u32),
+    output_size: (u32, u32),
+    minimize_watcher: Option<MinimizeWatcher>,
+    minimized: bool,
+    /// Set once a frame was sent that didn't come from the capture, after which the GPU
+    /// path's own timestamps can't be mixed in
+    cpu_only: bool,
+    /// Shortest time between encoded frames, in 100ns units, when the frame rate is capped
+    min_frame_interval: Option<i64>,
+    /// Output time of the slot the last encoded frame filled when the frame rate is capped
+    frame_slot: Option<i64>,
+    /// Reads preview frames back from the GPU when the frame itself stays there
+    preview_downscaler: GpuDownscaler,
+}
+
+/// Payload of `recording-source-closed`
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceClosed {
+    /// The finished file, or `None` if it was discarded
+    pub output_path: Option<String>,
 }
 
 impl GraphicsCaptureApiHandler for ScreenRecorder {
@@ -42,8 +111,11 @@ impl GraphicsCaptureApiHandler for ScreenRecorder {
         println!("Using dimensions: {}x{}", ctx.flags.width, ctx.flags.height);
         println!("Output file: {}", ctx.flags.output_path);
 
-        // Use cropped dimensions if region is specified
-        let (encoder_width, encoder_height) = if let Some(region) = &ctx.flags.region {
+        // Use the virtual camera size for auto-zoom, otherwise cropped dimensions if region is specified
+        let (encoder_width, encoder_height) = if let Some(auto_zoom) = &ctx.flags.options.auto_zoom
+        {
+            AutoZoom::output_size(auto_zoom)
+        } else if let Some(region) = &ctx.flags.region {
             (region.width, region.height)
         } else {
             (ctx.flags.width, ctx.flags.height)
@@ -52,16 +124,63 @@ impl GraphicsCaptureApiHandler for ScreenRecorder {
         println!("Encoder dimensions: {}x{}", encoder_width, encoder_height);
 
         let encoder = VideoEncoder::new(
-            VideoSettingsBuilder::new(encoder_width, encoder_height),
-            AudioSettingsBuilder::default().disabled(true),
+            video_settings(encoder_width, encoder_height, &ctx.flags.options.video),
+            AudioSettingsBuilder::default().disabled(ctx.flags.audio.is_empty()),
             ContainerSettingsBuilder::default(),
             &ctx.flags.output_path,
         )?;
 
+        let pipeline = build_pipeline(&ctx.flags.options, true, &ctx.flags.app);
+
+        let minimize_watcher = match ctx.flags.target {
+            CaptureTarget::Window { hwnd } => Some(MinimizeWatcher::start(hwnd)),
+            CaptureTarget::Monitor { .. } => None,
+        };
+
+        let separate_tracks = ctx.flags.options.separate_audio_tracks && ctx.flags.audio.len() > 1;
+        let tracks = separate_tracks
+            .then(|| TrackFiles::create(&ctx.flags.output_path, &ctx.flags.audio))
+            .transpose()?;
+
+        // Started last so no audio queues up while the encoder and pipeline are being set up
+        let audio = (!ctx.flags.audio.is_empty()).then(|| {
+            AudioCapture::start(
+                ctx.flags.audio,
+                Some(ctx.flags.app.clone()),
+                separate_tracks,
+            )
+        });
+
         Ok(Self {
             encoder: Some(encoder),
-            stop_signal: ctx.flags.stop_signal,
+            pause_signal: ctx.flags.pause_signal,
+            pauses: PauseTimeline::new(),
             region: ctx.flags.region,
+            target: ctx.flags.target,
+            pipeline,
+            audio,
+            tracks,
+            first_timestamp: None,
+            last_timestamp: 0,
+            stats: RecordingStats {
+                output_path: ctx.flags.output_path,
+                ..Default::default()
+            },
+            app: ctx.flags.app,
+            source_changes: ctx.flags.options.source_changes,
+            source_size: (ctx.flags.width, ctx.flags.height),
+            output_size: (encoder_width, encoder_height),
+            minimize_watcher,
+            minimized: false,
+            cpu_only: false,
+            min_frame_interval: ctx
+                .flags
+                .options
+                .video
+                .frame_rate
+                .map(|fps| 10_000_000 / fps.clamp(1, 120) as i64),
+            frame_slot: None,
+            preview_downscaler: GpuDownscaler::new(),
         })
     }
 
@@ -69,84 +188,398 @@ impl GraphicsCaptureApiHandler for ScreenRecorder {
     fn on_frame_arrived(
         &mut self,
         frame: &mut Frame,
-        capture_control: InternalCaptureControl,
+        _capture_control: InternalCaptureControl,
     ) -> Result<(), Self::Error> {
-        if self.stop_signal.load(Ordering::Relaxed) {
-            println!("\nStopping recording...");
-            self.encoder.take().unwrap().finish()?;
-            capture_control.stop();
+        // Frames can still arrive after `start_recording` finished the file to stop
+        if self.encoder.is_none() {
             return Ok(());
         }
 
-        match &self.region {
-            Some(region) => {
-                // The region coordinates are in screen space, but we need to adjust them
-                // relative to the captured frame. For now, let's assume they're already correct
-                // but add some debugging to verify
-                let start_x = region.x as u32;
-                let start_y = region.y as u32;
-                let end_x = (region.x as u32) + region.width;
-                let end_y = (region.y as u32) + region.height;
-
-                let duration = frame.timespan().Duration;
-                let mut cropped_frame = frame
-                    .buffer_crop(start_x, start_y, end_x, end_y)
-                    .expect("Failed to crop buffer");
-
-                let raw_cropped_buffer = cropped_frame.as_nopadding_buffer()?;
-
-                let expected_size = region.width as usize * region.height as usize * 4;
-                if raw_cropped_buffer.len() != expected_size {
-                    println!(
-                        "WARNING: Buffer size mismatch! Expected: {}, Got: {}",
-                        expected_size,
-                        raw_cropped_buffer.len()
-                    );
-                }
+        let duration = frame.timespan().Duration;
+        self.update_pauses(duration)?;
+        // Frames after a pause are moved back so the output has no gap. Frames captured during
+        // a pause are left out, including ones that only arrive once it has ended.
+        let Some(timestamp) = self.pauses.map(duration) else {
+            // The preview keeps showing the source while the recording is paused
+            offer_captured_frame(frame, self.region.as_ref(), &mut self.preview_downscaler);
+            // Audio captured during the pause is dropped as it arrives
+            return self.send_audio();
+        };
 
-                // The buffer comes in upside down, I believe this is a side effect of the cropping
-                // but I'm not sure - just flip it for now.
-                let mut flipped_buffer = Vec::with_capacity(raw_cropped_buffer.len());
-                let width = region.width as usize;
-                let height = region.height as usize;
-                let bytes_per_row = width * 4; // RGBA = 4 bytes per pixel
-
-                for row in (0..height).rev() {
-                    let start_idx = row * bytes_per_row;
-                    let end_idx = start_idx + bytes_per_row;
-                    flipped_buffer.extend_from_slice(&raw_cropped_buffer[start_idx..end_idx]);
-                }
+        // Frames arriving faster than the frame rate cap are skipped. Slots advance by the
+        // interval rather than from each frame, so late frames don't lower the rate.
+        if let Some(interval) = self.min_frame_interval {
+            let slot = match self.frame_slot {
+                Some(last) if timestamp < last + interval => return self.send_audio(),
+                // After a pause or a still screen, start again from this frame
+                Some(last) if timestamp < last + 2 * interval => last + interval,
+                _ => timestamp,
+            };
+            self.frame_slot = Some(slot);
+        }
+
+        self.first_timestamp.get_or_insert(duration);
+        self.last_timestamp = duration;
+        self.stats.video_frames += 1;
+
+        let resized = (frame.width(), frame.height()) != self.source_size;
+
+        // Fast path: hand the GPU surface straight to the encoder, which can't be retimed
+        if self.region.is_none()
+            && self.pipeline.is_empty()
+            && !self.pauses.has_paused()
+            && !resized
+            && !self.cpu_only
+        {
+            offer_captured_frame(frame, None, &mut self.preview_downscaler);
+            self.encoder.as_mut().unwrap().send_frame(frame)?;
+            return self.send_audio();
+        }
+
+        let mut rgba = if resized {
+            // The encoder size is fixed, so the new size is fitted into the original one
+            let fitted = self.fit_to_source(&copy_frame(frame, None)?);
+            match &self.region {
+                Some(region) => fitted.crop(region.x, region.y, region.width, region.height),
+                None => fitted,
+            }
+        } else {
+            copy_frame(frame, self.region.as_ref())?
+        };
+
+        let (origin_x, origin_y) = self.frame_origin();
+        let ctx = FrameContext {
+            timestamp: duration,
+            origin_x,
+            origin_y,
+        };
+        self.pipeline.process(&mut rgba, &ctx);
+
+        // The encoder expects raw buffers bottom-up
+        let flipped_buffer = rgba.to_bottom_up();
+        offer_preview_frame(move || Some(rgba));
+
+        match self
+            .encoder
+            .as_mut()
+            .unwrap()
+            .send_frame_buffer(&flipped_buffer, timestamp)
+        {
+            Ok(_) => (),
+            Err(e) => {
+                println!("ERROR sending frame: {}", e);
+                return Err(Box::new(e));
+            }
+        }
+
+        self.send_audio()
+    }
 
-                match self
-                    .encoder
-                    .as_mut()
-                    .unwrap()
-                    .send_frame_buffer(&flipped_buffer, duration)
-                {
-                    Ok(_) => (),
-                    Err(e) => {
-                        println!("ERROR sending frame: {}", e);
-                        return Err(Box::new(e));
-                    }
+    // Called when the capture item (usually a window) closes; no frames arrive after this.
+    fn on_closed(&mut self) -> Result<(), Self::Error> {
+        println!("Capture source closed, finishing recording");
+        self.finish()?;
+
+        let mut output_path = Some(self.stats.output_path.clone());
+        if self.source_changes.on_close == CloseBehavior::Discard {
+            match std::fs::remove_file(&self.stats.output_path) {
+                Ok(()) => output_path = None,
+                Err(e) => eprintln!("Failed to discard recording: {}", e),
+            }
+            for path in &self.stats.audio_tracks {
+                if let Err(e) = std::fs::remove_file(path) {
+                    eprintln!("Failed to discard audio track {}: {}", path, e);
                 }
             }
-            None => {
-                self.encoder.as_mut().unwrap().send_frame(frame)?;
+        }
+
+        if let Err(e) = self
+            .app
+            .emit("recording-source-closed", SourceClosed { output_path })
+        {
+            eprintln!("Failed to emit recording-source-closed event: {}", e);
+        }
+        Ok(())
+    }
+}
+
+impl ScreenRecorder {
+    /// Flushes the remaining audio and finishes the file. Does nothing the second time.
+    fn finish(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.encoder.is_none() {
+            return Ok(());
+        }
+
+        if let Some(audio) = self.audio.as_mut() {
+            audio.stop();
+        }
+        self.send_audio()?;
+        self.encoder.take().unwrap().finish()?;
+        if let Some(tracks) = self.tracks.take() {
+            self.stats.audio_tracks = tracks.finish()?;
+        }
+        self.report_stats();
+        Ok(())
+    }
+
+    /// Applies pauses, minimizing and queued audio between frames. A static screen or a
+    /// minimized window delivers no frames, so without this they would wait for the next one.
+    pub fn tick(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.encoder.is_none() {
+            return Ok(());
+        }
+        self.update_pauses(audio::now())?;
+        self.send_audio()
+    }
+
+    /// Starts or ends a pause at `now` to match the pause button and the minimize state
+    fn update_pauses(&mut self, now: i64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.apply_minimize_changes()?;
+        let minimize_pause =
+            self.minimized && self.source_changes.on_minimize == MinimizeBehavior::Pause;
+        self.pauses.update(
+            self.pause_signal.load(Ordering::Relaxed) || minimize_pause,
+            now,
+        );
+        Ok(())
+    }
+
+    /// Applies the minimize and restore changes seen since the last frame
+    fn apply_minimize_changes(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(watcher) = &self.minimize_watcher else {
+            return Ok(());
+        };
+
+        for (time, minimized) in watcher.drain() {
+            self.minimized = minimized;
+            match self.source_changes.on_minimize {
+                MinimizeBehavior::Pause => {
+                    let paused = minimized || self.pause_signal.load(Ordering::Relaxed);
+                    self.pauses.update(paused, time);
+                }
+                MinimizeBehavior::Placeholder if minimized => self.send_placeholder(time)?,
+                MinimizeBehavior::Placeholder => {}
             }
         }
+        Ok(())
+    }
 
+    /// Sends a black frame at `time`, which the encoder shows until the next real frame
+    fn send_placeholder(
+        &mut self,
+        time: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Frames have to go out in order, and nothing is shown before the first one anyway
+        if self.first_timestamp.is_none() || time <= self.last_timestamp {
+            return Ok(());
+        }
+        let Some(timestamp) = self.pauses.map(time) else {
+            return Ok(());
+        };
+
+        let (width, height) = self.output_size;
+        let placeholder = RgbaFrame::blank(width, height);
+        self.encoder
+            .as_mut()
+            .unwrap()
+            .send_frame_buffer(&placeholder.to_bottom_up(), timestamp)?;
+        offer_preview_frame(move || Some(placeholder));
+
+        self.last_timestamp = time;
+        self.stats.video_frames += 1;
+        self.cpu_only = true;
         Ok(())
     }
 
-    // Optional handler called when the capture item (usually a window) closes.
-    fn on_closed(&mut self) -> Result<(), Self::Error> {
-        println!("Capture session ended");
+    /// Fits a frame whose source changed size back into the size the recording started with
+    fn fit_to_source(&self, frame: &RgbaFrame) -> RgbaFrame {
+        let (width, height) = self.source_size;
+        match self.source_changes.on_resize {
+            ResizeBehavior::Letterbox => frame.letterbox(width, height),
+            ResizeBehavior::Scale => frame.resample(
+                0.0,
+                0.0,
+                frame.width as f32,
+                frame.height as f32,
+                width,
+                height,
+            ),
+        }
+    }
+
+    /// Logs the finished recording's stats and sends them to the frontend
+    fn report_stats(&mut self) {
+        if let Some(first_timestamp) = self.first_timestamp {
+            // Measured on the output timeline, which the pauses were cut out of
+            let output_time = |time: i64| self.pauses.map(time).unwrap_or(time);
+            self.stats.duration_secs = (output_time(self.last_timestamp)
+                - output_time(first_timestamp)) as f64
+                / 10_000_000.0;
+        }
+        self.stats.paused_secs = self.pauses.total(audio::now()) as f64 / 10_000_000.0;
+        if let Some(audio) = &self.audio {
+            self.stats.audio_drift = audio.drift_stats();
+        }
+        self.stats.report(&self.app);
+    }
+
+    fn send_audio(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (Some(audio), Some(first_timestamp)) = (&self.audio, self.first_timestamp) else {
+            return Ok(());
+        };
+        let Some(encoder) = self.encoder.as_mut() else {
+            return Ok(());
+        };
+        send_queued_audio(encoder, audio, first_timestamp, &self.pauses)?;
+        if let Some(tracks) = self.tracks.as_mut() {
+            tracks.write(audio, first_timestamp, &self.pauses)?;
+        }
         Ok(())
     }
+
+    /// Screen-space position of the top-left pixel of the frames being encoded
+    fn frame_origin(&self) -> (i32, i32) {
+        let (left, top) = match self.target {
+            CaptureTarget::Monitor { left, top } => (left, top),
+            CaptureTarget::Window { hwnd } => Window::from_raw_hwnd(hwnd as *mut _)
+                .rect()
+                .map(|rect| (rect.left, rect.top))
+                .unwrap_or((0, 0)),
+        };
+
+        match &self.region {
+            Some(region) => (left + region.x, top + region.y),
+            None => (left, top),
+        }
+    }
+}
+
+/// Encoder settings for a `width` x `height` video with the options' overrides
+pub(crate) fn video_settings(
+    width: u32,
+    height: u32,
+    video: &VideoEncoderSettings,
+) -> VideoSettingsBuilder {
+    let mut settings = VideoSettingsBuilder::new(width, height);
+    if let Some(frame_rate) = video.frame_rate {
+        settings = settings.frame_rate(frame_rate.clamp(1, 120));
+    }
+    if let Some(bitrate) = video.bitrate {
+        settings = settings.bitrate(bitrate);
+    }
+    settings
+}
+
+/// Builds the processors the options ask for. Those placed by screen position (redaction,
+/// window masking, the cursor and auto-zoom) are left out unless `screen_space` is set.
+pub(crate) fn build_pipeline(
+    options: &RecordingOptions,
+    screen_space: bool,
+    app: &AppHandle,
+) -> FramePipeline {
+    let mut pipeline = FramePipeline::new();
+    if screen_space && !options.redactions.is_empty() {
+        pipeline.push(Redactor::new(options.redactions.clone()));
+    }
+    if screen_space && options.exclude_own_windows && !masked_windows().is_empty() {
+        pipeline.push(OwnWindowMask);
+    }
+    // The cursor overlay and auto-zoom both follow the cursor, so they share one sampling thread
+    let tracker = (screen_space
+        && (options.cursor.needs_compositing() || options.auto_zoom.is_some()))
+    .then(|| Arc::new(CursorTracker::start()));
+    if let (true, Some(tracker)) = (options.cursor.needs_compositing(), &tracker) {
+        pipeline.push(CursorOverlay::new(
+            options.cursor.clone(),
+            Arc::clone(tracker),
+        ));
+    }
+    // Anything drawn after the zoom stays fixed on the output instead of following the camera
+    if let (Some(auto_zoom), Some(tracker)) = (&options.auto_zoom, &tracker) {
+        pipeline.push(AutoZoom::new(auto_zoom.clone(), Arc::clone(tracker)));
+    }
+    if let Some(webcam) = &options.webcam {
+        pipeline.push(WebcamOverlay::new(webcam.clone(), app.clone()));
+    }
+    if let Some(keystrokes) = &options.keystrokes {
+        pipeline.push(KeystrokeOverlay::new(keystrokes.clone()));
+    }
+    pipeline
+}
+
+/// Passes queued audio to the encoder. Audio captured before the first video frame or
+/// during a pause is dropped so both streams stay together.
+pub(crate) fn send_queued_audio(
+    encoder: &mut VideoEncoder,
+    audio: &AudioCapture,
+    first_timestamp: i64,
+    pauses: &PauseTimeline,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for chunk in audio.drain() {
+        if chunk.timestamp < first_timestamp || chunk.data.is_empty() {
+            continue;
+        }
+        let Some(timestamp) = pauses.map(chunk.timestamp) else {
+            continue;
+        };
+        encoder.send_audio_buffer(&chunk.data, timestamp)?;
+    }
+    Ok(())
+}
+
+/// Copies the captured frame, or just the region of it if one is set
+pub(crate) fn copy_frame(
+    frame: &mut Frame,
+    region: Option<&Region>,
+) -> Result<RgbaFrame, Box<dyn std::error::Error + Send + Sync>> {
+    match region {
+        Some(region) => crop_region(frame, region),
+        None => {
+            let mut buffer = frame.buffer()?;
+            let (width, height) = (buffer.width(), buffer.height());
+            Ok(RgbaFrame::new(
+                width,
+                height,
+                buffer.as_nopadding_buffer()?.to_vec(),
+            ))
+        }
+    }
+}
+
+/// Copies the region out of the captured frame
+fn crop_region(
+    frame: &mut Frame,
+    region: &Region,
+) -> Result<RgbaFrame, Box<dyn std::error::Error + Send + Sync>> {
+    // The region coordinates are relative to the captured monitor, which matches the frame
+    let start_x = region.x as u32;
+    let start_y = region.y as u32;
+    let end_x = (region.x as u32) + region.width;
+    let end_y = (region.y as u32) + region.height;
+
+    let mut cropped_frame = frame.buffer_crop(start_x, start_y, end_x, end_y)?;
+
+    let raw_cropped_buffer = cropped_frame.as_nopadding_buffer()?;
+
+    let expected_size = region.width as usize * region.height as usize * 4;
+    if raw_cropped_buffer.len() != expected_size {
+        println!(
+            "WARNING: Buffer size mismatch! Expected: {}, Got: {}",
+            expected_size,
+            raw_cropped_buffer.len()
+        );
+    }
+
+    Ok(RgbaFrame::new(
+        region.width,
+        region.height,
+        raw_cropped_buffer.to_vec(),
+    ))
 }
 
 pub fn get_available_windows() -> Result<Vec<Window>, Box<dyn std::error::Error>> {
     let windows = Window::enumerate()?;
+    let desktops = VirtualDesktops::open();
 
     // Filter out minimized windows and windows without titles
     let filtered_windows: Vec<Window> = windows
@@ -165,6 +598,23 @@ pub fn get_available_windows() -> Result<Vec<Window>, Box<dyn std::error::Error>
                 return false;
             }
 
+            // Never offer framelet's own windows as capture sources
+            if window_process_id(raw_hwnd as isize) == std::process::id() {
+                return false;
+            }
+
+            // Cloaked windows pass the visibility check but show nothing, e.g. suspended UWP
+            // apps. Those on another virtual desktop are still real windows.
+            if is_cloaked(raw_hwnd as isize) && desktops.is_on_current(raw_hwnd as isize) {
+                return false;
+            }
+
+            // Zero-sized helper windows
+            match window_bounds(raw_hwnd as isize) {
+                Some(rect) if rect.right > rect.left && rect.bottom > rect.top => {}
+                _ => return false,
+            }
+
             // Check if window has a title and is not minimized
             if let Ok(title) = window.title() {
                 if title.trim().is_empty() {
@@ -178,31 +628,59 @@ pub fn get_available_windows() -> Result<Vec<Window>, Box<dyn std::error::Error>
     Ok(filtered_windows)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn start_recording(
+    app: AppHandle,
     capture_item: WindowsCaptureGraphicsCaptureItem,
     output_path: String,
     stop_signal: Arc<AtomicBool>,
+    pause_signal: Arc<AtomicBool>,
     region: Option<Region>,
+    target: CaptureTarget,
+    options: RecordingOptions,
+    audio: Vec<AudioInput>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let width = capture_item.Size()?.Width as u32;
     let height = capture_item.Size()?.Height as u32;
 
+    let cursor_capture = options.cursor.capture_settings();
+
     let config = RecordingConfig {
         width,
         height,
         output_path,
-        stop_signal,
+        pause_signal,
         region,
+        target,
+        options,
+        audio,
+        app,
     };
 
     let settings = Settings::new(
         capture_item,
-        CursorCaptureSettings::Default,
+        cursor_capture,
         DrawBorderSettings::WithoutBorder,
         ColorFormat::Rgba8,
         config,
     );
 
-    ScreenRecorder::start(settings)?;
+    let control = ScreenRecorder::start_free_threaded(settings)?;
+    while !control.is_finished() {
+        thread::sleep(TICK_INTERVAL);
+        // Checked here rather than per frame, as a minimized or idle source sends none
+        if stop_signal.load(Ordering::Relaxed) {
+            println!("\nStopping recording...");
+            let finished = control.callback().lock().finish();
+            control.stop().map_err(|e| e.to_string())?;
+            return finished.map_err(|e| e.to_string().into());
+        }
+        let ticked = control.callback().lock().tick();
+        if let Err(e) = ticked {
+            let _ = control.stop();
+            return Err(e.to_string().into());
+        }
+    }
+    control.wait().map_err(|e| e.to_string())?;
     Ok(())
 }
@@ -1,23 +1,49 @@
 use std::{
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     thread,
 };
 
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::types::CaptureSourceType;
+
+/// Payload of `recording-source-lost`
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceLost {
+    pub source_type: CaptureSourceType,
+    pub handle: isize,
+}
+
 /// Manages the lifecycle of a recording session
 pub struct RecordingSession {
     stop_signal: Arc<AtomicBool>,
+    pause_signal: Arc<AtomicBool>,
     recording_thread: Option<thread::JoinHandle<()>>,
+    /// The monitors and windows being recorded; several for a scene, none for audio-only
+    /// recordings
+    sources: Vec<(CaptureSourceType, isize)>,
+    /// Sources already reported lost
+    lost: Mutex<Vec<(CaptureSourceType, isize)>>,
 }
 
 impl RecordingSession {
     /// Creates a new recording session
-    pub fn new(stop_signal: Arc<AtomicBool>, recording_thread: thread::JoinHandle<()>) -> Self {
+    pub fn new(
+        stop_signal: Arc<AtomicBool>,
+        pause_signal: Arc<AtomicBool>,
+        recording_thread: thread::JoinHandle<()>,
+        sources: Vec<(CaptureSourceType, isize)>,
+    ) -> Self {
         Self {
             stop_signal,
+            pause_signal,
             recording_thread: Some(recording_thread),
+            sources,
+            lost: Mutex::new(Vec::new()),
         }
     }
 
@@ -34,8 +60,61 @@ impl RecordingSession {
         Ok(())
     }
 
+    /// Pauses the recording; nothing is written until it resumes and the gap is cut out
+    pub fn pause(&self) {
+        self.pause_signal.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.pause_signal.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_signal.load(Ordering::Relaxed)
+    }
+
+    /// Whether this is the session stopped by `stop_signal`
+    pub fn is_stopped_by(&self, stop_signal: &Arc<AtomicBool>) -> bool {
+        Arc::ptr_eq(&self.stop_signal, stop_signal)
+    }
+
     /// Checks if the session is active
     pub fn is_active(&self) -> bool {
         self.recording_thread.is_some()
     }
+
+    pub fn sources(&self) -> &[(CaptureSourceType, isize)] {
+        &self.sources
+    }
+
+    /// Called when a recorded monitor was disconnected or window closed.
+    /// Emits `recording-source-lost` the first time for each source.
+    pub fn source_lost(&self, app: &AppHandle, (source_type, handle): (CaptureSourceType, isize)) {
+        if !self.sources.contains(&(source_type, handle)) {
+            return;
+        }
+        {
+            let mut lost = self.lost.lock().unwrap();
+            if lost.contains(&(source_type, handle)) {
+                return;
+            }
+            lost.push((source_type, handle));
+        }
+
+        println!("Recorded {:?} {} is gone", source_type, handle);
+        if let Err(e) = app.emit(
+            "recording-source-lost",
+            SourceLost {
+                source_type,
+                handle,
+            },
+        ) {
+            eprintln!("Failed to emit recording-source-lost event: {}", e);
+        }
+    }
+
+    /// Whether any recorded source has disappeared
+    pub fn is_source_lost(&self) -> bool {
+        !self.lost.lock().unwrap().is_empty()
+    }
 }
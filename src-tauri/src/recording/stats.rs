@@ -0,0 +1,48 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::audio::DriftStats;
+
+/// Summary of a finished recording, logged and emitted as `recording-stats`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecordingStats {
+    pub output_path: String,
+    pub video_frames: u64,
+    /// Length of the output, not counting pauses
+    pub duration_secs: f64,
+    pub paused_secs: f64,
+    pub audio_drift: Vec<DriftStats>,
+    /// Files holding each audio input on its own, when they were kept apart
+    pub audio_tracks: Vec<String>,
+}
+
+impl RecordingStats {
+    /// Logs the stats and sends them to the frontend
+    pub fn report(&self, app: &AppHandle) {
+        self.log();
+        if let Err(e) = app.emit("recording-stats", self) {
+            eprintln!("Failed to emit recording-stats event: {}", e);
+        }
+    }
+
+    pub fn log(&self) {
+        println!(
+            "Recorded {} frames over {:.1}s ({:.1}s paused) to {}",
+            self.video_frames, self.duration_secs, self.paused_secs, self.output_path
+        );
+        for path in &self.audio_tracks {
+            println!("Audio track saved to {}", path);
+        }
+        for drift in &self.audio_drift {
+            println!(
+                "Audio track {}: skew {:.1} ppm, max drift {:.2}ms, final drift {:.2}ms, {} frames inserted, {} dropped",
+                drift.track,
+                drift.skew_ppm,
+                drift.max_drift_ms,
+                drift.final_drift_ms,
+                drift.inserted_frames,
+                drift.dropped_frames
+            );
+        }
+    }
+}
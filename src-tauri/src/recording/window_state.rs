@@ -0,0 +1,58 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use windows::Win32::{Foundation::HWND, UI::WindowsAndMessaging::IsIconic};
+
+use crate::audio;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Watches whether the recorded window is minimized. A minimized window delivers no frames,
+/// so the recorder can't notice on its own until the window comes back.
+pub struct MinimizeWatcher {
+    /// Capture-clock time of each change and whether the window became minimized
+    changes: Arc<Mutex<Vec<(i64, bool)>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MinimizeWatcher {
+    pub fn start(hwnd: isize) -> Self {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        thread::spawn({
+            let changes = Arc::clone(&changes);
+            let stop = Arc::clone(&stop);
+            move || {
+                let mut minimized = false;
+                while !stop.load(Ordering::Relaxed) {
+                    let now_minimized = unsafe { IsIconic(HWND(hwnd as *mut _)) }.as_bool();
+                    if now_minimized != minimized {
+                        minimized = now_minimized;
+                        changes.lock().unwrap().push((audio::now(), minimized));
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        });
+
+        Self { changes, stop }
+    }
+
+    /// Takes the changes seen since the last call, oldest first
+    pub fn drain(&self) -> Vec<(i64, bool)> {
+        std::mem::take(&mut *self.changes.lock().unwrap())
+    }
+}
+
+impl Drop for MinimizeWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -1,13 +1,23 @@
+use crate::pipeline::{
+    AutoZoomSettings, CursorSettings, KeystrokeOverlaySettings, RedactionRule,
+    WebcamOverlaySettings,
+};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CaptureSourceType {
     Monitor,
     Window,
+    /// Several monitors composited into one picture
+    Desktop,
+    /// Any sources arranged by a scene layout
+    Scene,
+    /// No video at all; only the audio inputs are recorded
+    Audio,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CaptureSource {
     pub name: String,
     pub width: u32,
@@ -25,3 +35,89 @@ pub struct Region {
     pub width: u32,
     pub height: u32,
 }
+
+/// Optional per-recording settings passed alongside the capture source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingOptions {
+    pub redactions: Vec<RedactionRule>,
+    /// Keep framelet's own windows out of monitor captures
+    pub exclude_own_windows: bool,
+    pub cursor: CursorSettings,
+    /// Shows recent shortcuts as badges when set
+    pub keystrokes: Option<KeystrokeOverlaySettings>,
+    /// Records a cursor-following virtual camera instead of the whole capture when set
+    pub auto_zoom: Option<AutoZoomSettings>,
+    /// Composites a webcam picture-in-picture when set
+    pub webcam: Option<WebcamOverlaySettings>,
+    /// What to do when the recorded window closes, resizes or is minimized
+    pub source_changes: SourceChangeSettings,
+    pub video: VideoEncoderSettings,
+    /// With several audio inputs, also saves each one to its own file beside the recording
+    pub separate_audio_tracks: bool,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        Self {
+            redactions: Vec::new(),
+            exclude_own_windows: true,
+            cursor: CursorSettings::default(),
+            keystrokes: None,
+            auto_zoom: None,
+            webcam: None,
+            source_changes: SourceChangeSettings::default(),
+            video: VideoEncoderSettings::default(),
+            separate_audio_tracks: true,
+        }
+    }
+}
+
+/// Overrides for the video encoder; unset values keep its defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoEncoderSettings {
+    /// Frames per second. Captures delivering more are thinned out.
+    pub frame_rate: Option<u32>,
+    /// Bits per second
+    pub bitrate: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceChangeSettings {
+    pub on_close: CloseBehavior,
+    pub on_resize: ResizeBehavior,
+    pub on_minimize: MinimizeBehavior,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseBehavior {
+    /// Finishes the file, keeping everything recorded up to the close
+    #[default]
+    Finalize,
+    /// Finishes and deletes the file
+    Discard,
+}
+
+/// How frames are fitted into the output once the source no longer matches its size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeBehavior {
+    /// Keeps the aspect ratio and fills the rest with black
+    #[default]
+    Letterbox,
+    /// Stretches to the output size
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MinimizeBehavior {
+    /// Shows a black frame until the window is restored
+    #[default]
+    Placeholder,
+    /// Leaves the minimized time out of the recording, like a manual pause
+    Pause,
+}
@@ -0,0 +1,113 @@
+use std::{ffi::c_void, ptr};
+
+use serde::Serialize;
+use thiserror::Error;
+use windows::{
+    core::{GUID, PWSTR},
+    Win32::{
+        Media::MediaFoundation::{
+            IMFActivate, IMFAttributes, MFCreateAttributes, MFEnumDeviceSources,
+            MF_DEVSOURCE_ATTRIBUTE_FRIENDLY_NAME, MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE,
+            MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_GUID,
+            MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_SYMBOLIC_LINK,
+        },
+        System::Com::CoTaskMemFree,
+    },
+};
+
+use super::media_foundation::MediaFoundation;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebcamDevice {
+    /// Symbolic link of the device, stable across enumerations
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Error)]
+pub enum WebcamError {
+    #[error("failed to start Media Foundation: {0}")]
+    MediaFoundation(#[source] windows::core::Error),
+
+    #[error("failed to list webcam devices: {0}")]
+    ListDevices(#[source] windows::core::Error),
+
+    #[error("failed to open the webcam source: {0}")]
+    Open(#[source] windows::core::Error),
+
+    #[error("failed to read from the webcam: {0}")]
+    Read(#[source] windows::core::Error),
+}
+
+impl Serialize for WebcamError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Lists the video capture devices currently attached
+pub fn list_devices() -> Result<Vec<WebcamDevice>, WebcamError> {
+    let _media_foundation = MediaFoundation::startup().map_err(WebcamError::MediaFoundation)?;
+    let attributes = video_capture_attributes(1).map_err(WebcamError::ListDevices)?;
+
+    let mut activates: *mut Option<IMFActivate> = ptr::null_mut();
+    let mut count = 0u32;
+    unsafe { MFEnumDeviceSources(&attributes, &mut activates, &mut count) }
+        .map_err(WebcamError::ListDevices)?;
+
+    if activates.is_null() {
+        return Ok(Vec::new());
+    }
+
+    let mut devices = Vec::new();
+    let list = unsafe { std::slice::from_raw_parts_mut(activates, count as usize) };
+    for activate in list.iter_mut() {
+        // Taking each entry releases it once we are done with it
+        let Some(activate) = activate.take() else {
+            continue;
+        };
+
+        let id = allocated_string(
+            &activate,
+            &MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_SYMBOLIC_LINK,
+        );
+        let name = allocated_string(&activate, &MF_DEVSOURCE_ATTRIBUTE_FRIENDLY_NAME);
+        if let (Some(id), Some(name)) = (id, name) {
+            devices.push(WebcamDevice { id, name });
+        }
+    }
+    unsafe { CoTaskMemFree(Some(activates as *const c_void)) };
+
+    Ok(devices)
+}
+
+/// Attributes selecting video capture devices, with room for `capacity` entries
+pub(super) fn video_capture_attributes(capacity: u32) -> windows::core::Result<IMFAttributes> {
+    let mut attributes = None;
+    unsafe { MFCreateAttributes(&mut attributes, capacity)? };
+    let attributes = attributes.ok_or_else(windows::core::Error::empty)?;
+
+    unsafe {
+        attributes.SetGUID(
+            &MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE,
+            &MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_GUID,
+        )?
+    };
+    Ok(attributes)
+}
+
+fn allocated_string(activate: &IMFActivate, key: &GUID) -> Option<String> {
+    let mut value = PWSTR::null();
+    let mut len = 0u32;
+    unsafe {
+        activate
+            .GetAllocatedString(key, &mut value, &mut len)
+            .ok()?;
+        let string = value.to_string().ok();
+        CoTaskMemFree(Some(value.0 as *const c_void));
+        string
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use super::{pattern, reader};
+use crate::pipeline::RgbaFrame;
+
+/// Where webcam frames come from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebcamSource {
+    /// A camera from `get_webcam_devices`
+    Device { id: String },
+    /// A video file played on a loop, for testing without a camera
+    File { path: String },
+    /// A synthetic moving test pattern
+    #[default]
+    Pattern,
+}
+
+/// Payload of `webcam-failed`, emitted when a camera or file can't be opened or read.
+/// The recording goes on without the webcam picture.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebcamFailed {
+    pub source: WebcamSource,
+    pub error: String,
+}
+
+/// Reads webcam frames on a background thread, keeping only the most recent one
+pub struct WebcamFeed {
+    latest: Arc<Mutex<Option<Arc<RgbaFrame>>>>,
+    stop_signal: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl WebcamFeed {
+    /// Starts reading from `source`, reporting a failure to read it as `webcam-failed`
+    pub fn start(source: WebcamSource, app: AppHandle) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let stop_signal = Arc::new(AtomicBool::new(false));
+
+        let thread = thread::spawn({
+            let latest = Arc::clone(&latest);
+            let stop_signal = Arc::clone(&stop_signal);
+            move || {
+                let result = match &source {
+                    WebcamSource::Device { id } => reader::run_device(id, &latest, &stop_signal),
+                    WebcamSource::File { path } => reader::run_file(path, &latest, &stop_signal),
+                    WebcamSource::Pattern => {
+                        pattern::run(&latest, &stop_signal);
+                        Ok(())
+                    }
+                };
+                if let Err(e) = result {
+                    eprintln!("Webcam stopped: {}", e);
+                    let failed = WebcamFailed {
+                        source,
+                        error: e.to_string(),
+                    };
+                    if let Err(e) = app.emit("webcam-failed", &failed) {
+                        eprintln!("Failed to emit webcam-failed event: {}", e);
+                    }
+                }
+            }
+        });
+
+        Self {
+            latest,
+            stop_signal,
+            thread: Some(thread),
+        }
+    }
+
+    /// Most recent frame, or `None` until the source delivers its first one
+    pub fn latest(&self) -> Option<Arc<RgbaFrame>> {
+        self.latest.lock().unwrap().clone()
+    }
+}
+
+impl Drop for WebcamFeed {
+    fn drop(&mut self) {
+        self.stop_signal.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use windows::Win32::Media::MediaFoundation::{MFShutdown, MFStartup, MFSTARTUP_FULL, MF_VERSION};
+
+use crate::com::ComApartment;
+
+/// Keeps COM and Media Foundation initialized on the current thread while alive
+pub struct MediaFoundation {
+    /// Dropped after `drop` has shut Media Foundation down
+    _com: ComApartment,
+}
+
+impl MediaFoundation {
+    pub fn startup() -> windows::core::Result<Self> {
+        let com = ComApartment::enter();
+        unsafe { MFStartup(MF_VERSION, MFSTARTUP_FULL) }?;
+        Ok(Self { _com: com })
+    }
+}
+
+impl Drop for MediaFoundation {
+    fn drop(&mut self) {
+        let _ = unsafe { MFShutdown() };
+    }
+}
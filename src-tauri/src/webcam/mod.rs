@@ -0,0 +1,9 @@
+mod devices;
+mod feed;
+mod media_foundation;
+mod pattern;
+mod reader;
+
+pub use devices::{list_devices, WebcamDevice, WebcamError};
+pub use feed::{WebcamFailed, WebcamFeed, WebcamSource};
+pub use reader::VideoFileReader;
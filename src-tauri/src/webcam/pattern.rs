@@ -0,0 +1,57 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::pipeline::RgbaFrame;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 480;
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+const SWEEP_PERIOD: f32 = 2.0;
+const SWEEP_SIZE: u32 = 80;
+
+/// 75% color bars: white, yellow, cyan, green, magenta, red, blue
+const BARS: [[u8; 4]; 7] = [
+    [191, 191, 191, 255],
+    [191, 191, 0, 255],
+    [0, 191, 191, 255],
+    [0, 191, 0, 255],
+    [191, 0, 191, 255],
+    [191, 0, 0, 255],
+    [0, 0, 191, 255],
+];
+
+/// Produces a synthetic moving test pattern until stopped
+pub(super) fn run(latest: &Mutex<Option<Arc<RgbaFrame>>>, stop: &AtomicBool) {
+    let started = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        *latest.lock().unwrap() = Some(Arc::new(render(started.elapsed())));
+        thread::sleep(FRAME_INTERVAL);
+    }
+}
+
+fn render(elapsed: Duration) -> RgbaFrame {
+    let mut frame = RgbaFrame::blank(WIDTH, HEIGHT);
+
+    let bar_width = WIDTH.div_ceil(BARS.len() as u32);
+    for (i, color) in BARS.iter().enumerate() {
+        if let Some(rect) = frame.clip((i as u32 * bar_width) as i32, 0, bar_width, HEIGHT) {
+            frame.fill_rect(rect, *color);
+        }
+    }
+
+    // A sweeping block makes frozen or dropped frames easy to spot
+    let progress = (elapsed.as_secs_f32() % SWEEP_PERIOD) / SWEEP_PERIOD;
+    let x = (progress * (WIDTH - SWEEP_SIZE) as f32) as i32;
+    let y = ((HEIGHT - SWEEP_SIZE) / 2) as i32;
+    if let Some(rect) = frame.clip(x, y, SWEEP_SIZE, SWEEP_SIZE) {
+        frame.fill_rect(rect, [16, 16, 16, 255]);
+    }
+
+    frame
+}
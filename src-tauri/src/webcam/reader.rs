@@ -0,0 +1,283 @@
+use std::{
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use windows::{
+    core::{Interface, HSTRING, PROPVARIANT},
+    Win32::Media::MediaFoundation::{
+        IMF2DBuffer, IMFAttributes, IMFSample, IMFSourceReader, MFCreateAttributes,
+        MFCreateDeviceSource, MFCreateMediaType, MFCreateSourceReaderFromMediaSource,
+        MFCreateSourceReaderFromURL, MFMediaType_Video, MFVideoFormat_RGB32,
+        MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_SYMBOLIC_LINK, MF_MT_DEFAULT_STRIDE,
+        MF_MT_FRAME_SIZE, MF_MT_MAJOR_TYPE, MF_MT_SUBTYPE,
+        MF_SOURCE_READERF_CURRENTMEDIATYPECHANGED, MF_SOURCE_READERF_ENDOFSTREAM,
+        MF_SOURCE_READER_ALL_STREAMS, MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING,
+        MF_SOURCE_READER_FIRST_VIDEO_STREAM,
+    },
+};
+
+use super::{devices::video_capture_attributes, media_foundation::MediaFoundation, WebcamError};
+use crate::pipeline::RgbaFrame;
+
+const VIDEO_STREAM: u32 = MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32;
+
+enum ReadResult {
+    /// A decoded frame and its presentation time in 100ns units
+    Frame(RgbaFrame, i64),
+    /// The reader produced no sample this time, e.g. a stream gap
+    Skipped,
+    EndOfStream,
+}
+
+/// A Media Foundation source reader converting video to RGBA
+struct SourceReader {
+    reader: IMFSourceReader,
+    width: u32,
+    height: u32,
+    default_stride: i32,
+}
+
+impl SourceReader {
+    fn open_device(id: &str) -> windows::core::Result<Self> {
+        let attributes = video_capture_attributes(2)?;
+        unsafe {
+            attributes.SetString(
+                &MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_SYMBOLIC_LINK,
+                &HSTRING::from(id),
+            )?;
+            let source = MFCreateDeviceSource(&attributes)?;
+            Self::configure(MFCreateSourceReaderFromMediaSource(
+                &source,
+                &reader_attributes()?,
+            )?)
+        }
+    }
+
+    fn open_file(path: &str) -> windows::core::Result<Self> {
+        let reader =
+            unsafe { MFCreateSourceReaderFromURL(&HSTRING::from(path), &reader_attributes()?)? };
+        Self::configure(reader)
+    }
+
+    /// Selects only the first video stream and asks the reader to convert it to RGB32
+    fn configure(reader: IMFSourceReader) -> windows::core::Result<Self> {
+        unsafe {
+            reader.SetStreamSelection(MF_SOURCE_READER_ALL_STREAMS.0 as u32, false.into())?;
+            reader.SetStreamSelection(VIDEO_STREAM, true.into())?;
+
+            let media_type = MFCreateMediaType()?;
+            media_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
+            media_type.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_RGB32)?;
+            reader.SetCurrentMediaType(VIDEO_STREAM, None, &media_type)?;
+        }
+
+        let mut reader = Self {
+            reader,
+            width: 0,
+            height: 0,
+            default_stride: 0,
+        };
+        reader.refresh_format()?;
+        Ok(reader)
+    }
+
+    fn refresh_format(&mut self) -> windows::core::Result<()> {
+        let current = unsafe { self.reader.GetCurrentMediaType(VIDEO_STREAM)? };
+        let size = unsafe { current.GetUINT64(&MF_MT_FRAME_SIZE)? };
+        self.width = (size >> 32) as u32;
+        self.height = size as u32;
+
+        // RGB32 is bottom-up unless the media type says otherwise
+        self.default_stride = unsafe { current.GetUINT32(&MF_MT_DEFAULT_STRIDE) }
+            .map(|stride| stride as i32)
+            .unwrap_or(-(self.width as i32 * 4));
+        Ok(())
+    }
+
+    fn read_frame(&mut self) -> windows::core::Result<ReadResult> {
+        let mut flags = 0u32;
+        let mut timestamp = 0i64;
+        let mut sample: Option<IMFSample> = None;
+        unsafe {
+            self.reader.ReadSample(
+                VIDEO_STREAM,
+                0,
+                None,
+                Some(&mut flags as *mut _),
+                Some(&mut timestamp as *mut _),
+                Some(&mut sample as *mut _),
+            )?;
+        }
+
+        if flags & MF_SOURCE_READERF_ENDOFSTREAM.0 as u32 != 0 {
+            return Ok(ReadResult::EndOfStream);
+        }
+        if flags & MF_SOURCE_READERF_CURRENTMEDIATYPECHANGED.0 as u32 != 0 {
+            self.refresh_format()?;
+        }
+
+        match sample {
+            Some(sample) => Ok(ReadResult::Frame(self.convert(&sample)?, timestamp)),
+            None => Ok(ReadResult::Skipped),
+        }
+    }
+
+    /// Copies a BGRX sample into a top-down RGBA frame
+    fn convert(&self, sample: &IMFSample) -> windows::core::Result<RgbaFrame> {
+        let buffer = unsafe { sample.ConvertToContiguousBuffer()? };
+        let (width, height) = (self.width, self.height);
+        let row_bytes = width as usize * 4;
+        let mut data = vec![0u8; row_bytes * height as usize];
+
+        let mut copy_rows = |scanline0: *const u8, pitch: i32| {
+            for (y, row) in data.chunks_exact_mut(row_bytes).enumerate() {
+                let src = unsafe {
+                    std::slice::from_raw_parts(
+                        scanline0.offset(y as isize * pitch as isize),
+                        row_bytes,
+                    )
+                };
+                for (dst, src) in row.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+                    dst.copy_from_slice(&[src[2], src[1], src[0], 255]);
+                }
+            }
+        };
+
+        // 2D buffers report the real pitch and first scanline, whichever way up the image is
+        if let Ok(buffer_2d) = buffer.cast::<IMF2DBuffer>() {
+            let mut scanline0 = ptr::null_mut();
+            let mut pitch = 0i32;
+            unsafe { buffer_2d.Lock2D(&mut scanline0, &mut pitch)? };
+            copy_rows(scanline0, pitch);
+            unsafe { buffer_2d.Unlock2D()? };
+        } else {
+            let mut base = ptr::null_mut();
+            unsafe { buffer.Lock(&mut base, None, None)? };
+            let stride = self.default_stride;
+            let scanline0 = if stride < 0 {
+                unsafe { base.offset((height as isize - 1) * -(stride as isize)) }
+            } else {
+                base
+            };
+            copy_rows(scanline0, stride);
+            unsafe { buffer.Unlock()? };
+        }
+
+        Ok(RgbaFrame::new(width, height, data))
+    }
+
+    fn rewind(&self) -> windows::core::Result<()> {
+        unsafe {
+            self.reader
+                .SetCurrentPosition(&windows::core::GUID::zeroed(), &PROPVARIANT::from(0i64))
+        }
+    }
+}
+
+/// Decodes a video file from start to end as fast as its frames are asked for, each with its
+/// presentation time in 100ns units
+pub struct VideoFileReader {
+    reader: SourceReader,
+    /// Declared after the reader so it is shut down last
+    _media_foundation: MediaFoundation,
+    finished: bool,
+}
+
+impl VideoFileReader {
+    pub fn open(path: &str) -> windows::core::Result<Self> {
+        let media_foundation = MediaFoundation::startup()?;
+        Ok(Self {
+            reader: SourceReader::open_file(path)?,
+            _media_foundation: media_foundation,
+            finished: false,
+        })
+    }
+}
+
+impl Iterator for VideoFileReader {
+    type Item = windows::core::Result<(RgbaFrame, i64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            match self.reader.read_frame() {
+                Ok(ReadResult::Frame(frame, timestamp)) => return Some(Ok((frame, timestamp))),
+                Ok(ReadResult::Skipped) => {}
+                Ok(ReadResult::EndOfStream) => self.finished = true,
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn reader_attributes() -> windows::core::Result<IMFAttributes> {
+    let mut attributes = None;
+    unsafe { MFCreateAttributes(&mut attributes, 1)? };
+    let attributes = attributes.ok_or_else(windows::core::Error::empty)?;
+    // Lets the reader convert from the camera's native format (usually NV12 or MJPG) to RGB32
+    unsafe { attributes.SetUINT32(&MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING, 1)? };
+    Ok(attributes)
+}
+
+/// Reads from a camera until stopped
+pub(super) fn run_device(
+    id: &str,
+    latest: &Mutex<Option<Arc<RgbaFrame>>>,
+    stop: &AtomicBool,
+) -> Result<(), WebcamError> {
+    run(|| SourceReader::open_device(id), false, latest, stop)
+}
+
+/// Plays a video file in real time on a loop until stopped
+pub(super) fn run_file(
+    path: &str,
+    latest: &Mutex<Option<Arc<RgbaFrame>>>,
+    stop: &AtomicBool,
+) -> Result<(), WebcamError> {
+    run(|| SourceReader::open_file(path), true, latest, stop)
+}
+
+/// Reads until stopped or the source ends; fails if the source can't be opened or read
+fn run(
+    open: impl FnOnce() -> windows::core::Result<SourceReader>,
+    looping: bool,
+    latest: &Mutex<Option<Arc<RgbaFrame>>>,
+    stop: &AtomicBool,
+) -> Result<(), WebcamError> {
+    // Declared first so Media Foundation outlives the reader
+    let _media_foundation = MediaFoundation::startup().map_err(WebcamError::MediaFoundation)?;
+    let mut reader = open().map_err(WebcamError::Open)?;
+
+    let mut started = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        match reader.read_frame() {
+            Ok(ReadResult::Frame(frame, timestamp)) => {
+                // Files decode faster than real time, so hold each frame until it is due
+                if looping {
+                    let due = Duration::from_nanos(timestamp.max(0) as u64 * 100);
+                    if let Some(wait) = due.checked_sub(started.elapsed()) {
+                        thread::sleep(wait);
+                    }
+                }
+                *latest.lock().unwrap() = Some(Arc::new(frame));
+            }
+            Ok(ReadResult::Skipped) => {}
+            Ok(ReadResult::EndOfStream) if looping => {
+                reader.rewind().map_err(WebcamError::Read)?;
+                started = Instant::now();
+            }
+            Ok(ReadResult::EndOfStream) => break,
+            Err(e) => return Err(WebcamError::Read(e)),
+        }
+    }
+    Ok(())
+}
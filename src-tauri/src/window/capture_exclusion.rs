@@ -0,0 +1,71 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use lazy_static::lazy_static;
+use tauri::Manager;
+use windows::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{SetWindowDisplayAffinity, WDA_EXCLUDEFROMCAPTURE, WDA_NONE},
+};
+
+/// Set while a recording wants framelet's own windows kept out of the capture
+static EXCLUSION_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// Own windows the OS refused to exclude, which the frame pipeline masks instead
+    static ref MASKED_WINDOWS: Mutex<Vec<isize>> = Mutex::new(Vec::new());
+}
+
+/// Keeps every framelet window out of captures until `restore_app_windows` is called
+pub fn exclude_app_windows(app: &tauri::AppHandle) {
+    EXCLUSION_ACTIVE.store(true, Ordering::Relaxed);
+
+    for window in app.webview_windows().values() {
+        exclude_window(window);
+    }
+}
+
+/// Excludes a single window, falling back to masking if the OS doesn't support it
+/// (`WDA_EXCLUDEFROMCAPTURE` requires Windows 10 2004 or newer)
+pub fn exclude_window(window: &tauri::WebviewWindow) {
+    let Ok(hwnd) = window.hwnd() else {
+        return;
+    };
+
+    let result = unsafe { SetWindowDisplayAffinity(HWND(hwnd.0), WDA_EXCLUDEFROMCAPTURE) };
+    if let Err(e) = result {
+        println!(
+            "Capture exclusion unavailable for '{}', masking instead: {}",
+            window.label(),
+            e
+        );
+        MASKED_WINDOWS.lock().unwrap().push(hwnd.0 as isize);
+    }
+}
+
+/// Makes framelet windows capturable again
+pub fn restore_app_windows(app: &tauri::AppHandle) {
+    if !EXCLUSION_ACTIVE.swap(false, Ordering::Relaxed) {
+        return;
+    }
+
+    for window in app.webview_windows().values() {
+        if let Ok(hwnd) = window.hwnd() {
+            let _ = unsafe { SetWindowDisplayAffinity(HWND(hwnd.0), WDA_NONE) };
+        }
+    }
+
+    MASKED_WINDOWS.lock().unwrap().clear();
+}
+
+/// Whether windows created now should be excluded from capture
+pub fn is_exclusion_active() -> bool {
+    EXCLUSION_ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Own windows that have to be masked in the frame pipeline
+pub fn masked_windows() -> Vec<isize> {
+    MASKED_WINDOWS.lock().unwrap().clone()
+}
@@ -1,3 +1,9 @@
+mod capture_exclusion;
 mod region_selector;
 
-pub use region_selector::{close_region_selector, open_region_selector, region_selected};
+pub use capture_exclusion::{
+    exclude_app_windows, exclude_window, is_exclusion_active, masked_windows, restore_app_windows,
+};
+pub use region_selector::{
+    close_region_selector, get_snap_windows, open_region_selector, region_selected, SnapWindow,
+};
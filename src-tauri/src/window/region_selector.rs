@@ -1,7 +1,76 @@
-use crate::types::Region;
-use std::os::raw::c_void;
+use super::capture_exclusion::{exclude_window, is_exclusion_active};
+use crate::{
+    capture::{is_cloaked, window_bounds},
+    recording::screen_recorder,
+    types::Region,
+};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::{os::raw::c_void, sync::Mutex};
 use tauri::{Emitter, Manager};
-use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO};
+use windows::Win32::{
+    Foundation::RECT,
+    Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO},
+};
+
+/// A window the region selector can snap to, in physical pixels relative to the monitor
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapWindow {
+    pub hwnd: isize,
+    pub title: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+lazy_static! {
+    /// Windows on the monitor the selector was opened for, topmost first
+    static ref SNAP_WINDOWS: Mutex<Vec<SnapWindow>> = Mutex::new(Vec::new());
+}
+
+/// Visible windows overlapping `monitor`, clipped to it, topmost first
+fn snap_windows(monitor: &RECT) -> Vec<SnapWindow> {
+    let windows = match screen_recorder::get_available_windows() {
+        Ok(windows) => windows,
+        Err(e) => {
+            eprintln!("Failed to list windows for snapping: {}", e);
+            return Vec::new();
+        }
+    };
+
+    windows
+        .into_iter()
+        .filter_map(|window| {
+            let hwnd = window.as_raw_hwnd() as isize;
+            // Windows on other virtual desktops are listed too, but aren't on screen
+            if is_cloaked(hwnd) {
+                return None;
+            }
+            let bounds = window_bounds(hwnd)?;
+            let left = bounds.left.max(monitor.left);
+            let top = bounds.top.max(monitor.top);
+            let right = bounds.right.min(monitor.right);
+            let bottom = bounds.bottom.min(monitor.bottom);
+            if right <= left || bottom <= top {
+                return None;
+            }
+            Some(SnapWindow {
+                hwnd,
+                title: window.title().unwrap_or_default(),
+                x: left - monitor.left,
+                y: top - monitor.top,
+                width: (right - left) as u32,
+                height: (bottom - top) as u32,
+            })
+        })
+        .collect()
+}
+
+/// Windows the open region selector can snap to
+pub fn get_snap_windows() -> Vec<SnapWindow> {
+    SNAP_WINDOWS.lock().unwrap().clone()
+}
 
 /// Opens the region selector window for the specified monitor
 pub async fn open_region_selector(
@@ -20,6 +89,9 @@ pub async fn open_region_selector(
         let left = mi.rcMonitor.left as f64;
         let top = mi.rcMonitor.top as f64;
 
+        // Listed before the overlay exists, as it would cover everything else
+        *SNAP_WINDOWS.lock().unwrap() = snap_windows(&mi.rcMonitor);
+
         // Creating the window on-demand might have a slight performance penalty, but when trying to
         // reuse the same window there was odd behavior with the window flashing a menubar / visible resize.
         let window = tauri::WebviewWindowBuilder::new(
@@ -39,6 +111,11 @@ pub async fn open_region_selector(
 
         let window = window.map_err(|e| e.to_string())?;
 
+        // Don't let the overlay show up in a recording that is already running
+        if is_exclusion_active() {
+            exclude_window(&window);
+        }
+
         window.show().map_err(|e| e.to_string())?;
         window.set_focus().map_err(|e| e.to_string())?;
         Ok(())
@@ -49,6 +126,7 @@ pub async fn open_region_selector(
 
 /// Closes the region selector window
 pub async fn close_region_selector(app: tauri::AppHandle) -> Result<(), String> {
+    SNAP_WINDOWS.lock().unwrap().clear();
     if let Some(window) = app.get_webview_window("region-selector") {
         window
             .destroy()
@@ -63,6 +141,7 @@ pub async fn close_region_selector(app: tauri::AppHandle) -> Result<(), String>
 /// Handles region selection and emits the selected coordinates
 pub async fn region_selected(app: tauri::AppHandle, coordinates: Region) -> Result<(), String> {
     println!("Region selected: {:?}", coordinates);
+    SNAP_WINDOWS.lock().unwrap().clear();
 
     // Emit an event to the main window with the selected coordinates
     app.emit("region-selected", &coordinates)
@@ -4,6 +4,7 @@ import { Label } from '@/components/ui/label';
 import { Switch } from '@/components/ui/switch';
 import { Separator } from '@/components/ui/separator';
 import { cn } from '@/lib/utils';
+import { AudioDevice, DEFAULT_AUDIO_DEVICE, SourceLevel } from '@/types/recording';
 
 interface AudioSettingsProps {
   recordAudio: boolean;
@@ -14,13 +15,30 @@ interface AudioSettingsProps {
   onMicrophoneEnabledChange: (enabled: boolean) => void;
   inputSource: string;
   onInputSourceChange: (source: string) => void;
-  audioSourcesList: string[];
-  microphoneSourcesList: string[];
+  outputDevices: AudioDevice[];
+  inputDevices: AudioDevice[];
+  systemLevel?: SourceLevel;
+  microphoneLevel?: SourceLevel;
 }
 
 // Utility for label disabled style
 const labelDisabled = 'opacity-50';
 
+function LevelMeter({ level }: { level?: SourceLevel }) {
+  // Show the meter on a dB scale so quiet sources are still visible
+  const toWidth = (value: number) => {
+    const db = 20 * Math.log10(Math.max(value, 1e-6));
+    return `${Math.min(Math.max((db + 60) / 60, 0), 1) * 100}%`;
+  };
+
+  return (
+    <div className="bg-muted relative h-1.5 w-full overflow-hidden rounded-full">
+      <div className="absolute inset-y-0 left-0 bg-green-500/50" style={{ width: toWidth(level?.peak ?? 0) }} />
+      <div className="absolute inset-y-0 left-0 bg-green-500" style={{ width: toWidth(level?.rms ?? 0) }} />
+    </div>
+  );
+}
+
 export function AudioSettings({
   recordAudio,
   onRecordAudioChange,
@@ -30,8 +48,10 @@ export function AudioSettings({
   onMicrophoneEnabledChange,
   inputSource,
   onInputSourceChange,
-  audioSourcesList,
-  microphoneSourcesList,
+  outputDevices,
+  inputDevices,
+  systemLevel,
+  microphoneLevel,
 }: AudioSettingsProps) {
   return (
     <Card className="col-span-1 flex w-full gap-y-4">
@@ -54,13 +74,15 @@ export function AudioSettings({
               <SelectValue placeholder="Select audio source" />
             </SelectTrigger>
             <SelectContent>
-              {audioSourcesList.map((src) => (
-                <SelectItem key={src} value={src}>
-                  {src}
+              <SelectItem value={DEFAULT_AUDIO_DEVICE}>All System Audio</SelectItem>
+              {outputDevices.map((device) => (
+                <SelectItem key={device.id} value={device.id}>
+                  {device.name}
                 </SelectItem>
               ))}
             </SelectContent>
           </Select>
+          {recordAudio && <LevelMeter level={systemLevel} />}
         </div>
 
         <Separator />
@@ -80,13 +102,15 @@ export function AudioSettings({
               <SelectValue placeholder="Select input source" />
             </SelectTrigger>
             <SelectContent>
-              {microphoneSourcesList.map((src) => (
-                <SelectItem key={src} value={src}>
-                  {src}
+              <SelectItem value={DEFAULT_AUDIO_DEVICE}>Default Microphone</SelectItem>
+              {inputDevices.map((device) => (
+                <SelectItem key={device.id} value={device.id}>
+                  {device.name}
                 </SelectItem>
               ))}
             </SelectContent>
           </Select>
+          {microphoneEnabled && <LevelMeter level={microphoneLevel} />}
         </div>
       </CardContent>
     </Card>
@@ -3,6 +3,7 @@ import { Label } from '@/components/ui/label';
 import { Button } from '@/components/ui/button';
 import { save } from '@tauri-apps/plugin-dialog';
 import { videoDir } from '@tauri-apps/api/path';
+import { AUDIO_FILE_EXTENSIONS } from '@/types/recording';
 
 interface OutputSettingsProps {
   outputPath: string;
@@ -14,7 +15,11 @@ export function OutputSettings({ outputPath, onOutputPathChange }: OutputSetting
     const path = await save({
       title: 'Select Output Path',
       defaultPath: await videoDir(),
-      filters: [{ name: 'MP4', extensions: ['mp4'] }],
+      filters: [
+        { name: 'MP4', extensions: ['mp4'] },
+        { name: 'GIF', extensions: ['gif'] },
+        { name: 'Audio Only', extensions: AUDIO_FILE_EXTENSIONS },
+      ],
     });
 
     if (path) {
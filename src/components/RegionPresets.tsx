@@ -0,0 +1,109 @@
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { SaveIcon, Trash2Icon } from 'lucide-react';
+import { Region, RegionPresetStatus } from '@/types/recording';
+import { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+interface RegionPresetsProps {
+  /** HMONITOR the current region was drawn on */
+  monitorHandle: number;
+  selectedRegion: Region | null;
+  onApplyPreset: (preset: RegionPresetStatus) => void;
+  /** Changes whenever the monitors may have changed, to recheck which presets still fit */
+  refreshKey?: unknown;
+}
+
+export function RegionPresets({ monitorHandle, selectedRegion, onApplyPreset, refreshKey }: RegionPresetsProps) {
+  const [presets, setPresets] = useState<RegionPresetStatus[]>([]);
+  const [selectedPreset, setSelectedPreset] = useState<string>('');
+  const [presetName, setPresetName] = useState('');
+
+  const loadPresets = useCallback(async () => {
+    try {
+      setPresets(await invoke<RegionPresetStatus[]>('list_region_presets'));
+    } catch (error) {
+      console.error('Error loading region presets:', error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadPresets();
+  }, [loadPresets, refreshKey]);
+
+  const applyPreset = async (name: string) => {
+    setSelectedPreset(name);
+    try {
+      onApplyPreset(await invoke<RegionPresetStatus>('apply_region_preset', { name }));
+    } catch (error) {
+      console.error('Error applying region preset:', error);
+      loadPresets();
+    }
+  };
+
+  const savePreset = async () => {
+    if (!selectedRegion || !presetName.trim()) return;
+    try {
+      const preset = await invoke<RegionPresetStatus>('save_region_preset', {
+        name: presetName,
+        monitorHandle,
+        region: selectedRegion,
+      });
+      setSelectedPreset(preset.name);
+      setPresetName('');
+      loadPresets();
+    } catch (error) {
+      console.error('Error saving region preset:', error);
+    }
+  };
+
+  const deletePreset = async () => {
+    if (!selectedPreset) return;
+    try {
+      await invoke('delete_region_preset', { name: selectedPreset });
+      setSelectedPreset('');
+      loadPresets();
+    } catch (error) {
+      console.error('Error deleting region preset:', error);
+    }
+  };
+
+  return (
+    <div className="flex flex-col gap-y-2">
+      <div className="flex items-center gap-x-2">
+        <Select value={selectedPreset || undefined} onValueChange={applyPreset} disabled={presets.length === 0}>
+          <SelectTrigger className="w-full">
+            <SelectValue placeholder={presets.length > 0 ? 'Apply a saved region' : 'No Saved Regions'} />
+          </SelectTrigger>
+          <SelectContent>
+            {presets.map((preset) => (
+              <SelectItem key={preset.name} value={preset.name} disabled={!!preset.invalid_reason}>
+                <span className="truncate">
+                  {preset.name} ({preset.region.width}x{preset.region.height} on {preset.monitor_name})
+                  {preset.invalid_reason && <span className="text-destructive"> &ndash; {preset.invalid_reason}</span>}
+                </span>
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+        <Button variant="outline" size="icon" onClick={deletePreset} disabled={!selectedPreset}>
+          <Trash2Icon />
+        </Button>
+      </div>
+      {selectedRegion && (
+        <div className="flex items-center gap-x-2">
+          <Input
+            placeholder="Name this region"
+            value={presetName}
+            onChange={(e) => setPresetName(e.target.value)}
+            onKeyDown={(e) => e.key === 'Enter' && savePreset()}
+          />
+          <Button variant="outline" size="icon" onClick={savePreset} disabled={!presetName.trim()}>
+            <SaveIcon />
+          </Button>
+        </div>
+      )}
+    </div>
+  );
+}
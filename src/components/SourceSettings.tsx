@@ -2,8 +2,10 @@ import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
 import { Button } from '@/components/ui/button';
 import { CropIcon, MonitorIcon, AppWindowMacIcon, RefreshCw } from 'lucide-react';
-import { CaptureSource, Region } from '@/types/recording';
+import { RegionPresets } from '@/components/RegionPresets';
+import { CaptureSource, Region, RegionPresetStatus, SourceThumbnail } from '@/types/recording';
 import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 
 interface SourceSettingsProps {
   captureSources: CaptureSource[];
@@ -13,12 +15,30 @@ interface SourceSettingsProps {
   onMonitorCaptureModeChange: (mode: 'full' | 'custom') => void;
   selectedRegion: Region | null;
   onOpenRegionSelector?: () => void;
+  onApplyRegionPreset?: (preset: RegionPresetStatus) => void;
   onTabChange?: (newTab: string) => void;
   onRefresh?: () => void;
 }
 
 type SourceTab = 'monitor' | 'window';
 
+const THUMBNAIL_SIZE = 160;
+
+function SourceLabel({ source, thumbnail }: { source: CaptureSource; thumbnail?: string }) {
+  return (
+    <span className="flex items-center gap-x-2">
+      {thumbnail ? (
+        <img src={thumbnail} alt="" className="h-6 w-10 shrink-0 rounded-sm object-contain" />
+      ) : (
+        <span className="bg-muted h-6 w-10 shrink-0 rounded-sm" />
+      )}
+      <span className="truncate">
+        {source.name} ({source.width}x{source.height})
+      </span>
+    </span>
+  );
+}
+
 export function SourceSettings({
   captureSources,
   selectedSource,
@@ -27,16 +47,59 @@ export function SourceSettings({
   onMonitorCaptureModeChange,
   selectedRegion,
   onOpenRegionSelector,
+  onApplyRegionPreset,
   onTabChange,
   onRefresh,
 }: SourceSettingsProps) {
-  const monitorSources = captureSources.filter((source) => source.source_type === 'monitor');
+  // The combined desktop is listed with the monitors it's made of
+  const monitorSources = captureSources.filter(
+    (source) => source.source_type === 'monitor' || source.source_type === 'desktop',
+  );
   const windowSources = captureSources.filter((source) => source.source_type === 'window');
   const [selectedTab, setSelectedTab] = useState<SourceTab>('monitor');
+  const [thumbnails, setThumbnails] = useState<Record<number, string>>({});
+
+  // Load thumbnails one at a time so a slow window doesn't hold up the rest
+  useEffect(() => {
+    let cancelled = false;
+    const sources = captureSources.filter((source) => source.source_type === selectedTab);
+
+    (async () => {
+      for (const source of sources) {
+        if (cancelled) {
+          return;
+        }
+        try {
+          const thumbnail = await invoke<SourceThumbnail>('get_source_thumbnail', {
+            handle: source.handle,
+            sourceType: source.source_type,
+            maxSize: THUMBNAIL_SIZE,
+          });
+          setThumbnails((current) => ({ ...current, [source.handle]: thumbnail.data_url }));
+        } catch (error) {
+          console.warn(`No thumbnail for ${source.name}:`, error);
+        }
+      }
+    })();
+
+    return () => {
+      cancelled = true;
+    };
+  }, [captureSources, selectedTab]);
 
   // Auto-select first available source when switching tabs
   useEffect(() => {
     const currentSources = selectedTab === 'monitor' ? monitorSources : windowSources;
+    const otherSources = selectedTab === 'monitor' ? windowSources : monitorSources;
+    // A source picked elsewhere, e.g. restored from settings, brings its tab along
+    if (
+      currentSources.length > 0 &&
+      !currentSources.some((source) => source.handle === selectedSource) &&
+      otherSources.some((source) => source.handle === selectedSource)
+    ) {
+      setSelectedTab(selectedTab === 'monitor' ? 'window' : 'monitor');
+      return;
+    }
     if (currentSources.length > 0) {
       const firstSource = currentSources[0];
       // Only change if the current selection is not in the current tab's sources
@@ -62,6 +125,9 @@ export function SourceSettings({
   const currentSources = selectedTab === 'monitor' ? monitorSources : windowSources;
   const hasSources = currentSources.length > 0;
   const emptyMessage = selectedTab === 'monitor' ? 'No Monitors Found' : 'No Windows Found';
+  const desktopSelected = captureSources.some(
+    (source) => source.handle === selectedSource && source.source_type === 'desktop',
+  );
 
   return (
     <Tabs className="w-full gap-y-4" onValueChange={handleTabChange} value={selectedTab}>
@@ -86,7 +152,7 @@ export function SourceSettings({
             <SelectContent>
               {monitorSources.map((source) => (
                 <SelectItem key={source.handle} value={source.handle.toString()}>
-                  {source.name} ({source.width}x{source.height})
+                  <SourceLabel source={source} thumbnail={thumbnails[source.handle]} />
                 </SelectItem>
               ))}
             </SelectContent>
@@ -111,7 +177,7 @@ export function SourceSettings({
               onMonitorCaptureModeChange('custom');
               onOpenRegionSelector?.();
             }}
-            disabled={!hasSources}
+            disabled={!hasSources || desktopSelected}
           >
             <CropIcon /> Custom Region
           </Button>
@@ -121,6 +187,14 @@ export function SourceSettings({
             Selected Region: {selectedRegion.width} &#x00d7; {selectedRegion.height}
           </div>
         )}
+        {hasSources && !desktopSelected && onApplyRegionPreset && (
+          <RegionPresets
+            monitorHandle={selectedSource}
+            selectedRegion={monitorCaptureMode === 'custom' ? selectedRegion : null}
+            onApplyPreset={onApplyRegionPreset}
+            refreshKey={captureSources}
+          />
+        )}
       </TabsContent>
       <TabsContent value="window">
         <div className="flex items-center justify-between gap-x-2">
@@ -135,7 +209,7 @@ export function SourceSettings({
             <SelectContent>
               {windowSources.map((source) => (
                 <SelectItem key={source.handle} value={source.handle.toString()}>
-                  {source.name} ({source.width}x{source.height})
+                  <SourceLabel source={source} thumbnail={thumbnails[source.handle]} />
                 </SelectItem>
               ))}
             </SelectContent>
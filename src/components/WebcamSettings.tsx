@@ -3,13 +3,14 @@ import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@
 import { Label } from '@/components/ui/label';
 import { Switch } from '@/components/ui/switch';
 import { cn } from '@/lib/utils';
+import { WEBCAM_TEST_PATTERN, WebcamDevice } from '@/types/recording';
 
 interface WebcamSettingsProps {
   enableWebcam: boolean;
   onEnableWebcamChange: (enabled: boolean) => void;
   webcamSource: string;
   onWebcamSourceChange: (source: string) => void;
-  webcamSourcesList: string[];
+  webcamDevices: WebcamDevice[];
 }
 
 // Utility for label disabled style
@@ -20,7 +21,7 @@ export function WebcamSettings({
   onEnableWebcamChange,
   webcamSource,
   onWebcamSourceChange,
-  webcamSourcesList,
+  webcamDevices,
 }: WebcamSettingsProps) {
   return (
     <Card className="col-span-1 flex w-full gap-y-4">
@@ -42,11 +43,12 @@ export function WebcamSettings({
               <SelectValue placeholder="Select webcam source" />
             </SelectTrigger>
             <SelectContent>
-              {webcamSourcesList.map((src) => (
-                <SelectItem key={src} value={src}>
-                  {src}
+              {webcamDevices.map((device) => (
+                <SelectItem key={device.id} value={device.id}>
+                  {device.name}
                 </SelectItem>
               ))}
+              <SelectItem value={WEBCAM_TEST_PATTERN}>Test Pattern</SelectItem>
             </SelectContent>
           </Select>
         </div>
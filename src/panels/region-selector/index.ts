@@ -1,24 +1,65 @@
 import { invoke } from '@tauri-apps/api/core';
+import { Region, SnapWindow } from '@/types/recording';
 
-let isSelecting = false;
-let startX = 0;
-let startY = 0;
-let currentX = 0;
-let currentY = 0;
+// All rectangles are in physical pixels relative to the monitor, like the region sent back
+type Handle = 'n' | 's' | 'e' | 'w' | 'ne' | 'nw' | 'se' | 'sw';
+
+type Drag =
+  | { kind: 'draw'; startX: number; startY: number; moved: boolean }
+  | { kind: 'move'; offsetX: number; offsetY: number; width: number; height: number }
+  | { kind: 'resize'; handle: Handle; origin: Region };
+
+/** Drawn regions smaller than this are discarded */
+const MIN_SIZE = 10;
+/** How far the pointer has to travel before a press counts as a drag rather than a click */
+const CLICK_SLOP = 4;
+/** How close, in CSS pixels, an edge has to come to a window or screen edge to snap to it */
+const SNAP_DISTANCE = 8;
+
+const ASPECT_RATIOS: Record<string, number | null> = {
+  free: null,
+  '16:9': 16 / 9,
+  '1:1': 1,
+  '9:16': 9 / 16,
+};
+
+let drag: Drag | null = null;
+let selection: Region | null = null;
+let hovered: SnapWindow | null = null;
+let aspectRatio: number | null = null;
+let snapWindows: SnapWindow[] = [];
 
 // Get the DPI scaling factor
 const dpiScale = window.devicePixelRatio || 1;
 
 const overlay = document.getElementById('selection-overlay') as HTMLElement;
+const highlight = document.getElementById('window-highlight') as HTMLElement;
+const windowTitle = document.getElementById('window-title') as HTMLElement;
 const coordinates = document.getElementById('coordinates') as HTMLElement;
+const toolbar = document.getElementById('toolbar') as HTMLElement;
+const confirmBtn = document.getElementById('confirm-btn') as HTMLButtonElement;
 const cancelBtn = document.getElementById('cancel-btn') as HTMLButtonElement;
+const handles = Array.from(overlay.querySelectorAll<HTMLElement>('[data-handle]'));
+const aspectButtons = Array.from(document.querySelectorAll<HTMLButtonElement>('[data-aspect]'));
+
+invoke<SnapWindow[]>('get_snap_windows')
+  .then((windows) => {
+    snapWindows = windows;
+  })
+  .catch((error) => console.error('Error loading windows to snap to:', error));
 
 // ----- Event bindings -----
-document.addEventListener('mousedown', startSelection);
-document.addEventListener('mousemove', updateSelection);
-document.addEventListener('mouseup', endSelection);
+document.addEventListener('mousedown', startDrag);
+document.addEventListener('mousemove', updateDrag);
+document.addEventListener('mouseup', endDrag);
+document.addEventListener('dblclick', handleDoubleClick);
 document.addEventListener('keydown', handleKeyDown);
+toolbar.addEventListener('mousedown', (e) => e.stopPropagation());
+confirmBtn.addEventListener('click', confirmSelection);
 cancelBtn.addEventListener('click', cancelSelection);
+aspectButtons.forEach((button) =>
+  button.addEventListener('click', () => setAspectRatio(ASPECT_RATIOS[button.dataset.aspect ?? 'free'] ?? null)),
+);
 
 document.addEventListener('visibilitychange', () => {
   if (!document.hidden) {
@@ -26,72 +67,360 @@ document.addEventListener('visibilitychange', () => {
   }
 });
 
-function startSelection(e: MouseEvent) {
-  isSelecting = true;
-  // Apply DPI scaling to get actual screen coordinates
-  startX = Math.round(e.pageX * dpiScale);
-  startY = Math.round(e.pageY * dpiScale);
-  currentX = startX;
-  currentY = startY;
+render();
 
-  // Use unscaled coordinates for visual display
-  overlay.style.left = `${e.pageX}px`;
-  overlay.style.top = `${e.pageY}px`;
-  overlay.style.width = '0px';
-  overlay.style.height = '0px';
-  overlay.classList.remove('hidden');
-  coordinates.classList.remove('hidden');
-  updateCoordinatesDisplay();
+// ----- Geometry -----
+
+function screenWidth() {
+  return Math.round(window.innerWidth * dpiScale);
 }
 
-function updateSelection(e: MouseEvent) {
-  if (!isSelecting) return;
+function screenHeight() {
+  return Math.round(window.innerHeight * dpiScale);
+}
 
+function toPhysical(e: MouseEvent) {
   // Apply DPI scaling to get actual screen coordinates
-  currentX = Math.round(e.pageX * dpiScale);
-  currentY = Math.round(e.pageY * dpiScale);
-  // Use unscaled coordinates for visual display
-  const displayLeft = Math.min(startX / dpiScale, currentX / dpiScale);
-  const displayTop = Math.min(startY / dpiScale, currentY / dpiScale);
-  const displayWidth = Math.abs(currentX / dpiScale - startX / dpiScale);
-  const displayHeight = Math.abs(currentY / dpiScale - startY / dpiScale);
+  return { x: Math.round(e.pageX * dpiScale), y: Math.round(e.pageY * dpiScale) };
+}
 
-  overlay.style.left = `${displayLeft}px`;
-  overlay.style.top = `${displayTop}px`;
-  overlay.style.width = `${displayWidth}px`;
-  overlay.style.height = `${displayHeight}px`;
+function contains(rect: Region, x: number, y: number) {
+  return x >= rect.x && y >= rect.y && x < rect.x + rect.width && y < rect.y + rect.height;
+}
 
-  updateCoordinatesDisplay();
+/** Topmost window under the point */
+function windowAt(x: number, y: number) {
+  return snapWindows.find((snapWindow) => contains(snapWindow, x, y)) ?? null;
 }
 
-function updateCoordinatesDisplay() {
-  const left = Math.min(startX, currentX);
-  const top = Math.min(startY, currentY);
-  const width = Math.abs(currentX - startX);
-  const height = Math.abs(currentY - startY);
+/** Screen and window edges a vertical (`x`) or horizontal (`y`) edge can snap to */
+function snapEdges(axis: 'x' | 'y') {
+  const edges = axis === 'x' ? [0, screenWidth()] : [0, screenHeight()];
+  for (const snapWindow of snapWindows) {
+    if (axis === 'x') {
+      edges.push(snapWindow.x, snapWindow.x + snapWindow.width);
+    } else {
+      edges.push(snapWindow.y, snapWindow.y + snapWindow.height);
+    }
+  }
+  return edges;
+}
 
-  coordinates.textContent = `X: ${left}, Y: ${top}, Width: ${width}, Height: ${height} (DPI: ${dpiScale})`;
+/** Moves `value` onto the nearest edge within snapping distance */
+function snap(value: number, axis: 'x' | 'y', enabled: boolean) {
+  if (!enabled) {
+    return value;
+  }
+  let best = value;
+  let bestDistance = SNAP_DISTANCE * dpiScale;
+  for (const edge of snapEdges(axis)) {
+    const distance = Math.abs(edge - value);
+    if (distance <= bestDistance) {
+      best = edge;
+      bestDistance = distance;
+    }
+  }
+  return best;
 }
 
-async function endSelection() {
-  if (!isSelecting) return;
-  isSelecting = false;
+/** Offset that snaps whichever side of a span is closer to an edge */
+function snapOffset(start: number, size: number, axis: 'x' | 'y', enabled: boolean) {
+  const before = snap(start, axis, enabled) - start;
+  const after = snap(start + size, axis, enabled) - (start + size);
+  if (before === 0) return after;
+  if (after === 0) return before;
+  return Math.abs(before) <= Math.abs(after) ? before : after;
+}
 
-  const left = Math.min(startX, currentX);
-  const top = Math.min(startY, currentY);
-  const width = Math.abs(currentX - startX);
-  const height = Math.abs(currentY - startY);
+/** Rectangle spanned from a fixed corner to the pointer, kept on screen and to the aspect ratio */
+function spanRect(anchorX: number, anchorY: number, x: number, y: number): Region {
+  const left = x < anchorX;
+  const up = y < anchorY;
+  const maxWidth = left ? anchorX : screenWidth() - anchorX;
+  const maxHeight = up ? anchorY : screenHeight() - anchorY;
 
-  if (width > 10 && height > 10) {
-    const regionData = { x: left, y: top, width, height };
-    try {
-      await invoke('region_selected', { coordinates: regionData });
-      await cancelSelection();
-    } catch (error) {
-      console.error('Error sending region coordinates:', error);
-      await cancelSelection();
+  let width = Math.min(Math.abs(x - anchorX), maxWidth);
+  let height = Math.min(Math.abs(y - anchorY), maxHeight);
+  if (aspectRatio) {
+    // Follow whichever side the pointer pulled further, then shrink to stay on screen
+    if (width / aspectRatio >= height) {
+      height = width / aspectRatio;
+    } else {
+      width = height * aspectRatio;
     }
+    const fit = Math.min(1, maxWidth / width || 1, maxHeight / height || 1);
+    width = Math.round(width * fit);
+    height = Math.round(height * fit);
+  }
+
+  return {
+    x: left ? anchorX - width : anchorX,
+    y: up ? anchorY - height : anchorY,
+    width,
+    height,
+  };
+}
+
+/** Shifts the rectangle onto the screen, shrinking it if it's larger than the screen */
+function clampToScreen(rect: Region): Region {
+  const width = Math.min(rect.width, screenWidth());
+  const height = Math.min(rect.height, screenHeight());
+  return {
+    x: Math.min(Math.max(rect.x, 0), screenWidth() - width),
+    y: Math.min(Math.max(rect.y, 0), screenHeight() - height),
+    width,
+    height,
+  };
+}
+
+/** Reshapes the rectangle to the aspect ratio around its center, keeping its width where possible */
+function applyAspectRatio(rect: Region): Region {
+  if (!aspectRatio) {
+    return rect;
+  }
+  let width = rect.width;
+  let height = Math.round(width / aspectRatio);
+  if (height > screenHeight()) {
+    height = screenHeight();
+    width = Math.round(height * aspectRatio);
+  }
+  return clampToScreen({
+    x: Math.round(rect.x + (rect.width - width) / 2),
+    y: Math.round(rect.y + (rect.height - height) / 2),
+    width,
+    height,
+  });
+}
+
+function resizeRect(origin: Region, handle: Handle, x: number, y: number): Region {
+  const right = origin.x + origin.width;
+  const bottom = origin.y + origin.height;
+
+  // Corners span from the opposite corner
+  if (handle.length === 2) {
+    const anchorX = handle.includes('w') ? right : origin.x;
+    const anchorY = handle.includes('n') ? bottom : origin.y;
+    return spanRect(anchorX, anchorY, x, y);
+  }
+
+  // Edges move one side; with a locked aspect ratio the other axis follows around its center
+  const clampedX = Math.min(Math.max(x, 0), screenWidth());
+  const clampedY = Math.min(Math.max(y, 0), screenHeight());
+  let rect: Region;
+  if (handle === 'e' || handle === 'w') {
+    const edge = handle === 'w' ? right : origin.x;
+    rect = { x: Math.min(edge, clampedX), y: origin.y, width: Math.abs(clampedX - edge), height: origin.height };
+    if (aspectRatio) {
+      const height = Math.round(rect.width / aspectRatio);
+      rect = { ...rect, y: Math.round(origin.y + (origin.height - height) / 2), height };
+    }
+  } else {
+    const edge = handle === 'n' ? bottom : origin.y;
+    rect = { x: origin.x, y: Math.min(edge, clampedY), width: origin.width, height: Math.abs(clampedY - edge) };
+    if (aspectRatio) {
+      const width = Math.round(rect.height * aspectRatio);
+      rect = { ...rect, x: Math.round(origin.x + (origin.width - width) / 2), width };
+    }
+  }
+  return clampToScreen(rect);
+}
+
+// ----- Mouse -----
+
+function startDrag(e: MouseEvent) {
+  if (e.button !== 0) return;
+  const { x, y } = toPhysical(e);
+  const handle = (e.target as HTMLElement).dataset.handle as Handle | undefined;
+
+  if (selection && handle) {
+    drag = { kind: 'resize', handle, origin: selection };
+  } else if (selection && contains(selection, x, y)) {
+    drag = {
+      kind: 'move',
+      offsetX: x - selection.x,
+      offsetY: y - selection.y,
+      width: selection.width,
+      height: selection.height,
+    };
   } else {
+    drag = { kind: 'draw', startX: x, startY: y, moved: false };
+  }
+  render();
+}
+
+function updateDrag(e: MouseEvent) {
+  const { x, y } = toPhysical(e);
+  const snapping = !e.ctrlKey;
+
+  if (!drag) {
+    hovered = selection ? null : windowAt(x, y);
+    document.body.style.cursor = selection && contains(selection, x, y) ? 'move' : '';
+    render();
+    return;
+  }
+
+  switch (drag.kind) {
+    case 'draw': {
+      if (!drag.moved && Math.hypot(x - drag.startX, y - drag.startY) < CLICK_SLOP * dpiScale) {
+        return;
+      }
+      if (!drag.moved) {
+        drag.moved = true;
+        drag.startX = snap(drag.startX, 'x', snapping);
+        drag.startY = snap(drag.startY, 'y', snapping);
+      }
+      hovered = null;
+      selection = spanRect(drag.startX, drag.startY, snap(x, 'x', snapping), snap(y, 'y', snapping));
+      break;
+    }
+    case 'move': {
+      const rect = clampToScreen({ x: x - drag.offsetX, y: y - drag.offsetY, width: drag.width, height: drag.height });
+      selection = clampToScreen({
+        ...rect,
+        x: rect.x + snapOffset(rect.x, rect.width, 'x', snapping),
+        y: rect.y + snapOffset(rect.y, rect.height, 'y', snapping),
+      });
+      break;
+    }
+    case 'resize':
+      selection = resizeRect(drag.origin, drag.handle, snap(x, 'x', snapping), snap(y, 'y', snapping));
+      break;
+  }
+  render();
+}
+
+function endDrag(e: MouseEvent) {
+  if (!drag) return;
+  const finished = drag;
+  drag = null;
+
+  if (finished.kind === 'draw') {
+    if (!finished.moved) {
+      // A click picks the window under the pointer, or clears the selection
+      const { x, y } = toPhysical(e);
+      const target = windowAt(x, y);
+      selection = target
+        ? applyAspectRatio({ x: target.x, y: target.y, width: target.width, height: target.height })
+        : null;
+      hovered = null;
+    } else if (selection && (selection.width < MIN_SIZE || selection.height < MIN_SIZE)) {
+      selection = null;
+    }
+  } else if (selection && (selection.width < MIN_SIZE || selection.height < MIN_SIZE)) {
+    // Resizing can't make a region vanish, only shrink it to the minimum
+    selection = clampToScreen({
+      ...selection,
+      width: Math.max(selection.width, MIN_SIZE),
+      height: Math.max(selection.height, MIN_SIZE),
+    });
+  }
+  render();
+}
+
+function handleDoubleClick(e: MouseEvent) {
+  const { x, y } = toPhysical(e);
+  if (selection && contains(selection, x, y)) {
+    confirmSelection();
+  }
+}
+
+// ----- Keyboard -----
+
+function handleKeyDown(e: KeyboardEvent) {
+  if (e.key === 'Escape') {
+    cancelSelection();
+    return;
+  }
+  if (e.key === 'Enter') {
+    confirmSelection();
+    return;
+  }
+
+  const directions: Record<string, [number, number]> = {
+    ArrowLeft: [-1, 0],
+    ArrowRight: [1, 0],
+    ArrowUp: [0, -1],
+    ArrowDown: [0, 1],
+  };
+  const direction = directions[e.key];
+  if (!direction || !selection || drag) return;
+  e.preventDefault();
+
+  const step = e.shiftKey ? 10 : 1;
+  const [dx, dy] = direction;
+  if (e.altKey) {
+    // Grow or shrink from the right and bottom edges
+    const width = Math.max(selection.width + dx * step, MIN_SIZE);
+    let height = Math.max(selection.height + dy * step, MIN_SIZE);
+    if (aspectRatio) {
+      height = dx !== 0 ? Math.round(width / aspectRatio) : height;
+      const lockedWidth = dy !== 0 ? Math.round(height * aspectRatio) : width;
+      selection = clampToScreen({ ...selection, width: lockedWidth, height });
+    } else {
+      selection = clampToScreen({ ...selection, width, height });
+    }
+  } else {
+    selection = clampToScreen({ ...selection, x: selection.x + dx * step, y: selection.y + dy * step });
+  }
+  render();
+}
+
+function setAspectRatio(ratio: number | null) {
+  aspectRatio = ratio;
+  if (selection) {
+    selection = applyAspectRatio(selection);
+  }
+  render();
+}
+
+// ----- Display -----
+
+function placeElement(element: HTMLElement, rect: Region) {
+  // Use unscaled coordinates for visual display
+  element.style.left = `${rect.x / dpiScale}px`;
+  element.style.top = `${rect.y / dpiScale}px`;
+  element.style.width = `${rect.width / dpiScale}px`;
+  element.style.height = `${rect.height / dpiScale}px`;
+}
+
+function render() {
+  overlay.classList.toggle('hidden', !selection);
+  if (selection) {
+    placeElement(overlay, selection);
+  }
+  // Handles would get in the way while the region is still being drawn
+  const showHandles = !!selection && drag?.kind !== 'draw';
+  handles.forEach((handle) => handle.classList.toggle('hidden', !showHandles));
+
+  highlight.classList.toggle('hidden', !hovered || !!selection);
+  if (hovered) {
+    placeElement(highlight, hovered);
+    windowTitle.textContent = hovered.title;
+  }
+
+  const shown = selection ?? hovered;
+  coordinates.classList.toggle('hidden', !shown);
+  if (shown) {
+    coordinates.textContent = `X: ${shown.x}, Y: ${shown.y}, Width: ${shown.width}, Height: ${shown.height} (DPI: ${dpiScale})`;
+  }
+
+  confirmBtn.disabled = !selection;
+  aspectButtons.forEach((button) => {
+    const active = (ASPECT_RATIOS[button.dataset.aspect ?? 'free'] ?? null) === aspectRatio;
+    button.classList.toggle('bg-white/30', active);
+  });
+}
+
+// ----- Result -----
+
+async function confirmSelection() {
+  if (!selection) return;
+  const regionData = { ...selection };
+  try {
+    await invoke('region_selected', { coordinates: regionData });
+    await cancelSelection();
+  } catch (error) {
+    console.error('Error sending region coordinates:', error);
     await cancelSelection();
   }
 }
@@ -105,15 +434,10 @@ async function cancelSelection() {
   }
 }
 
-function handleKeyDown(e: KeyboardEvent) {
-  if (e.key === 'Escape') {
-    cancelSelection();
-  }
-}
-
 function resetState() {
-  isSelecting = false;
-  overlay.classList.add('hidden');
-  coordinates.classList.add('hidden');
-  startX = startY = currentX = currentY = 0;
+  drag = null;
+  selection = null;
+  hovered = null;
+  document.body.style.cursor = '';
+  render();
 }
@@ -1,5 +1,5 @@
-import { useState, useEffect, useCallback, useMemo } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { useState, useEffect, useCallback, useMemo, useRef } from 'react';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Button } from '@/components/ui/button';
@@ -8,7 +8,30 @@ import { SourceSettings } from '@/components/SourceSettings';
 import { OutputSettings } from '@/components/OutputSettings';
 import { AudioSettings } from '@/components/AudioSettings';
 import { WebcamSettings } from '@/components/WebcamSettings';
-import { CaptureSource, Region } from '@/types/recording';
+import {
+  AudioDevice,
+  AudioInput,
+  CaptureSource,
+  CaptureSourcesChanged,
+  DEFAULT_AUDIO_DEVICE,
+  GifExported,
+  PreviewFrame,
+  RecordingOptions,
+  RecordingStats,
+  Region,
+  RegionPresetStatus,
+  Settings,
+  SettingsChanges,
+  SilenceWarning,
+  SourceClosed,
+  SourceLevel,
+  SourceLost,
+  WEBCAM_TEST_PATTERN,
+  WebcamDevice,
+  WebcamFailed,
+  isAudioFile,
+  isGifFile,
+} from '@/types/recording';
 import { RefreshCw } from 'lucide-react';
 
 export default function RecordPage() {
@@ -16,25 +39,75 @@ export default function RecordPage() {
   const [selectedSource, setSelectedSource] = useState<number>(0);
   const [outputPath, setOutputPath] = useState('');
   const [isRecording, setIsRecording] = useState(false);
+  const [isPaused, setIsPaused] = useState(false);
   const [selectedRegion, setSelectedRegion] = useState<Region | null>(null);
   const [isRegionSelectorOpen, setIsRegionSelectorOpen] = useState(false);
+  const [previewUrl, setPreviewUrl] = useState<string | null>(null);
 
   /* ---------------------------------- Audio ---------------------------------- */
   const [recordAudio, setRecordAudio] = useState(false);
-  const [audioSource, setAudioSource] = useState('');
-  const audioSourcesList = useMemo(() => ['All System Audio'], []);
+  const [audioSource, setAudioSource] = useState(DEFAULT_AUDIO_DEVICE);
+  const [audioDevices, setAudioDevices] = useState<AudioDevice[]>([]);
+  const outputDevices = useMemo(() => audioDevices.filter((device) => device.kind === 'output'), [audioDevices]);
+  const inputDevices = useMemo(() => audioDevices.filter((device) => device.kind === 'input'), [audioDevices]);
 
   const [microphoneEnabled, setMicrophoneEnabled] = useState(false);
-  const [inputSource, setInputSource] = useState('');
-  const microphoneSourcesList = useMemo(() => ['Default - Microphone'], []);
+  const [inputSource, setInputSource] = useState(DEFAULT_AUDIO_DEVICE);
+  const [audioLevels, setAudioLevels] = useState<SourceLevel[]>([]);
+
+  const audioInputs = useMemo(() => {
+    const deviceId = (value: string) => (value === DEFAULT_AUDIO_DEVICE ? null : value);
+    const inputs: AudioInput[] = [];
+    if (recordAudio) {
+      inputs.push({ source: { type: 'loopback', device_id: deviceId(audioSource) } });
+    }
+    if (microphoneEnabled) {
+      inputs.push({ source: { type: 'microphone', device_id: deviceId(inputSource) } });
+    }
+    return inputs;
+  }, [recordAudio, audioSource, microphoneEnabled, inputSource]);
 
   /* --------------------------------- Webcam ---------------------------------- */
   const [enableWebcam, setEnableWebcam] = useState(false);
   const [webcamSource, setWebcamSource] = useState('');
-  const webcamSourcesList = useMemo(() => ['Logitech 4K Webcam'], []);
+  const [webcamDevices, setWebcamDevices] = useState<WebcamDevice[]>([]);
 
   const [monitorCaptureMode, setMonitorCaptureMode] = useState<'full' | 'custom'>('full');
 
+  /* -------------------------------- Settings --------------------------------- */
+  const [settingsLoaded, setSettingsLoaded] = useState(false);
+  const [recordingDefaults, setRecordingDefaults] = useState<RecordingOptions>({});
+  // Restored once the source list is in, as handles change between runs
+  const savedSource = useRef<Settings['source'] | null>(null);
+
+  // Restore the last session's choices before anything gets saved over them
+  useEffect(() => {
+    invoke<Settings>('get_settings')
+      .then((settings) => {
+        setOutputPath(settings.output.path);
+        setRecordAudio(settings.audio.system_audio);
+        setAudioSource(settings.audio.system_device ?? DEFAULT_AUDIO_DEVICE);
+        setMicrophoneEnabled(settings.audio.microphone);
+        setInputSource(settings.audio.microphone_device ?? DEFAULT_AUDIO_DEVICE);
+        setEnableWebcam(settings.webcam.enabled);
+        if (settings.webcam.device) {
+          setWebcamSource(settings.webcam.device);
+        }
+        setRecordingDefaults(settings.recording);
+        savedSource.current = settings.source;
+      })
+      .catch((error) => console.error('Error loading settings:', error))
+      .finally(() => setSettingsLoaded(true));
+
+    const unlistenSettingsChanged = listen<Settings>('settings-changed', (event) => {
+      setRecordingDefaults(event.payload.recording);
+    });
+
+    return () => {
+      unlistenSettingsChanged.then((unlisten) => unlisten());
+    };
+  }, []);
+
   const loadCaptureSources = useCallback(async () => {
     try {
       const sources = await invoke<CaptureSource[]>('get_capture_sources');
@@ -47,6 +120,139 @@ export default function RecordPage() {
     }
   }, []);
 
+  const loadWebcamDevices = useCallback(async () => {
+    try {
+      const devices = await invoke<WebcamDevice[]>('get_webcam_devices');
+      setWebcamDevices(devices);
+      setWebcamSource((current) => current || devices[0]?.id || WEBCAM_TEST_PATTERN);
+    } catch (error) {
+      console.error('Error loading webcam devices:', error);
+    }
+  }, []);
+
+  const loadAudioDevices = useCallback(async () => {
+    try {
+      setAudioDevices(await invoke<AudioDevice[]>('get_audio_devices'));
+    } catch (error) {
+      console.error('Error loading audio devices:', error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadWebcamDevices();
+    loadAudioDevices();
+
+    // Keep the device pickers in sync with headsets being plugged in or removed
+    const unlistenAudioDevices = listen<AudioDevice[]>('audio-devices-changed', (event) => {
+      const devices = event.payload;
+      setAudioDevices(devices);
+      const keepIfPresent = (current: string) =>
+        devices.some((device) => device.id === current) ? current : DEFAULT_AUDIO_DEVICE;
+      setAudioSource(keepIfPresent);
+      setInputSource(keepIfPresent);
+    });
+
+    const unlistenAudioLevels = listen<SourceLevel[]>('audio-levels', (event) => {
+      setAudioLevels(event.payload);
+    });
+
+    const unlistenSilenceWarning = listen<SilenceWarning>('audio-silence-warning', (event) => {
+      console.warn(`Audio source ${event.payload.source.type} has been silent for ${event.payload.silent_secs}s`);
+    });
+
+    return () => {
+      unlistenAudioDevices.then((unlisten) => unlisten());
+      unlistenAudioLevels.then((unlisten) => unlisten());
+      unlistenSilenceWarning.then((unlisten) => unlisten());
+    };
+  }, [loadWebcamDevices, loadAudioDevices]);
+
+  // Preview levels before recording; the recording takes over metering once it starts
+  useEffect(() => {
+    if (isRecording) {
+      return;
+    }
+
+    setAudioLevels([]);
+    invoke('start_audio_monitor', { audio: audioInputs }).catch((error) =>
+      console.error('Error starting audio monitor:', error),
+    );
+    return () => {
+      invoke('stop_audio_monitor').catch((error) => console.error('Error stopping audio monitor:', error));
+    };
+  }, [isRecording, audioInputs]);
+
+  // Live preview of the selected source; while recording, the recorder supplies the frames
+  // Only the type is a dependency, so list updates for other sources don't restart the preview
+  const selectedSourceType = captureSources.find((source) => source.handle === selectedSource)?.source_type;
+  useEffect(() => {
+    if (!selectedSourceType) {
+      setPreviewUrl(null);
+      invoke('stop_preview').catch((error) => console.error('Error stopping preview:', error));
+      return;
+    }
+
+    const onFrame = new Channel<PreviewFrame>();
+    onFrame.onmessage = (frame) => setPreviewUrl(frame.data_url);
+    invoke('start_preview', {
+      handle: selectedSource,
+      sourceType: selectedSourceType,
+      region: monitorCaptureMode === 'custom' ? selectedRegion : null,
+      onFrame,
+    }).catch((error) => console.error('Error starting preview:', error));
+
+    // start_preview replaces the running preview, so stopping it here would race the next start
+    return () => {
+      onFrame.onmessage = () => {};
+    };
+  }, [selectedSource, selectedSourceType, monitorCaptureMode, selectedRegion, isRecording]);
+
+  useEffect(() => {
+    return () => {
+      invoke('stop_preview').catch((error) => console.error('Error stopping preview:', error));
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlistenRecordingStats = listen<RecordingStats>('recording-stats', (event) => {
+      console.log('Recording stats:', event.payload);
+    });
+
+    const unlistenSourceLost = listen<SourceLost>('recording-source-lost', (event) => {
+      console.warn('The recorded source is gone:', event.payload);
+    });
+
+    const unlistenWebcamFailed = listen<WebcamFailed>('webcam-failed', (event) => {
+      console.error('Webcam stopped:', event.payload.error);
+    });
+
+    const unlistenGifExported = listen<GifExported>('gif-exported', (event) => {
+      const { output_path, error } = event.payload;
+      if (error) {
+        console.error(`Failed to save ${output_path}:`, error);
+      } else {
+        console.log('GIF saved to', output_path);
+      }
+    });
+
+    // Keep the source list current as monitors and windows come and go
+    const unlistenSourcesChanged = listen<CaptureSourcesChanged>('capture-sources-changed', (event) => {
+      const { sources } = event.payload;
+      setCaptureSources(sources);
+      setSelectedSource((current) =>
+        sources.some((source) => source.handle === current) ? current : (sources[0]?.handle ?? 0),
+      );
+    });
+
+    return () => {
+      unlistenRecordingStats.then((unlisten) => unlisten());
+      unlistenSourceLost.then((unlisten) => unlisten());
+      unlistenWebcamFailed.then((unlisten) => unlisten());
+      unlistenGifExported.then((unlisten) => unlisten());
+      unlistenSourcesChanged.then((unlisten) => unlisten());
+    };
+  }, []);
+
   useEffect(() => {
     loadCaptureSources();
 
@@ -73,36 +279,149 @@ export default function RecordPage() {
     };
   }, [monitorCaptureMode, isRegionSelectorOpen, loadCaptureSources]);
 
+  useEffect(() => {
+    const saved = savedSource.current;
+    if (!settingsLoaded || !saved || captureSources.length === 0) {
+      return;
+    }
+    savedSource.current = null;
+
+    const source = captureSources.find((s) => s.source_type === saved.source_type && s.name === saved.name);
+    if (source) {
+      setSelectedSource(source.handle);
+      if (saved.capture_mode === 'custom' && saved.region) {
+        setMonitorCaptureMode('custom');
+        setSelectedRegion(saved.region);
+      }
+    }
+  }, [captureSources, settingsLoaded]);
+
+  // Remember choices for the next run, once they've settled
+  useEffect(() => {
+    if (!settingsLoaded) {
+      return;
+    }
+
+    const deviceId = (value: string) => (value === DEFAULT_AUDIO_DEVICE ? null : value);
+    const changes: SettingsChanges = {
+      output: { path: outputPath },
+      audio: {
+        system_audio: recordAudio,
+        system_device: deviceId(audioSource),
+        microphone: microphoneEnabled,
+        microphone_device: deviceId(inputSource),
+      },
+      webcam: { enabled: enableWebcam, device: webcamSource || null },
+    };
+    // Until the saved source has been restored, the selection is just the first in the list
+    const source = captureSources.find((s) => s.handle === selectedSource);
+    if (source && !savedSource.current) {
+      const custom = monitorCaptureMode === 'custom' && selectedRegion !== null;
+      changes.source = {
+        source_type: source.source_type,
+        name: source.name,
+        capture_mode: custom ? 'custom' : 'full',
+        region: custom ? selectedRegion : null,
+      };
+    }
+
+    const timer = setTimeout(() => {
+      invoke('update_settings', { changes }).catch((error) => console.error('Error saving settings:', error));
+    }, 500);
+    return () => clearTimeout(timer);
+  }, [
+    settingsLoaded,
+    outputPath,
+    recordAudio,
+    audioSource,
+    microphoneEnabled,
+    inputSource,
+    enableWebcam,
+    webcamSource,
+    captureSources,
+    selectedSource,
+    monitorCaptureMode,
+    selectedRegion,
+  ]);
+
   const startRecording = useCallback(async () => {
+    const audioOnly = isAudioFile(outputPath);
     const source = captureSources.find((s) => s.handle === selectedSource);
-    if (!source) {
+    if (!source && !audioOnly) {
       console.error('Error: no capture source selected');
       return;
     }
 
+    const options: RecordingOptions = {
+      ...recordingDefaults,
+      webcam:
+        enableWebcam && webcamSource
+          ? {
+              source:
+                webcamSource === WEBCAM_TEST_PATTERN ? { type: 'pattern' } : { type: 'device', id: webcamSource },
+            }
+          : null,
+    };
+
     try {
       const result = await invoke<string>('start_recording', {
-        handle: source.handle,
-        sourceType: source.source_type,
+        handle: source?.handle ?? 0,
+        sourceType: audioOnly ? 'audio' : source?.source_type,
         outputPath: outputPath,
         region: monitorCaptureMode === 'custom' ? selectedRegion : null,
+        options,
+        // GIFs have no sound
+        audio: isGifFile(outputPath) ? [] : audioInputs,
       });
       console.log('Recording started:', result);
       setIsRecording(true);
     } catch (error) {
       console.error('Error starting recording:', error);
     }
-  }, [captureSources, selectedSource, outputPath, monitorCaptureMode, selectedRegion]);
+  }, [
+    captureSources,
+    selectedSource,
+    outputPath,
+    monitorCaptureMode,
+    selectedRegion,
+    enableWebcam,
+    webcamSource,
+    audioInputs,
+    recordingDefaults,
+  ]);
 
   const stopRecording = useCallback(async () => {
     try {
       await invoke<string>('stop_recording');
       setIsRecording(false);
+      setIsPaused(false);
     } catch (error) {
       console.error('Error stopping recording:', error);
     }
   }, []);
 
+  // The recorder finishes the file and ends the session itself when the window closes
+  useEffect(() => {
+    const unlistenSourceClosed = listen<SourceClosed>('recording-source-closed', (event) => {
+      console.log('Recorded window closed, recording finished:', event.payload);
+      setIsRecording(false);
+      setIsPaused(false);
+    });
+
+    return () => {
+      unlistenSourceClosed.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  const togglePause = useCallback(async () => {
+    try {
+      await invoke<string>(isPaused ? 'resume_recording' : 'pause_recording');
+      setIsPaused(!isPaused);
+    } catch (error) {
+      console.error('Error pausing recording:', error);
+    }
+  }, [isPaused]);
+
   const openRegionSelector = useCallback(async () => {
     try {
       setIsRegionSelectorOpen(true);
@@ -122,6 +441,13 @@ export default function RecordPage() {
     }
   }, []);
 
+  const applyRegionPreset = useCallback((preset: RegionPresetStatus) => {
+    if (preset.monitor_handle === null) return;
+    setSelectedSource(preset.monitor_handle);
+    setMonitorCaptureMode('custom');
+    setSelectedRegion(preset.region);
+  }, []);
+
   const handleSourceChange = useCallback(
     (source: number) => {
       setSelectedSource(source);
@@ -154,10 +480,18 @@ export default function RecordPage() {
                 onMonitorCaptureModeChange={handleMonitorCaptureModeChange}
                 selectedRegion={selectedRegion}
                 onOpenRegionSelector={openRegionSelector}
+                onApplyRegionPreset={applyRegionPreset}
                 onTabChange={handleTabChange}
                 onRefresh={loadCaptureSources}
               />
             </div>
+            <div className="bg-muted flex aspect-video w-full items-center justify-center overflow-hidden rounded-md">
+              {previewUrl ? (
+                <img src={previewUrl} alt="Preview" className="h-full w-full object-contain" />
+              ) : (
+                <span className="text-muted-foreground text-sm">No Preview</span>
+              )}
+            </div>
           </CardContent>
 
           <CardContent className="flex flex-col gap-y-4">
@@ -168,9 +502,16 @@ export default function RecordPage() {
 
           <CardContent className="mt-auto flex flex-col gap-y-4">
             <Separator />
-            <Button onClick={isRecording ? stopRecording : startRecording}>
-              {isRecording ? 'Stop Recording' : 'Start Recording'}
-            </Button>
+            <div className="flex gap-x-2">
+              <Button className="flex-1" onClick={isRecording ? stopRecording : startRecording}>
+                {isRecording ? 'Stop Recording' : 'Start Recording'}
+              </Button>
+              {isRecording && (
+                <Button variant="outline" onClick={togglePause}>
+                  {isPaused ? 'Resume' : 'Pause'}
+                </Button>
+              )}
+            </div>
           </CardContent>
         </Card>
 
@@ -184,8 +525,10 @@ export default function RecordPage() {
             onMicrophoneEnabledChange={setMicrophoneEnabled}
             inputSource={inputSource}
             onInputSourceChange={setInputSource}
-            audioSourcesList={audioSourcesList}
-            microphoneSourcesList={microphoneSourcesList}
+            outputDevices={outputDevices}
+            inputDevices={inputDevices}
+            systemLevel={audioLevels.find((level) => level.source.type === 'loopback')}
+            microphoneLevel={audioLevels.find((level) => level.source.type === 'microphone')}
           />
 
           <WebcamSettings
@@ -193,7 +536,7 @@ export default function RecordPage() {
             onEnableWebcamChange={setEnableWebcam}
             webcamSource={webcamSource}
             onWebcamSourceChange={setWebcamSource}
-            webcamSourcesList={webcamSourcesList}
+            webcamDevices={webcamDevices}
           />
         </div>
       </div>
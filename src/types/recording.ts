@@ -6,9 +6,386 @@ export interface CaptureSource {
   source_type: string;
 }
 
+/** A window as returned by `find_window` */
+export interface WindowInfo {
+  id: number;
+  hwnd: number;
+  title: string;
+  width: number;
+  height: number;
+  pid: number;
+  process_name: string | null;
+  exe_path: string | null;
+  left: number;
+  top: number;
+  monitor: number;
+  dpi_scale: number;
+  z_order: number;
+  cloaked: boolean;
+  on_other_desktop: boolean;
+}
+
+/** Picks a window without knowing its handle; see `find_window` and `start_recording` */
+export type WindowSelector = (
+  | { type: 'title'; title: string }
+  | { type: 'title_regex'; pattern: string }
+  | { type: 'executable'; name: string }
+  | { type: 'foreground' }
+) & {
+  /** Seconds to keep looking while no window matches */
+  wait_secs?: number;
+};
+
+/** Where a scene layer's picture comes from; `window_match` is looked up when recording starts */
+export type SceneSource =
+  | { type: 'monitor'; handle: number }
+  | { type: 'window'; handle: number }
+  | { type: 'window_match'; selector: WindowSelector };
+
+export interface SceneLayer {
+  source: SceneSource;
+  /** Where the source is drawn on the output */
+  rect: Region;
+  /** Part of the source to show; all of it when unset */
+  crop?: Region | null;
+  /** Layers with a higher index are drawn on top */
+  z_index?: number;
+  scaling?: 'fit' | 'fill' | 'stretch';
+}
+
+/** Several sources arranged on one output, recorded with the `scene` source type */
+export interface Scene {
+  width?: number;
+  height?: number;
+  fps?: number;
+  background?: [number, number, number, number];
+  layers: SceneLayer[];
+}
+
+/** Payload of `capture-sources-changed`; `sources` is the full list after the change */
+export interface CaptureSourcesChanged {
+  added: CaptureSource[];
+  removed: CaptureSource[];
+  renamed: { source: CaptureSource; old_name: string }[];
+  resized: { source: CaptureSource; old_width: number; old_height: number }[];
+  sources: CaptureSource[];
+}
+
+/** Payload of `recording-source-lost` */
+export interface SourceLost {
+  source_type: string;
+  handle: number;
+}
+
+/** Output extensions that record audio only, without capturing video */
+export const AUDIO_FILE_EXTENSIONS = ['wav', 'flac', 'opus', 'ogg'];
+
+export const isAudioFile = (path: string) =>
+  AUDIO_FILE_EXTENSIONS.some((extension) => path.toLowerCase().endsWith(`.${extension}`));
+
+/** GIFs are recorded to mp4 and converted once the recording is finished */
+export const isGifFile = (path: string) => path.toLowerCase().endsWith('.gif');
+
+export interface SourceThumbnail {
+  width: number;
+  height: number;
+  data_url: string;
+}
+
+export interface PreviewSettings {
+  fps?: number;
+  max_width?: number;
+  max_height?: number;
+}
+
+/** One frame sent over the `start_preview` channel */
+export interface PreviewFrame {
+  width: number;
+  height: number;
+  data_url: string;
+}
+
 export interface Region {
   x: number;
   y: number;
   width: number;
   height: number;
 }
+
+/** A saved region, checked against the monitors connected now */
+export interface RegionPresetStatus {
+  name: string;
+  monitor_id: string;
+  monitor_name: string;
+  region: Region;
+  /** HMONITOR of the preset's monitor; null when it isn't connected */
+  monitor_handle: number | null;
+  /** Why the preset can't be applied; null when it can */
+  invalid_reason: string | null;
+}
+
+/** A window the region selector can snap to, in physical pixels relative to its monitor */
+export interface SnapWindow extends Region {
+  hwnd: number;
+  title: string;
+}
+
+export type RedactionStyle =
+  | { type: 'pixelate'; block_size: number }
+  | { type: 'blur'; radius: number }
+  | { type: 'black_out' };
+
+export type RedactionTarget =
+  | { kind: 'rect'; region: Region }
+  | { kind: 'window_title'; pattern: string }
+  | { kind: 'process'; name: string };
+
+export interface RedactionRule {
+  target: RedactionTarget;
+  style?: RedactionStyle;
+}
+
+export interface CursorHighlight {
+  color: [number, number, number, number];
+  radius: number;
+}
+
+export interface CursorSettings {
+  hidden?: boolean;
+  scale?: number;
+  highlight?: CursorHighlight | null;
+  click_ripples?: boolean;
+}
+
+export type Corner = 'top_left' | 'top_right' | 'bottom_left' | 'bottom_right';
+
+export interface KeystrokeOverlaySettings {
+  corner?: Corner;
+  filter?: 'all' | 'modifier_combos';
+  display_ms?: number;
+  font_size?: number;
+  max_visible?: number;
+}
+
+export interface AutoZoomSettings {
+  output_width?: number;
+  output_height?: number;
+  zoom?: number;
+  click_zoom?: number;
+  click_hold_ms?: number;
+  transition_ms?: number;
+  easing?: 'linear' | 'ease_in' | 'ease_out' | 'ease_in_out';
+}
+
+export interface WebcamDevice {
+  id: string;
+  name: string;
+}
+
+/** Select value for the synthetic test pattern, which works without a camera */
+export const WEBCAM_TEST_PATTERN = 'pattern';
+
+export type WebcamSource = { type: 'device'; id: string } | { type: 'file'; path: string } | { type: 'pattern' };
+
+/** Payload of `webcam-failed`; the recording goes on without the webcam picture */
+export interface WebcamFailed {
+  source: WebcamSource;
+  error: string;
+}
+
+export interface WebcamOverlaySettings {
+  source?: WebcamSource;
+  corner?: Corner;
+  shape?: 'circle' | 'rounded_rect';
+  size?: number;
+  border_width?: number;
+  border_color?: [number, number, number, number];
+  mirror?: boolean;
+}
+
+export interface AudioDevice {
+  id: string;
+  name: string;
+  kind: 'output' | 'input';
+  is_default: boolean;
+  sample_rate: number;
+  channels: number;
+}
+
+/** Select value for whichever device the system currently uses by default */
+export const DEFAULT_AUDIO_DEVICE = 'default';
+
+/** Where an audio input comes from; a null `device_id` means the system default device */
+export type AudioSourceSettings =
+  | { type: 'loopback'; device_id: string | null }
+  | { type: 'microphone'; device_id: string | null }
+  | { type: 'sine'; frequency: number };
+
+export interface NoiseGateSettings {
+  threshold_db?: number;
+  range_db?: number;
+  attack_ms?: number;
+  hold_ms?: number;
+  release_ms?: number;
+}
+
+export interface NoiseSuppressionSettings {
+  reduction_db?: number;
+  strength?: number;
+}
+
+export interface CompressorSettings {
+  threshold_db?: number;
+  ratio?: number;
+  attack_ms?: number;
+  release_ms?: number;
+  makeup_db?: number;
+  limit_db?: number;
+}
+
+export interface LoudnessSettings {
+  target_lufs?: number;
+  max_gain_db?: number;
+  peak_db?: number;
+}
+
+/** Per-source processing; stages left null are skipped */
+export interface AudioFilterSettings {
+  noise_gate?: NoiseGateSettings | null;
+  noise_suppression?: NoiseSuppressionSettings | null;
+  compressor?: CompressorSettings | null;
+  loudness?: LoudnessSettings | null;
+}
+
+export interface AudioInput {
+  source: AudioSourceSettings;
+  gain?: number;
+  filters?: AudioFilterSettings;
+  silence_warning_secs?: number;
+}
+
+/** Payload of `audio-levels`, emitted about 20 times a second per source */
+export interface SourceLevel {
+  track: number;
+  source: AudioSourceSettings;
+  peak: number;
+  rms: number;
+}
+
+export interface DriftStats {
+  track: number;
+  source: AudioSourceSettings;
+  skew_ppm: number;
+  max_drift_ms: number;
+  final_drift_ms: number;
+  inserted_frames: number;
+  dropped_frames: number;
+}
+
+/** Payload of `recording-stats`, emitted when a recording finishes */
+export interface RecordingStats {
+  output_path: string;
+  video_frames: number;
+  duration_secs: number;
+  paused_secs: number;
+  audio_drift: DriftStats[];
+  /** Files holding each audio input on its own, when they were kept apart */
+  audio_tracks: string[];
+}
+
+/** Payload of `gif-exported`, emitted once a GIF recording has been converted */
+export interface GifExported {
+  output_path: string;
+  /** Why the conversion failed; the mp4 it was made from is kept */
+  error?: string | null;
+}
+
+/** Payload of `audio-silence-warning` */
+export interface SilenceWarning {
+  track: number;
+  source: AudioSourceSettings;
+  silent_secs: number;
+}
+
+/** What happens when the recorded window closes, resizes or is minimized */
+export interface SourceChangeSettings {
+  on_close?: 'finalize' | 'discard';
+  on_resize?: 'letterbox' | 'scale';
+  on_minimize?: 'placeholder' | 'pause';
+}
+
+/** Payload of `recording-source-closed`; `output_path` is null when the file was discarded */
+export interface SourceClosed {
+  output_path: string | null;
+}
+
+export interface RecordingOptions {
+  redactions?: RedactionRule[];
+  exclude_own_windows?: boolean;
+  cursor?: CursorSettings;
+  keystrokes?: KeystrokeOverlaySettings | null;
+  auto_zoom?: AutoZoomSettings | null;
+  webcam?: WebcamOverlaySettings | null;
+  source_changes?: SourceChangeSettings;
+  video?: VideoEncoderSettings;
+  /** With several audio inputs, also saves each one to its own file beside the recording */
+  separate_audio_tracks?: boolean;
+}
+
+/** Overrides for the video encoder; unset values keep its defaults */
+export interface VideoEncoderSettings {
+  /** Captures delivering more frames per second are thinned out */
+  frame_rate?: number | null;
+  /** Bits per second */
+  bitrate?: number | null;
+}
+
+/** What a profile records; sources are described rather than referred to by handle */
+export type ProfileSource =
+  | { type: 'monitor'; monitor_id?: string | null; region?: Region | null }
+  | { type: 'region_preset'; name: string }
+  | { type: 'window'; selector: WindowSelector }
+  | { type: 'desktop' }
+  | { type: 'scene'; scene: Scene }
+  | { type: 'audio' };
+
+/** Where a profile's recordings go; `{profile}`, `{date}` and `{time}` are filled into the pattern */
+export interface OutputNaming {
+  directory?: string;
+  pattern?: string;
+  extension?: string;
+}
+
+/** Settings saved under a name and passed to `start_recording` as `profile` */
+export interface RecordingProfile {
+  name: string;
+  source: ProfileSource;
+  options?: RecordingOptions;
+  audio?: AudioInput[];
+  output?: OutputNaming;
+}
+
+/** Saved settings, as returned by `get_settings` and sent with `settings-changed` */
+export interface Settings {
+  output: { path: string };
+  /** The last source recorded, found again by name since handles change between runs */
+  source: {
+    source_type: string | null;
+    name: string | null;
+    capture_mode: 'full' | 'custom';
+    region: Region | null;
+  };
+  /** A null device means the system default */
+  audio: {
+    system_audio: boolean;
+    system_device: string | null;
+    microphone: boolean;
+    microphone_device: string | null;
+  };
+  webcam: { enabled: boolean; device: string | null };
+  /** Used for recordings that don't pass options of their own */
+  recording: RecordingOptions;
+}
+
+/** Partial settings for `update_settings`; nested objects are merged key by key */
+export type SettingsChanges = { [K in keyof Settings]?: Partial<Settings[K]> };